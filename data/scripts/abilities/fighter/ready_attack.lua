@@ -0,0 +1,18 @@
+function on_activate(parent, ability)
+  local cb = ability:create_callback(parent)
+  cb:set_on_reaction_fn("on_reaction")
+
+  -- reserves the AP for the attack until our next turn
+  local ap = parent:stats().attack_cost
+  if not parent:ready_reaction(cb, "EnterThreat", ap) then
+    game:say_line("Not enough AP to ready an attack.", parent)
+    return
+  end
+
+  ability:activate(parent)
+end
+
+function on_reaction(parent, ability, targets)
+  local target = targets:first()
+  parent:anim_weapon_attack(target)
+end
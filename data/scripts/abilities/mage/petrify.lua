@@ -29,9 +29,7 @@ function on_target_select(parent, ability, targets)
   
   local effect = target:create_effect(ability:name(), duration)
   effect:set_tag("petrify")
-  effect:add_move_disabled()
-  effect:add_attack_disabled()
-  effect:add_abilities_disabled()
+  effect:set_condition("petrified")
   effect:add_num_bonus("ap", -8 * game:ap_display_factor())
   effect:add_num_bonus("defense", -30 - stats.caster_level)
   effect:add_num_bonus("fortitude", -20 - stats.caster_level)
@@ -36,9 +36,9 @@ function after_attack(parent, ability, targets, hit)
   local stats = parent:stats()
   local amount = 1 + (stats.level / 8 + stats.intellect_bonus / 8) * factor
 
-  target:remove_effects_with_tag("disease")
   local effect = target:create_effect(ability:name())
   effect:set_tag("disease")
+  effect:set_stacking("highest_wins", amount)
   
   effect:add_attribute_bonus("Strength", -amount)
   effect:add_attribute_bonus("Dexterity", -amount)
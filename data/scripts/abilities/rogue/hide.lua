@@ -2,11 +2,6 @@ function on_activate(parent, ability)
   activate_no_check(parent, ability)
   
   ability:activate(parent)
-  
-  if check_spotted(parent, ability) then
-    game:say_line("Failed to hide", parent)
-	ability:deactivate(parent)
-  end
 end
 
 function on_deactivate(parent, ability)
@@ -22,11 +17,11 @@ function activate_no_check(parent, ability)
   local effect = parent:create_effect(ability:name())
   effect:deactivate_with(ability)
   effect:add_hidden()
+  effect:add_num_bonus("stealth", hide_level(parent, ability))
 
   local cb = ability:create_callback(parent)
-  cb:set_on_round_elapsed_fn("on_round_elapsed")
   cb:set_after_attack_fn("after_attack")
-  cb:set_on_moved_fn("on_moved")
+  cb:set_on_spotted_fn("on_spotted")
   effect:add_callback(cb)
   
   local anim = parent:create_color_anim()
@@ -40,23 +35,15 @@ function activate_no_check(parent, ability)
   game:play_sfx("sfx/rustle10")
 end
 
-function on_moved(parent, ability)
-  if check_spotted(parent, ability) then
-    game:say_line("Spotted!", parent)
-	ability:deactivate(parent)
-	game:cancel_blocking_anims()
-	game:run_script_delayed("hide", "check_ai_activation", 0.1)
-	game:play_sfx("sfx/rustle12")
-  end
-end
+-- called when a hostile spots the parent with a perception roll
+function on_spotted(parent, ability, targets)
+  if parent:has_effect_with_tag("unspottable") then return end
 
-function on_round_elapsed(parent, ability)
-  if check_spotted(parent, ability) then
-    game:say_line("Spotted!", parent)
-	ability:deactivate(parent)
-	game:run_script_delayed("hide", "check_ai_activation", 0.1)
-	game:play_sfx("sfx/rustle12")
-  end
+  game:say_line("Spotted!", parent)
+  ability:deactivate(parent)
+  game:cancel_blocking_anims()
+  game:run_script_delayed("hide", "check_ai_activation", 0.1)
+  game:play_sfx("sfx/rustle12")
 end
 
 function check_ai_activation(parent)
@@ -80,30 +67,12 @@ function after_attack(parent, ability)
   game:run_script_delayed("hide", "check_ai_activation", 0.1)
 end
 
-function check_spotted(parent, ability)
-  if parent:has_effect_with_tag("unspottable") then return end
-
-  local stats = parent:stats()
-  local parent_concealment = stats.concealment
-  local parent_hide_level = 15 + parent:ability_level(ability) * 20 + stats.level * 2
-  local break_even_distance = 5
+-- the stealth bonus while hidden.  hostiles roll their perception against
+-- this each round, with the roll rules defined in rules.yml
+function hide_level(parent, ability)
+  local level = 25 + parent:ability_level(ability) * 20 + parent:stats().level * 2
   if parent:has_ability("hide_in_plain_sight") then
-    break_even_distance = 0
+    level = level + 50
   end
-  
-  local targets = parent:targets():hostile():visible()
-  targets = targets:to_table()
-  for i = 1, #targets do
-    local target = targets[i]
-	
-	local target_perception = target:stats().perception * 5
-	local dist = (target:dist_to_entity(parent) - break_even_distance) * 10
-	
-	-- game:log("hide check with " .. parent_hide_level .. " + " .. parent_concealment .. " + " .. dist .. " vs " .. target_perception)
-	if parent_hide_level + parent_concealment + dist - target_perception < 0 then
-	  return true
-	end
-  end
-  
-  return false
+  return level
 end
@@ -66,7 +66,7 @@ function on_entered(parent, ability, targets)
   end
   
   local effect = target:create_effect(ability:name(), duration)
-  effect:add_move_disabled()
+  effect:set_condition("immobilized")
   effect:set_tag("stuck")
   
   local anim = target:create_particle_generator("particles/circle4")
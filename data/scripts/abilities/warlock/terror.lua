@@ -28,7 +28,7 @@ function on_target_select(parent, ability, targets)
   
   local effect = target:create_effect(ability:name(), duration)
   effect:set_tag("fear")
-  effect:add_attack_disabled()
+  effect:set_condition("frightened")
   effect:add_num_bonus("will", -20)
   
   local gen = target:create_anim("terror")
@@ -3,6 +3,7 @@ MOVE_THRESHOLD = 0.1
 HEALING_FRAC = 0.5
 WAIT_TIME = 10
 MAX_MOVE_LEN = 60
+FOCUS_FIRE_FACTOR = 2.0
 
 -- This AI reads the following params
 -- AttackWhenHasAbilitiesChance value from 0 to 100.  Percent chance to use a standard attack
@@ -13,6 +14,8 @@ MAX_MOVE_LEN = 60
 -- move closer to targets even if they cannot directly attack, up to the specified distance
 -- multiplied by the parent size.  This normally will make it easy
 -- for the player to dispatch them with area of effect attacks.
+--
+-- The tactics of the parent's AI group, configured on its encounter, are also followed.
 
 function ai_action(parent, params)
     -- set default value of 0 for all params
@@ -32,12 +35,19 @@ function ai_action(parent, params)
     game:log("  Got " .. tostring(hostiles:num_targets()) .. " hostiles")
     game:log("  Got " .. tostring(#friendlies) .. " friendlies")
 
-    if parent:has_effect_with_tag("fear") then
+    if parent:has_effect_with_tag("fear") or parent:condition_ai_hint() == "Flee" then
         game:log("  Running away due to fear")
         attempt_run_away(parent, hostiles:visible():to_table())
         return end_turn(parent)
     end
 
+    local group = parent:ai_group()
+    if group ~= nil and group:is_retreating() then
+        game:log("  Running away with AI group")
+        attempt_run_away(parent, hostiles:visible():to_table())
+        return end_turn(parent)
+    end
+
     if check_swap_weapons_to_melee(parent, hostiles).done then
         return parent:state_wait(WAIT_TIME)
     end
@@ -53,6 +63,17 @@ function ai_action(parent, params)
     weights[parent:id()] = compute_weight(parent, parent)
     precompute_weights(parent, hostiles, weights)
     precompute_weights(parent, friendlies, weights)
+    apply_focus_target(group, weights)
+
+    local decisions = new_decisions(weights, hostiles, friendlies)
+    local state = choose_action(parent, params, group, items, abilities, hostiles, friendlies,
+        weights, decisions)
+    finish_decisions(decisions)
+    return state
+end
+
+function choose_action(parent, params, group, items, abilities, hostiles, friendlies,
+    weights, decisions)
 
     local failed_use_count = 0
 
@@ -68,7 +89,7 @@ function ai_action(parent, params)
     -- only check items and abilities at most 10 times
     for i = 1,10 do
         local result = find_and_use_item(parent, items, hostiles, friendlies,
-            failed_use_count, weights)
+            failed_use_count, weights, decisions)
         if result.done then
             game:log("  Item used or moved")
             return parent:state_wait(WAIT_TIME)
@@ -79,7 +100,7 @@ function ai_action(parent, params)
         end
 
         local result = find_and_use_ability(parent, params, abilities, hostiles,
-            friendlies, failed_use_count, weights)
+            friendlies, failed_use_count, weights, decisions)
         if result.done then
             game:log("  Ability used or moved")
             return parent:state_wait(WAIT_TIME)
@@ -123,15 +144,18 @@ function ai_action(parent, params)
             game:log("  Checking for attack against " .. target:id())
 
             local result = check_move_for_attack(parent, target, retry)
+            record_decision(decisions, "Attack", target, nil, result.attack)
             if result.attack then
                 game:log("  Perform attack")
                 parent:anim_weapon_attack(target, nil, true)
                 parent:clear_flag("ai_force_attack")
+                update_focus_target(parent, group, target)
 
                 return parent:state_wait(WAIT_TIME)
             end
 
             if result.moved then
+                record_decision(decisions, "Move", target, nil, true)
                 game:log("  Moved.")
                 return parent:state_wait(WAIT_TIME)
             end
@@ -196,6 +220,10 @@ function check_move_for_attack(parent, target, attempt)
             return { attack=true }
         end
 
+        if attempt == 0 and move_to_flank(parent, target) then
+            return { attack=false, moved=true }
+        end
+
         local increase = attempt * math.max(parent:width(), parent:height())
 
         game:log("    Attempt move towards target")
@@ -239,10 +267,38 @@ function check_move_for_attack(parent, target, attempt)
             return { attack=false, moved=true }
         end
 
+        if move_behind_melee(parent, target) then
+            return { attack=false, moved=true }
+        end
+
         return { attack=true }
     end
 end
 
+-- move to a position flanking the target, if the parent's AI group flanks
+function move_to_flank(parent, target)
+    local group = parent:ai_group()
+    if group == nil then return false end
+
+    local point = group:flank_point(parent, target)
+    if point == nil then return false end
+
+    game:log("    Moving to flank at " .. tostring(point.x) .. "," .. tostring(point.y))
+    return parent:move_towards_point(point.x, point.y)
+end
+
+-- move a ranged parent back behind the melee members of its AI group
+function move_behind_melee(parent, target)
+    local group = parent:ai_group()
+    if group == nil then return false end
+
+    local point = group:ranged_point(parent, target)
+    if point == nil then return false end
+
+    game:log("    Moving behind melee to " .. tostring(point.x) .. "," .. tostring(point.y))
+    return parent:move_towards_point(point.x, point.y)
+end
+
 function check_swap_weapons_to_ranged(parent)
     if parent:stats().attack_is_ranged then
         return { swapped=false }
@@ -284,21 +340,27 @@ function check_swap_weapons_to_melee(parent, hostiles)
     return { done=false }
 end
 
-function find_and_use_item(parent, items, hostiles, friendlies, failed_use_count, weights)
+function find_and_use_item(parent, items, hostiles, friendlies, failed_use_count, weights,
+    decisions)
     for i = 1, #items do
         local item = items[i]
 
         game:log("    Checking item " .. item:name())
         local result = check_action(parent, item:ai_data(), hostiles, friendlies, failed_use_count)
         if result.done then
+            record_decision(decisions, "Move for " .. item:name(), result.considered, nil, true)
             return { done=true }
         end
 
-        if result.target then
+        if not result.target then
+            record_decision(decisions, item:name(), result.considered, nil, false)
+        else
             game:log("      Use item")
             parent:use_item(item)
-            local result = handle_targeter(parent, result.target, item, item:ai_data(),
+            local target = result.target
+            local result = handle_targeter(parent, target, item, item:ai_data(),
                 hostiles, friendlies, weights)
+            record_decision(decisions, item:name(), target, nil, result.done)
 
             if result.done then
                 return { done=true }
@@ -310,7 +372,7 @@ function find_and_use_item(parent, items, hostiles, friendlies, failed_use_count
 end
 
 function find_and_use_ability(parent, params, abilities, hostiles, friendlies,
-    failed_use_count, weights)
+    failed_use_count, weights, decisions)
 
     local abilities_table = abilities:to_table()
     for i = 1, #abilities_table do
@@ -328,16 +390,23 @@ function find_and_use_ability(parent, params, abilities, hostiles, friendlies,
         game:log("    Checking ability " .. ability:name())
         local result = check_action(parent, ai_data, hostiles, friendlies, failed_use_count)
         if result.done then
+            record_decision(decisions, "Move for " .. ability:name(), result.considered,
+                ai_data.priority, true)
             return { done=true }
         end
 
-        if result.target then
+        if not result.target then
+            record_decision(decisions, ability:name(), result.considered, ai_data.priority, false)
+        else
             game:log("      Use ability")
             parent:use_ability(ability)
-            local result = handle_targeter(parent, result.target, ability, ai_data,
+            local target = result.target
+            local result = handle_targeter(parent, target, ability, ai_data,
                 hostiles, friendlies, weights)
+            record_decision(decisions, ability:name(), target, ai_data.priority, result.done)
 
             if result.done then
+                update_focus_target(parent, parent:ai_group(), target)
                 return { done=true }
             end
         end
@@ -574,10 +643,10 @@ end
 
 function check_move_towards(parent, target, dist)
     if parent:move_towards_entity(target, dist, MAX_MOVE_LEN) then
-        return { done=true }
+        return { done=true, considered=target }
     else
         game:log("      Unable to path towards " .. target:id())
-        return { done=false }
+        return { done=false, considered=target }
     end
 end
 
@@ -666,6 +735,57 @@ function sort_attack_targets(parent, hostiles, weights)
     return out
 end
 
+-- Tracks the actions recorded for the AI decision debugger this turn, so
+-- that every weighed target is recorded once the decision is finished
+function new_decisions(weights, hostiles, friendlies)
+    return { weights=weights, hostiles=hostiles, friendlies=friendlies, recorded={} }
+end
+
+function record_decision(decisions, action, target, priority, chosen)
+    local score = nil
+    if target ~= nil then
+        score = decisions.weights[target:id()]
+        decisions.recorded[target:id()] = true
+    end
+    game:record_ai_decision(action, target, score, priority, chosen)
+end
+
+-- records the targets that were weighed but never tried
+function finish_decisions(decisions)
+    local groups = { decisions.hostiles, decisions.friendlies }
+    for _, targets in ipairs(groups) do
+        for i = 1, #targets do
+            local target = targets[i]
+            if not decisions.recorded[target:id()] then
+                decisions.recorded[target:id()] = true
+                record_decision(decisions, "Weighed", target, nil, false)
+            end
+        end
+    end
+end
+
+-- prefer the target the parent's AI group is focusing on
+function apply_focus_target(group, weights)
+    if group == nil then return end
+
+    local focus = group:focus_target()
+    if focus == nil then return end
+
+    local weight = weights[focus:id()]
+    if weight ~= nil and weight < 0 then
+        weights[focus:id()] = weight * FOCUS_FIRE_FACTOR
+    end
+end
+
+-- make the target the AI group's focus, if it focuses fire and has no target yet
+function update_focus_target(parent, group, target)
+    if group == nil or not group:focus_fire() then return end
+    if group:focus_target() ~= nil then return end
+    if not parent:is_hostile(target) then return end
+
+    group:set_focus_target(target)
+end
+
 function precompute_weights(parent, targets, weights)
     for i = 1, #targets do
         local target = targets[i]
@@ -0,0 +1,397 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+//! Runs the game simulation without a window, display, or audio device.
+//! Party members take their combat turns using their actor's AI template, so
+//! encounters can be scripted and run to completion without any input.
+//!
+//! The process exit code reflects the outcome of the simulation:
+//! 0 for a normal completion, 1 for a fatal error, 2 if the party was
+//! defeated or a game over was triggered, and 3 if combat was still active
+//! when the frame limit was reached.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+
+use log::{error, info, warn};
+
+use sulis_core::config::Config;
+use sulis_core::io::{ControlFlowUpdater, System};
+use sulis_core::resource::ResourceSet;
+use sulis_core::ui::Widget;
+use sulis_core::util;
+use sulis_module::{on_trigger::QuestStateData, ItemState, Module, OnTrigger};
+use sulis_state::{
+    save_file,
+    script::{entity_with_id, ScriptEntity},
+    EntityState, GameState, Script, UICallback,
+};
+
+const USAGE: &str = "\
+Usage: headless --campaign <id> (--pc <actor> | --load <save file>) [options]
+
+Options:
+    --campaign <id>         ID or directory of the campaign to load
+    --pc <actor>            ID of the actor to use as the PC in a new game
+    --party <actor>         ID of an actor to add to the party in a new game.
+                            May be specified more than once
+    --load <save file>      Path to a save file to load instead of a new game
+    --script <id:func>      Trigger script function to fire once the game has
+                            been set up.  May be specified more than once
+    --frames <count>        Maximum number of frames to simulate [18000]
+    --frame-millis <millis> Simulated time elapsed in each frame [33]
+    --stop-after-combat     Stop as soon as the first combat has ended
+    --seed <seed>           Seed for combat rolls.  When loading a save, the
+                            saved combat random state is used by default,
+                            unless the campaign reseeds it on load
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Complete,
+    Defeat,
+    Timeout,
+}
+
+impl Outcome {
+    fn exit_code(self) -> i32 {
+        match self {
+            Outcome::Complete => 0,
+            Outcome::Defeat => 2,
+            Outcome::Timeout => 3,
+        }
+    }
+}
+
+struct Options {
+    campaign: String,
+    pc: Option<String>,
+    party: Vec<String>,
+    load: Option<String>,
+    scripts: Vec<(String, String)>,
+    frames: u32,
+    frame_millis: u32,
+    stop_after_combat: bool,
+    seed: Option<u128>,
+}
+
+impl Options {
+    fn parse(args: Vec<String>) -> Result<Options, String> {
+        let mut options = Options {
+            campaign: String::new(),
+            pc: None,
+            party: Vec::new(),
+            load: None,
+            scripts: Vec::new(),
+            frames: 18000,
+            frame_millis: 33,
+            stop_after_combat: false,
+            seed: None,
+        };
+
+        let mut iter = args.into_iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--stop-after-combat" => options.stop_after_combat = true,
+                "--campaign" => options.campaign = value(&arg, iter.next())?,
+                "--pc" => options.pc = Some(value(&arg, iter.next())?),
+                "--party" => options.party.push(value(&arg, iter.next())?),
+                "--load" => options.load = Some(value(&arg, iter.next())?),
+                "--script" => {
+                    let script = value(&arg, iter.next())?;
+                    let mut split = script.splitn(2, ':');
+                    match (split.next(), split.next()) {
+                        (Some(id), Some(func)) => {
+                            options.scripts.push((id.to_string(), func.to_string()))
+                        }
+                        _ => return Err(format!("Invalid script '{}', expected id:func", script)),
+                    }
+                }
+                "--frames" => options.frames = parse_num(&arg, iter.next())?,
+                "--frame-millis" => options.frame_millis = parse_num(&arg, iter.next())?,
+                "--seed" => {
+                    let seed = value(&arg, iter.next())?;
+                    match seed.parse::<u128>() {
+                        Ok(seed) => options.seed = Some(seed),
+                        Err(_) => return Err(format!("Invalid value '{}' for '{}'", seed, arg)),
+                    }
+                }
+                _ => return Err(format!("Unknown argument '{}'", arg)),
+            }
+        }
+
+        if options.campaign.is_empty() {
+            return Err("A campaign must be specified".to_string());
+        }
+
+        if options.pc.is_some() == options.load.is_some() {
+            return Err("Exactly one of --pc or --load must be specified".to_string());
+        }
+
+        if options.frame_millis == 0 {
+            return Err("--frame-millis must be greater than zero".to_string());
+        }
+
+        Ok(options)
+    }
+}
+
+fn value(arg: &str, value: Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| format!("Missing value for '{}'", arg))
+}
+
+fn parse_num(arg: &str, val: Option<String>) -> Result<u32, String> {
+    let val = value(arg, val)?;
+    val.parse::<u32>()
+        .map_err(|_| format!("Invalid value '{}' for '{}'", val, arg))
+}
+
+struct HeadlessControlFlowUpdater {
+    root: Rc<RefCell<Widget>>,
+    stop_after_combat: bool,
+    combat_seen: bool,
+    outcome: Rc<RefCell<Option<Outcome>>>,
+}
+
+impl ControlFlowUpdater for HeadlessControlFlowUpdater {
+    fn update(&mut self, millis: u32) -> Rc<RefCell<Widget>> {
+        if let Some(cb) = GameState::update(millis) {
+            self.activate(cb);
+        }
+
+        if GameState::party().iter().all(|e| e.borrow().actor.is_dead()) {
+            info!("All party members are dead.");
+            self.set_outcome(Outcome::Defeat);
+        }
+
+        if GameState::is_combat_active() {
+            self.combat_seen = true;
+        } else if self.stop_after_combat && self.combat_seen {
+            info!("Combat has ended.");
+            self.set_outcome(Outcome::Complete);
+        }
+
+        self.root()
+    }
+
+    fn recreate_window(&mut self) -> bool {
+        false
+    }
+
+    fn root(&self) -> Rc<RefCell<Widget>> {
+        Rc::clone(&self.root)
+    }
+
+    fn is_exit(&self) -> bool {
+        self.outcome.borrow().is_some()
+    }
+}
+
+impl HeadlessControlFlowUpdater {
+    fn set_outcome(&self, outcome: Outcome) {
+        let mut cur = self.outcome.borrow_mut();
+        if cur.is_none() {
+            *cur = Some(outcome);
+        }
+    }
+
+    /// Handles the subset of triggers that only modify the game state.  Triggers
+    /// that would show a window or otherwise require player input are skipped.
+    fn activate(&self, cb: UICallback) {
+        let pc = &cb.parent;
+        let target = &cb.target;
+
+        use OnTrigger::*;
+        for trigger in cb.on_trigger.iter() {
+            match trigger {
+                PlayerCoins(amount) => GameState::add_party_coins(*amount),
+                PartyMember(ref id) => match entity_with_id(id.to_string()) {
+                    None => warn!("Unable to add party member '{}'", id),
+                    Some(entity) => GameState::add_party_member(entity, true),
+                },
+                PartyItem(ref id) => match ItemState::from(id) {
+                    None => warn!("Unable to add item '{}'", id),
+                    Some(item) => {
+                        GameState::party_stash().borrow_mut().add_item(1, item);
+                    }
+                },
+                TargetNumFlag(ref data) => target.borrow_mut().add_num_flag(&data.flag, data.val),
+                PlayerNumFlag(ref data) => pc.borrow_mut().add_num_flag(&data.flag, data.val),
+                NotTargetNumFlag(ref data) => target.borrow_mut().clear_custom_flag(&data.flag),
+                NotPlayerNumFlag(ref data) => pc.borrow_mut().clear_custom_flag(&data.flag),
+                NotTargetFlag(ref flag) => target.borrow_mut().clear_custom_flag(flag),
+                NotPlayerFlag(ref flag) => pc.borrow_mut().clear_custom_flag(flag),
+                TargetFlag(ref flag) => target.borrow_mut().set_custom_flag(flag, "true"),
+                PlayerFlag(ref flag) => pc.borrow_mut().set_custom_flag(flag, "true"),
+                FireScript(ref script) => fire_script(&script.id, &script.func, pc, target),
+                QuestState(ref data) => set_quest_state(data),
+                SayLine(ref line) => {
+                    info!("'{}' says: {}", target.borrow().actor.actor.name, line);
+                }
+                GameOverWindow(ref text) => {
+                    info!("Game over: {}", text);
+                    self.set_outcome(Outcome::Defeat);
+                }
+                ExitToMenu => self.set_outcome(Outcome::Complete),
+                _ => {
+                    info!("Skipping trigger requiring player input: {:?}", trigger);
+                }
+            }
+        }
+    }
+}
+
+fn set_quest_state(data: &QuestStateData) {
+    match data.entry {
+        Some(ref entry) => GameState::set_quest_entry_state(
+            data.quest.to_string(),
+            entry.to_string(),
+            data.state,
+        ),
+        None => GameState::set_quest_state(data.quest.to_string(), data.state),
+    }
+}
+
+fn fire_script(
+    script_id: &str,
+    func: &str,
+    parent: &Rc<RefCell<EntityState>>,
+    target: &Rc<RefCell<EntityState>>,
+) {
+    Script::trigger(
+        script_id,
+        func,
+        (ScriptEntity::from(parent), ScriptEntity::from(target)),
+    );
+}
+
+fn load_resources(campaign: &str) -> Result<(), std::io::Error> {
+    let dirs = vec![Config::resources_config().directory, Module::campaign_dir(campaign)];
+    info!("Reading resources from '{:?}'", dirs);
+
+    let yaml = ResourceSet::load_resources(dirs.clone())?;
+    Module::load_resources(yaml, dirs)
+}
+
+fn init_game_state(options: &Options) -> Result<(), std::io::Error> {
+    if let Some(ref path) = options.load {
+        info!("Loading game state from '{}'", path);
+        let save_state = save_file::load_state_path(Path::new(path))?;
+        return GameState::load(save_state);
+    }
+
+    let pc_id = options.pc.as_ref().unwrap();
+    let pc_actor = match Module::actor(pc_id) {
+        None => return util::invalid_data_error(&format!("No actor with ID '{}'", pc_id)),
+        Some(actor) => actor,
+    };
+
+    let mut party_actors = Vec::new();
+    for id in options.party.iter() {
+        match Module::actor(id) {
+            None => return util::invalid_data_error(&format!("No actor with ID '{}'", id)),
+            Some(actor) => party_actors.push(actor),
+        }
+    }
+
+    info!("Initializing game state.");
+    GameState::init(pc_actor, party_actors, HashMap::new())
+}
+
+fn print_report(outcome: Outcome) {
+    let mgr = GameState::turn_manager();
+    let mgr = mgr.borrow();
+    let time = mgr.current_time();
+
+    println!("outcome: {:?}", outcome);
+    println!("elapsed_millis: {}", mgr.total_elapsed_millis());
+    println!(
+        "time: day {}, hour {}, round {}",
+        time.day, time.hour, time.round
+    );
+    println!("area: {}", GameState::area_state().borrow().area.area.id);
+    println!("combat_active: {}", mgr.is_combat_active());
+    println!("party:");
+    for member in GameState::party() {
+        let member = member.borrow();
+        println!(
+            "  - {}: {}/{} hp{}",
+            member.actor.actor.id,
+            member.actor.hp(),
+            member.actor.stats.max_hp,
+            if member.actor.is_dead() { ", dead" } else { "" }
+        );
+    }
+}
+
+fn main() {
+    let options = match Options::parse(std::env::args().skip(1).collect()) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprint!("{}", USAGE);
+            std::process::exit(1);
+        }
+    };
+
+    let _logger_handle = util::setup_logger();
+    info!("=========Initializing Headless=========");
+
+    if let Err(e) = load_resources(&options.campaign) {
+        error!("{}", e);
+        util::error_and_exit("Fatal error reading resources.");
+    }
+
+    if let Err(e) = init_game_state(&options) {
+        error!("{}", e);
+        util::error_and_exit("There was a fatal error creating the game state.");
+    }
+
+    GameState::set_party_ai_enabled(true);
+    GameState::set_autosave_enabled(false);
+    if let Some(seed) = options.seed {
+        GameState::set_combat_seed(seed);
+    }
+    info!("Combat seed: {}", GameState::combat_rand().seed());
+
+    let pc = GameState::player();
+    for (id, func) in options.scripts.iter() {
+        info!("Firing script '{}:{}'", id, func);
+        fire_script(id, func, &pc, &pc);
+    }
+
+    let outcome = Rc::new(RefCell::new(None));
+    let updater = HeadlessControlFlowUpdater {
+        root: Widget::empty("headless"),
+        stop_after_combat: options.stop_after_combat,
+        combat_seen: false,
+        outcome: Rc::clone(&outcome),
+    };
+
+    let system = System::create_headless(options.frame_millis, options.frames);
+    system.main_loop(Box::new(updater));
+
+    let outcome = match *outcome.borrow() {
+        Some(outcome) => outcome,
+        None if GameState::is_combat_active() => Outcome::Timeout,
+        None => Outcome::Complete,
+    };
+
+    print_report(outcome);
+    std::process::exit(outcome.exit_code());
+}
@@ -16,18 +16,20 @@
 
 #![windows_subsystem = "windows"]
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
 use std::rc::Rc;
 use std::cell::RefCell;
 
-use log::{error, info};
+use log::{error, info, warn};
 
+use sulis_core::config::Config;
 use sulis_core::resource::ResourceSet;
-use sulis_core::io::{DisplayConfiguration, System, ControlFlowUpdater};
+use sulis_core::io::{DisplayConfiguration, System, ControlFlowUpdater, InputAction, InputActionKind};
 use sulis_core::ui::{self, Cursor, Widget};
 use sulis_core::util::{self, ActiveResources};
 use sulis_module::{Actor, Module};
-use sulis_state::{GameState, NextGameStep, SaveState};
+use sulis_state::{GameState, NextGameStep, SaveState, replay::{Replay, ReplayFrame}};
 use sulis_view::{main_menu::{self, MainMenu}, RootView, trigger_activator};
 
 struct GameControlFlowUpdater {
@@ -39,6 +41,7 @@ struct GameControlFlowUpdater {
     exit: bool,
 
     next_step: Option<NextGameStep>,
+    replay_mode: ReplayMode,
 }
 
 #[derive(Clone)]
@@ -47,12 +50,27 @@ enum UiMode {
     Game(Rc<RefCell<RootView>>),
 }
 
+#[allow(clippy::large_enum_variant)]
+enum ReplayMode {
+    // input is handled normally
+    Off,
+
+    // input is handled normally, and also recorded along with the
+    // frame timing
+    Record { replay: Replay, actions: Vec<InputAction> },
+
+    // live input is ignored and the recorded input and frame timing
+    // is played back instead
+    Play { frames: VecDeque<ReplayFrame> },
+}
+
 impl ControlFlowUpdater for GameControlFlowUpdater {
     fn update(&mut self, millis: u32) -> Rc<RefCell<Widget>> {
         if let Some(step) = self.next_step.take() {
             self.handle_next_step(step);
         }
 
+        let millis = self.update_replay(millis);
         self.update_mode(millis);
 
         if let Err(e) = Widget::update(&self.root, millis) {
@@ -78,6 +96,19 @@ impl ControlFlowUpdater for GameControlFlowUpdater {
     fn is_exit(&self) -> bool {
         self.exit
     }
+
+    fn handle_action(&mut self, action: InputAction) {
+        match self.replay_mode {
+            ReplayMode::Off => (),
+            ReplayMode::Record { ref mut actions, .. } => actions.push(action),
+            ReplayMode::Play { .. } => {
+                // still allow the window to be closed during playback
+                if !matches!(action.kind, InputActionKind::Exit) { return; }
+            }
+        }
+
+        action.handle(&self.root);
+    }
 }
 
 impl GameControlFlowUpdater {
@@ -96,6 +127,71 @@ impl GameControlFlowUpdater {
             mode: UiMode::MainMenu(view),
             exit: false,
             next_step: None,
+            replay_mode: ReplayMode::Off,
+        }
+    }
+
+    fn start_replay(&mut self, replay: Replay) {
+        let Replay { save_state, frames, .. } = replay;
+        self.load_campaign(save_state);
+
+        let count: u32 = frames.iter().map(|frame| frame.frame_count()).sum();
+        info!("Playing back {} recorded frames.", count);
+        GameState::set_autosave_enabled(false);
+        self.replay_mode = ReplayMode::Play { frames };
+    }
+
+    fn start_recording(&mut self) {
+        self.finish_recording();
+        if !Config::debug().record_input { return; }
+
+        info!("Recording input.");
+        self.replay_mode = ReplayMode::Record { replay: Replay::create(), actions: Vec::new() };
+    }
+
+    fn finish_recording(&mut self) {
+        let replay_mode = std::mem::replace(&mut self.replay_mode, ReplayMode::Off);
+        if let ReplayMode::Record { mut replay, .. } = replay_mode {
+            match replay.write() {
+                Ok(path) => info!("Wrote input recording to '{:?}'", path),
+                Err(e) => {
+                    warn!("Unable to write input recording");
+                    warn!("{}", e);
+                }
+            }
+        }
+    }
+
+    // Records or plays back the input for this frame as needed, returning
+    // the elapsed millis the frame should be updated with
+    fn update_replay(&mut self, millis: u32) -> u32 {
+        match self.replay_mode {
+            ReplayMode::Off => millis,
+            ReplayMode::Record { ref mut replay, ref mut actions } => {
+                replay.push_frame(millis, std::mem::take(actions));
+                millis
+            }
+            ReplayMode::Play { ref mut frames } => {
+                let frame = match frames.front_mut() {
+                    None => {
+                        info!("Replay complete.  Returning control to the player.");
+                        GameState::set_autosave_enabled(true);
+                        self.replay_mode = ReplayMode::Off;
+                        return millis;
+                    }
+                    Some(frame) => frame,
+                };
+
+                if let Some(millis) = frame.pop_skipped() {
+                    return millis;
+                }
+
+                let frame = frames.pop_front().unwrap();
+                for action in frame.actions {
+                    action.handle(&self.root);
+                }
+                frame.millis
+            }
         }
     }
 
@@ -118,6 +214,7 @@ impl GameControlFlowUpdater {
         let view = RootView::new();
         self.root = ui::create_ui_tree(view.clone());
         self.mode = UiMode::Game(view);
+        self.start_recording();
     }
 
     fn load_campaign(&mut self, save_state: SaveState) {
@@ -130,10 +227,19 @@ impl GameControlFlowUpdater {
         let view = RootView::new();
         self.root = ui::create_ui_tree(view.clone());
         self.mode = UiMode::Game(view);
+        self.start_recording();
     }
 
     fn handle_next_step(&mut self, step: NextGameStep) {
         use NextGameStep::*;
+        // every step leaves the current game, if any
+        self.finish_recording();
+        if let ReplayMode::Play { .. } = self.replay_mode {
+            info!("Replay stopped.  Returning control to the player.");
+            GameState::set_autosave_enabled(true);
+            self.replay_mode = ReplayMode::Off;
+        }
+
         match step {
             Exit => {
                 self.exit = true;
@@ -195,9 +301,11 @@ fn create_io() -> System {
 }
 
 fn load_resources() {
-    let start = std::time::Instant::now();
+    load_active_resources(&ActiveResources::read());
+}
 
-    let active = ActiveResources::read();
+fn load_active_resources(active: &ActiveResources) {
+    let start = std::time::Instant::now();
 
     let dirs = active.directories();
 
@@ -226,6 +334,34 @@ fn load_resources() {
     info!("Loaded all resources in {}s", util::format_elapsed_secs(start.elapsed()));
 }
 
+// Reads the replay file specified with the --replay <file> argument, if any
+fn read_replay_arg() -> Option<Replay> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg != "--replay" { continue; }
+
+        let path = match args.next() {
+            None => {
+                util::error_and_exit("No replay file specified for '--replay'.");
+                unreachable!();
+            }
+            Some(path) => path,
+        };
+
+        info!("Reading replay from '{}'", path);
+        match Replay::load(Path::new(&path)) {
+            Err(e) => {
+                error!("{}", e);
+                util::error_and_exit("Unable to read the replay file.");
+                unreachable!();
+            }
+            Ok(replay) => return Some(replay),
+        }
+    }
+
+    None
+}
+
 fn main() {
     // CONFIG will be lazily initialized here; if it fails it
     // prints an error and exits.  Don't drop the returned handle
@@ -234,10 +370,17 @@ fn main() {
     info!("=========Initializing=========");
     info!("Setup Logger and read configuration from 'config.yml'");
 
-    load_resources();
+    let replay = read_replay_arg();
+    match replay {
+        None => load_resources(),
+        Some(ref replay) => load_active_resources(&replay.resources),
+    }
 
     let system = create_io();
 
-    let flow_controller = GameControlFlowUpdater::new(&system);
+    let mut flow_controller = GameControlFlowUpdater::new(&system);
+    if let Some(replay) = replay {
+        flow_controller.start_replay(replay);
+    }
     system.main_loop(Box::new(flow_controller));
 }
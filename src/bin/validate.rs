@@ -0,0 +1,132 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+//! Loads a campaign, along with any mods, and reports every broken reference
+//! between its resources and scripts, as well as resources that are never
+//! used.  Loading otherwise only logs the first problem found in each
+//! resource and skips it.
+//!
+//! The process exit code is 0 if no errors were found, 1 for a fatal error
+//! or invalid arguments, and 2 if any errors were found.
+
+use log::{error, info};
+
+use sulis_core::config::Config;
+use sulis_core::resource::ResourceSet;
+use sulis_core::util;
+use sulis_module::{Module, ValidationReport};
+
+const USAGE: &str = "\
+Usage: sulis-validate --campaign <id> [options]
+
+Options:
+    --campaign <id>     ID or directory of the campaign to validate
+    --mod <dir>         Directory of a mod to load on top of the campaign.
+                        May be specified more than once
+    --no-unused         Do not report unused resources
+";
+
+struct Options {
+    campaign: String,
+    mods: Vec<String>,
+    report_unused: bool,
+}
+
+impl Options {
+    fn parse(args: Vec<String>) -> Result<Options, String> {
+        let mut options = Options {
+            campaign: String::new(),
+            mods: Vec::new(),
+            report_unused: true,
+        };
+
+        let mut iter = args.into_iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--no-unused" => options.report_unused = false,
+                "--campaign" => options.campaign = value(&arg, iter.next())?,
+                "--mod" => options.mods.push(value(&arg, iter.next())?),
+                _ => return Err(format!("Unknown argument '{}'", arg)),
+            }
+        }
+
+        if options.campaign.is_empty() {
+            return Err("A campaign must be specified".to_string());
+        }
+
+        Ok(options)
+    }
+}
+
+fn value(arg: &str, value: Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| format!("Missing value for '{}'", arg))
+}
+
+fn print_report(report: &ValidationReport, report_unused: bool) {
+    for error in report.errors.iter() {
+        println!("error: {}", error);
+    }
+
+    if report_unused {
+        for unused in report.unused.iter() {
+            println!("unused: {}", unused);
+        }
+    }
+
+    print!("{} error(s)", report.errors.len());
+    if report_unused {
+        print!(", {} unused resource(s)", report.unused.len());
+    }
+    println!();
+}
+
+fn main() {
+    let options = match Options::parse(std::env::args().skip(1).collect()) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprint!("{}", USAGE);
+            std::process::exit(1);
+        }
+    };
+
+    let _logger_handle = util::setup_logger();
+    info!("=========Initializing Validate=========");
+
+    let mut dirs = vec![
+        Config::resources_config().directory,
+        Module::campaign_dir(&options.campaign),
+    ];
+    dirs.extend(options.mods.iter().cloned());
+    info!("Reading resources from '{:?}'", dirs);
+
+    let yaml = match ResourceSet::load_resources(dirs.clone()) {
+        Ok(yaml) => yaml,
+        Err(e) => {
+            error!("{}", e);
+            eprintln!("Fatal error reading resources: {}", e);
+            util::error_and_exit("Fatal error reading resources.");
+            return;
+        }
+    };
+
+    let report = Module::validate_resources(yaml, dirs);
+    print_report(&report, options.report_unused);
+
+    if !report.is_ok() {
+        std::process::exit(2);
+    }
+}
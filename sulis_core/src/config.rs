@@ -177,6 +177,14 @@ impl Config {
         CONFIG.with(|c| c.borrow().display.scroll_to_active)
     }
 
+    pub fn save_format() -> SaveFormat {
+        CONFIG.with(|c| c.borrow().resources.save_format)
+    }
+
+    pub fn autosave_count() -> usize {
+        CONFIG.with(|c| c.borrow().resources.autosave_count)
+    }
+
     pub fn bench_log_level() -> Level {
         CONFIG.with(|c| c.borrow().logging.bench_log_level)
     }
@@ -187,6 +195,18 @@ impl Config {
 pub struct DebugConfig {
     pub encounter_spawning: bool,
     pub limit_line_of_sight: bool,
+
+    #[serde(default)]
+    pub record_input: bool,
+
+    #[serde(default)]
+    pub hot_reload_scripts: bool,
+
+    #[serde(default)]
+    pub hot_reload_resources: bool,
+
+    #[serde(default)]
+    pub show_ai_decisions: bool,
 }
 
 impl Default for DebugConfig {
@@ -194,6 +214,10 @@ impl Default for DebugConfig {
         DebugConfig {
             encounter_spawning: true,
             limit_line_of_sight: true,
+            record_input: false,
+            hot_reload_scripts: false,
+            hot_reload_resources: false,
+            show_ai_decisions: false,
         }
     }
 }
@@ -293,6 +317,24 @@ pub struct ResourcesConfig {
     pub directory: String,
     pub campaigns_directory: String,
     pub mods_directory: String,
+
+    #[serde(default)]
+    pub save_format: SaveFormat,
+
+    #[serde(default = "default_autosave_count")]
+    pub autosave_count: usize,
+}
+
+fn default_autosave_count() -> usize {
+    3
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(deny_unknown_fields)]
+pub enum SaveFormat {
+    #[default]
+    Json,
+    CompressedJson,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -22,6 +22,9 @@ pub use self::event::Event;
 
 mod glium_adapter;
 
+mod headless_adapter;
+pub use self::headless_adapter::NullRenderer;
+
 mod input_action;
 pub use self::input_action::{InputAction, InputActionKind, InputActionState};
 
@@ -59,6 +62,13 @@ pub trait ControlFlowUpdater {
 
     fn root(&self) -> Rc<RefCell<Widget>>;
 
+    /// Called for each input action received from the system, prior to the
+    /// `update` for the frame.  By default, the action is sent directly to
+    /// the root widget.
+    fn handle_action(&mut self, action: InputAction) {
+        action.handle(&self.root());
+    }
+
     fn recreate_window(&mut self) -> bool;
 
     fn is_exit(&self) -> bool;
@@ -83,6 +93,10 @@ pub trait GraphicsRenderer {
 
     fn has_texture(&self, id: &str) -> bool;
 
+    /// Reads back the current contents of the texture with the specified ID,
+    /// if it exists and the renderer is able to do so.
+    fn texture_image(&self, id: &str) -> Option<ImageBuffer<Rgba<u8>, Vec<u8>>>;
+
     fn set_scissor(&mut self, pos: Point, size: Size);
 
     fn clear_scissor(&mut self);
@@ -329,8 +343,10 @@ pub struct Vertex {
 
 implement_vertex!(Vertex, position, tex_coords);
 
+#[allow(clippy::large_enum_variant)]
 pub enum System {
     Glium(glium_adapter::GliumSystem),
+    Headless(headless_adapter::HeadlessSystem),
 }
 
 impl System {
@@ -341,11 +357,24 @@ impl System {
         Ok(System::Glium(glium_system))
     }
 
+    /// Creates a system with no window, display, or audio device.  Each frame
+    /// advances by exactly `frame_millis`, and the main loop returns after
+    /// at most `max_frames` frames.
+    pub fn create_headless(frame_millis: u32, max_frames: u32) -> System {
+        System::Headless(headless_adapter::HeadlessSystem {
+            frame_millis,
+            max_frames,
+        })
+    }
+
     pub fn main_loop(self, updater: Box<dyn ControlFlowUpdater>) {
         match self {
             System::Glium(glium_system) => {
                 glium_adapter::main_loop(glium_system, updater);
             }
+            System::Headless(headless_system) => {
+                headless_adapter::main_loop(headless_system, updater);
+            }
         }
     }
 
@@ -354,6 +383,7 @@ impl System {
             System::Glium(glium_system) => {
                 glium_system.io.get_display_configurations(&glium_system.event_loop)
             }
+            System::Headless(_) => Vec::new(),
         }
     }
 }
@@ -19,6 +19,7 @@ use std::collections::HashMap;
 use std::io::{Error, ErrorKind};
 
 use crate::config::{Config, DisplayMode};
+use crate::extern_image::imageops;
 use crate::io::keyboard_event::Key;
 use crate::io::*;
 use crate::resource::ResourceSet;
@@ -282,6 +283,15 @@ impl<'a> GraphicsRenderer for GliumRenderer<'a> {
         self.display.textures.contains_key(id)
     }
 
+    fn texture_image(&self, id: &str) -> Option<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+        let texture = self.display.textures.get(id)?;
+        let raw: RawImage2d<u8> = texture.texture.read();
+        let image = ImageBuffer::from_raw(raw.width, raw.height, raw.data.into_owned())?;
+
+        // OpenGL textures are stored bottom row first
+        Some(imageops::flip_vertical(&image))
+    }
+
     fn draw_to_texture(&mut self, texture_id: &str, draw_list: DrawList) {
         self.create_texture_if_missing(&draw_list.texture, &draw_list);
         let texture = self.display.textures.get(texture_id).unwrap();
@@ -541,7 +551,6 @@ pub(crate) fn main_loop(
     let mut io = system.io;
     let event_loop = system.event_loop;
     let mut audio = system.audio;
-    let mut root = updater.root();
 
     let mut scale = io.scale_factor;
     let (ui_x, ui_y) = Config::ui_size();
@@ -579,11 +588,11 @@ pub(crate) fn main_loop(
             Event::MainEventsCleared => {
                 // merge all mouse move events into at most one per frame
                 if let Some((mouse_x, mouse_y)) = mouse_move {
-                    InputAction::mouse_move(mouse_x, mouse_y).handle(&root);
+                    updater.handle_action(InputAction::mouse_move(mouse_x, mouse_y));
                 }
                 mouse_move = None;
 
-                root = updater.update(last_elapsed);
+                let root = updater.update(last_elapsed);
                 if updater.is_exit() {
                     *control_flow = ControlFlow::Exit;
                 } else if updater.recreate_window() {
@@ -632,7 +641,7 @@ pub(crate) fn main_loop(
                         }
                         _ => {
                             for action in process_window_event(event) {
-                                action.handle(&root);
+                                updater.handle_action(action);
                             }
                         }
                     }
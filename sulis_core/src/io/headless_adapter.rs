@@ -0,0 +1,106 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::collections::HashSet;
+use std::time;
+
+use crate::config::Config;
+use crate::io::*;
+use crate::util::{Point, format_elapsed_secs};
+
+pub struct HeadlessSystem {
+    pub(crate) frame_millis: u32,
+    pub(crate) max_frames: u32,
+}
+
+/// A renderer that discards all draw calls.  Textures are only tracked by ID,
+/// so that widgets querying `has_texture` behave the same as with a display.
+#[derive(Default)]
+pub struct NullRenderer {
+    textures: HashSet<String>,
+}
+
+impl GraphicsRenderer for NullRenderer {
+    fn draw(&mut self, _draw_list: DrawList) {}
+
+    fn draw_to_texture(&mut self, _texture_id: &str, _draw_list: DrawList) {}
+
+    fn register_texture(
+        &mut self,
+        id: &str,
+        _image: ImageBuffer<Rgba<u8>, Vec<u8>>,
+        _min_filter: TextureMinFilter,
+        _mag_filter: TextureMagFilter,
+    ) {
+        self.textures.insert(id.to_string());
+    }
+
+    fn clear_texture(&mut self, _id: &str) {}
+
+    fn clear_texture_region(&mut self, _id: &str, _min_x: i32, _min_y: i32, _max_x: i32, _max_y: i32) {}
+
+    fn has_texture(&self, id: &str) -> bool {
+        self.textures.contains(id)
+    }
+
+    fn texture_image(&self, _id: &str) -> Option<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+        None
+    }
+
+    fn set_scissor(&mut self, _pos: Point, _size: Size) {}
+
+    fn clear_scissor(&mut self) {}
+}
+
+/// Runs the updater with a fixed simulated frame time rather than the wall
+/// clock, until either the updater requests an exit or the maximum number of
+/// frames has elapsed.
+pub(crate) fn main_loop(
+    system: HeadlessSystem,
+    mut updater: Box<dyn ControlFlowUpdater>,
+) {
+    let mut renderer = NullRenderer::default();
+    let (ui_x, ui_y) = Config::ui_size();
+    let pixel_size = Point::new(ui_x, ui_y);
+
+    info!(
+        "Starting headless loop with {} millis per frame.",
+        system.frame_millis
+    );
+    let start_time = time::Instant::now();
+
+    let mut frames = 0;
+    let mut total_elapsed = 0;
+    while frames < system.max_frames {
+        let root = updater.update(system.frame_millis);
+        frames += 1;
+        total_elapsed += system.frame_millis;
+
+        if updater.is_exit() {
+            break;
+        }
+
+        Audio::update(None, system.frame_millis);
+        root.borrow().draw(&mut renderer, pixel_size, total_elapsed);
+    }
+
+    info!(
+        "Simulated {} frames ({} millis of game time) in {} seconds",
+        frames,
+        total_elapsed,
+        format_elapsed_secs(start_time.elapsed())
+    );
+}
@@ -21,12 +21,15 @@ use crate::io::event::{ClickKind, Kind};
 use crate::io::{keyboard_event::Key, Event};
 use crate::ui::{Cursor, Widget};
 
+#[derive(Debug, Deserialize, Serialize, Copy, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct InputAction {
     pub kind: InputActionKind,
     pub state: InputActionState,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Debug, Deserialize, Serialize, Copy, Clone)]
+#[serde(deny_unknown_fields)]
 pub enum InputActionState {
     Started,
     Stopped,
@@ -40,6 +43,7 @@ pub enum InputActionKind {
     ToggleCharacter,
     ToggleMap,
     ToggleJournal,
+    ToggleCombatLog,
     ToggleFormation,
     Back,
     EndTurn,
@@ -14,6 +14,9 @@
 //  You should have received a copy of the GNU General Public License
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
+mod dir_watcher;
+pub use self::dir_watcher::DirWatcher;
+
 mod resource_builder_set;
 pub use self::resource_builder_set::{
     read_builder, read_builders, read_single_resource, read_single_resource_path, read_to_string,
@@ -0,0 +1,105 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Watches a set of directories, recursively, for changes to files with a
+/// given extension by polling their modification times.  This avoids any
+/// platform specific file notification APIs, and is intended for development
+/// use such as reloading resources while the game is running.
+pub struct DirWatcher {
+    dirs: Vec<PathBuf>,
+    extension: String,
+    files: HashMap<PathBuf, SystemTime>,
+}
+
+impl DirWatcher {
+    /// Creates a new watcher over the specified directories, recording the
+    /// current state of all matching files.  Directories that do not exist
+    /// are ignored.
+    pub fn new(dirs: Vec<PathBuf>, extension: &str) -> DirWatcher {
+        let mut watcher = DirWatcher {
+            dirs,
+            extension: extension.to_string(),
+            files: HashMap::new(),
+        };
+        watcher.files = watcher.scan();
+        watcher
+    }
+
+    /// Returns the files that have been added, removed, or modified since
+    /// the last time this was called, or since this watcher was created.
+    pub fn changed_files(&mut self) -> Vec<PathBuf> {
+        let files = self.scan();
+
+        let mut changed: Vec<PathBuf> = files
+            .iter()
+            .filter(|(path, modified)| self.files.get(*path) != Some(modified))
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        changed.extend(
+            self.files
+                .keys()
+                .filter(|path| !files.contains_key(*path))
+                .cloned(),
+        );
+
+        self.files = files;
+        changed
+    }
+
+    fn scan(&self) -> HashMap<PathBuf, SystemTime> {
+        let mut files = HashMap::new();
+        for dir in self.dirs.iter() {
+            self.scan_recursive(dir, &mut files);
+        }
+        files
+    }
+
+    fn scan_recursive(&self, dir: &Path, files: &mut HashMap<PathBuf, SystemTime>) {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                self.scan_recursive(&path, files);
+                continue;
+            }
+
+            match path.extension() {
+                Some(ext) if *ext == *self.extension => (),
+                _ => continue,
+            }
+
+            let modified = match entry.metadata().and_then(|meta| meta.modified()) {
+                Ok(modified) => modified,
+                Err(e) => {
+                    warn!("Unable to read modification time of {:?}: {}", path, e);
+                    continue;
+                }
+            };
+
+            files.insert(path, modified);
+        }
+    }
+}
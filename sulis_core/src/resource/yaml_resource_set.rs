@@ -50,6 +50,7 @@ pub enum YamlResourceKind {
     AiTemplate,
     Area,
     Class,
+    Condition,
     Conversation,
     Cutscene,
     Encounter,
@@ -100,6 +101,7 @@ impl YamlResourceKind {
             "ai" => AiTemplate,
             "areas" => Area,
             "classes" => Class,
+            "conditions" => Condition,
             "conversations" => Conversation,
             "cutscenes" => Cutscene,
             "encounters" => Encounter,
@@ -32,7 +32,7 @@ pub struct AreaModel {
     pub config: EditorConfig,
 
     tiles: TilesModel,
-    actors: Vec<(Point, Rc<Actor>, Option<String>)>,
+    actors: Vec<(Point, Rc<Actor>, ActorData)>,
     props: Vec<PropData>,
     encounters: Vec<EncounterData>,
     transitions: Vec<Transition>,
@@ -172,7 +172,14 @@ impl AreaModel {
             return;
         }
 
-        self.actors.push((Point::new(x, y), actor, None));
+        let data = ActorData {
+            id: actor.id.to_string(),
+            location: Point::new(x, y),
+            unique_id: None,
+            patrol: None,
+            schedule: Vec::new(),
+        };
+        self.actors.push((Point::new(x, y), actor, data));
     }
 
     pub fn remove_actors_within(&mut self, x: i32, y: i32, width: i32, height: i32) {
@@ -676,8 +683,7 @@ impl AreaModel {
                 Some(actor) => actor,
             };
 
-            self.actors
-                .push((actor_data.location, actor, actor_data.unique_id));
+            self.actors.push((actor_data.location, actor, actor_data));
         }
     }
 
@@ -770,11 +776,11 @@ impl AreaModel {
 
         trace!("Saving actors.");
         let mut actors: Vec<ActorData> = Vec::new();
-        for &(pos, ref actor, ref unique_id) in self.actors.iter() {
+        for &(pos, ref actor, ref data) in self.actors.iter() {
             actors.push(ActorData {
                 id: actor.id.to_string(),
-                unique_id: unique_id.clone(),
                 location: pos,
+                ..data.clone()
             });
         }
 
@@ -21,9 +21,10 @@ use std::rc::Rc;
 
 use crate::rules::{BonusList, StatList};
 use sulis_core::image::Image;
-use sulis_core::resource::ResourceSet;
+use sulis_core::resource::{ResourceSet, YamlResourceKind};
 use sulis_core::util::unable_to_create_error;
 
+use crate::validator::Validator;
 use crate::{Actor, Module, PrereqList, PrereqListBuilder};
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -407,6 +408,21 @@ pub struct AbilityBuilder {
     pub upgrades: Option<Vec<Upgrade>>,
 }
 
+impl AbilityBuilder {
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        if let Some(ref active) = self.active {
+            v.check_script(&active.script);
+            for mode in active.requires_active_mode.iter() {
+                v.check(YamlResourceKind::Ability, mode);
+            }
+        }
+
+        if let Some(ref prereqs) = self.prereqs {
+            prereqs.validate(v);
+        }
+    }
+}
+
 #[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub enum Range {
@@ -18,8 +18,10 @@ use std::io::Error;
 use std::rc::Rc;
 use std::slice::Iter;
 
+use sulis_core::resource::YamlResourceKind;
 use sulis_core::util::unable_to_create_error;
 
+use crate::validator::Validator;
 use crate::{Ability, Module};
 
 #[derive(Debug)]
@@ -79,3 +81,11 @@ pub struct AbilityListBuilder {
     pub name: String,
     abilities: Vec<EntryBuilder>,
 }
+
+impl AbilityListBuilder {
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        for entry in self.abilities.iter() {
+            v.check(YamlResourceKind::Ability, &entry.id);
+        }
+    }
+}
@@ -22,10 +22,11 @@ use std::rc::Rc;
 use crate::rules::AttributeList;
 use sulis_core::image::{Image, LayeredImage};
 use sulis_core::io::GraphicsRenderer;
-use sulis_core::resource::ResourceSet;
+use sulis_core::resource::{ResourceSet, YamlResourceKind};
 use sulis_core::ui::Color;
 use sulis_core::util::{unable_to_create_error, Offset, Scale};
 
+use crate::validator::Validator;
 use crate::{
     AITemplate, Ability, Class, Conversation, ImageLayer, ImageLayerSet, InventoryBuilder,
     LootList, Module, Race, RaceBuilder,
@@ -220,7 +221,7 @@ impl Actor {
             portrait: other.portrait.clone(),
             race: Rc::clone(&other.race),
             sex: other.sex,
-            attributes: other.attributes,
+            attributes: other.attributes.clone(),
             inventory,
             xp,
             total_level,
@@ -497,3 +498,37 @@ pub struct ActorBuilder {
     pub abilities: Vec<String>,
     pub ai: Option<String>,
 }
+
+impl ActorBuilder {
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        if let Some(ref race) = self.race {
+            v.check(YamlResourceKind::Race, race);
+        } else if let Some(ref race) = self.inline_race {
+            race.validate(v);
+        } else {
+            v.error("No race or inline race specified".to_string());
+        }
+
+        if let Some(ref convo) = self.conversation {
+            v.check(YamlResourceKind::Conversation, convo);
+        }
+
+        self.inventory.validate(v);
+
+        for class_id in self.levels.keys() {
+            v.check(YamlResourceKind::Class, class_id);
+        }
+
+        if let Some(loot) = self.reward.as_ref().and_then(|r| r.loot.as_ref()) {
+            v.check(YamlResourceKind::LootList, loot);
+        }
+
+        for ability_id in self.abilities.iter() {
+            v.check(YamlResourceKind::Ability, ability_id);
+        }
+
+        if let Some(ref ai) = self.ai {
+            v.check(YamlResourceKind::AiTemplate, ai);
+        }
+    }
+}
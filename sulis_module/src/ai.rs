@@ -16,6 +16,8 @@
 
 use std::collections::HashMap;
 
+use crate::validator::Validator;
+
 #[derive(Serialize, Deserialize, Clone, Copy, PartialOrd, Ord, Hash, PartialEq, Eq, Debug)]
 #[serde(deny_unknown_fields)]
 pub enum FuncKind {
@@ -27,15 +29,54 @@ pub enum FuncKind {
     AiAction,
 }
 
+/// Selects what decides an AI's actions each turn.  `Script` calls the
+/// Lua `ai_action` function, while `Utility` uses the native utility
+/// scoring planner.  An `AiAction` hook always takes precedence over the
+/// native planner.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub enum AIPlanner {
+    #[default]
+    Script,
+    Utility,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct AITemplate {
     pub id: String,
     pub script: String,
 
+    #[serde(default)]
+    pub planner: AIPlanner,
+
     #[serde(default)]
     pub hooks: HashMap<FuncKind, String>,
 
     #[serde(default)]
     pub params: HashMap<String, i32>,
 }
+
+impl AITemplate {
+    /// Returns the value of the param with the specified `id`, or `default`
+    /// if this template does not set it
+    pub fn param(&self, id: &str, default: i32) -> i32 {
+        self.params.get(id).copied().unwrap_or(default)
+    }
+
+    /// Returns true if the native planner should decide this AI's actions,
+    /// rather than a Lua `ai_action` function
+    pub fn uses_native_planner(&self) -> bool {
+        self.planner == AIPlanner::Utility && !self.hooks.contains_key(&FuncKind::AiAction)
+    }
+
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        if self.planner == AIPlanner::Script && !self.hooks.contains_key(&FuncKind::AiAction) {
+            v.check_script_func(&self.script, "ai_action");
+        }
+
+        for func in self.hooks.values() {
+            v.check_script_func(&self.script, func);
+        }
+    }
+}
@@ -38,11 +38,12 @@ use serde::ser::{SerializeMap, SerializeStruct};
 use serde::{Deserialize, Deserializer, Serializer};
 
 use sulis_core::image::Image;
-use sulis_core::resource::{ResourceSet, Sprite};
+use sulis_core::resource::{ResourceSet, Sprite, YamlResourceKind};
 use sulis_core::util::{unable_to_create_error, Point, Size};
 use sulis_core::io::SoundSource;
 
 use crate::generator::{EncounterParams, EncounterParamsBuilder, PropParams, PropParamsBuilder};
+use crate::validator::Validator;
 use crate::{Encounter, ItemListEntrySaveState, Module, ObjectSize, OnTrigger, Prop};
 
 pub const MAX_AREA_SIZE: i32 = 128;
@@ -81,6 +82,55 @@ pub struct ActorData {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub unique_id: Option<String>,
+
+    /// The route this actor walks while out of combat, when it has no
+    /// `schedule` entries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patrol: Option<Patrol>,
+
+    /// What this actor does at each time of day while out of combat
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schedule: Vec<ScheduleEntry>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(deny_unknown_fields)]
+pub enum PatrolKind {
+    /// Returns to the first point after reaching the last
+    #[default]
+    Loop,
+
+    /// Walks back through the points in reverse after reaching the last
+    PingPong,
+}
+
+/// A route that an actor walks along while out of combat
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Patrol {
+    pub points: Vec<Point>,
+
+    #[serde(default)]
+    pub kind: PatrolKind,
+
+    /// The time the actor waits at each point before moving on
+    #[serde(default)]
+    pub wait_millis: u32,
+}
+
+/// One part of an actor's daily schedule, active from `start_hour` until
+/// the `start_hour` of the next entry.  The actor either walks to and
+/// stays at `location`, or walks its `patrol`
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduleEntry {
+    pub start_hour: u32,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<Point>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patrol: Option<Patrol>,
 }
 
 #[derive(Clone)]
@@ -146,6 +196,7 @@ impl Area {
         );
 
         let (triggers, encounters) = Area::read_triggers_and_encounters(&builder)?;
+        let actors = Area::read_actors(&builder)?;
 
         let visibility_tile = ResourceSet::sprite(&builder.visibility_tile)?;
         let explored_tile = ResourceSet::sprite(&builder.explored_tile)?;
@@ -175,7 +226,7 @@ impl Area {
             name: builder.name.to_string(),
             width: builder.width as i32,
             height: builder.height as i32,
-            actors: builder.actors.clone(),
+            actors,
             encounters,
             props,
             visibility_tile,
@@ -197,6 +248,52 @@ impl Area {
         })
     }
 
+    /// Validates the patrols and schedules of the actors in this area, sorting
+    /// each schedule by start hour
+    fn read_actors(builder: &AreaBuilder) -> Result<Vec<ActorData>, Error> {
+        let hours_per_day = Module::rules().hours_per_day;
+        let in_bounds = |p: &Point| {
+            p.x >= 0 && p.y >= 0 && p.x < builder.width as i32 && p.y < builder.height as i32
+        };
+        let patrol_valid = |patrol: &Patrol| {
+            !patrol.points.is_empty() && patrol.points.iter().all(in_bounds)
+        };
+
+        let mut actors = builder.actors.clone();
+        for actor in actors.iter_mut() {
+            if let Some(patrol) = &actor.patrol {
+                if !patrol_valid(patrol) {
+                    warn!("Invalid patrol points for actor '{}'", actor.id);
+                    return unable_to_create_error("area", &builder.id);
+                }
+            }
+
+            let mut hours = HashSet::new();
+            for entry in actor.schedule.iter() {
+                if entry.start_hour >= hours_per_day || !hours.insert(entry.start_hour) {
+                    warn!("Invalid schedule start hour {} for '{}'", entry.start_hour, actor.id);
+                    return unable_to_create_error("area", &builder.id);
+                }
+
+                let valid = match (&entry.location, &entry.patrol) {
+                    (Some(location), None) => in_bounds(location),
+                    (None, Some(patrol)) => patrol_valid(patrol),
+                    _ => false,
+                };
+
+                if !valid {
+                    warn!("Schedule entries for '{}' must have either a valid location \
+                          or a valid patrol", actor.id);
+                    return unable_to_create_error("area", &builder.id);
+                }
+            }
+
+            actor.schedule.sort_by_key(|entry| entry.start_hour);
+        }
+
+        Ok(actors)
+    }
+
     fn read_triggers_and_encounters(
         builder: &AreaBuilder
     ) -> Result<(Vec<Trigger>, Vec<EncounterData>), Error> {
@@ -369,6 +466,52 @@ pub struct AreaBuilder {
     pub elevation: Vec<u8>,
 }
 
+impl AreaBuilder {
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        if let OnRest::FireScript { ref id, ref func } = self.on_rest {
+            v.check_script_func(id, func);
+        }
+
+        if let Some(ref generator) = self.generator {
+            v.check(YamlResourceKind::Generator, &generator.id);
+            for transition in generator.transitions.iter() {
+                v.check(YamlResourceKind::Area, &transition.to);
+            }
+            generator.encounters.validate(v);
+            generator.props.validate(v);
+        }
+
+        for actor in self.actors.iter() {
+            v.check(YamlResourceKind::Actor, &actor.id);
+        }
+
+        for prop in self.props.iter() {
+            v.check(YamlResourceKind::Prop, &prop.id);
+            for entry in prop.items.iter() {
+                entry.item.validate(v);
+            }
+        }
+
+        for encounter in self.encounters.iter() {
+            v.check(YamlResourceKind::Encounter, &encounter.id);
+        }
+
+        for transition in self.transitions.iter() {
+            v.check(YamlResourceKind::Size, &transition.size);
+            match transition.to {
+                ToKind::Area { ref id, .. } | ToKind::FindLink { ref id, .. } => {
+                    v.check(YamlResourceKind::Area, id);
+                }
+                ToKind::CurArea { .. } | ToKind::WorldMap => (),
+            }
+        }
+
+        for trigger in self.triggers.iter() {
+            v.check_triggers(&trigger.on_activate);
+        }
+    }
+}
+
 pub struct GeneratorParams {
     pub id: String,
 
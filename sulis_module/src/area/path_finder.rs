@@ -49,6 +49,18 @@ impl Destination {
             max_path_len: None,
         }
     }
+
+    /// Returns true if a mover at `x`, `y` is already within `dist` of this
+    /// destination, in which case the path finder will not find a path to it.
+    pub fn is_reached(&self, x: i32, y: i32) -> bool {
+        let s_x = x as f32 + self.parent_w / 2.0;
+        let s_y = y as f32 + self.parent_h / 2.0;
+
+        let dx = ((s_x - (self.x + self.w / 2.0)).abs() - self.w / 2.0).max(0.0);
+        let dy = ((s_y - (self.y + self.h / 2.0)).abs() - self.h / 2.0).max(0.0);
+
+        (dx * dx + dy * dy) as i32 <= (self.dist * self.dist) as i32
+    }
 }
 
 #[derive(Eq)]
@@ -179,7 +191,7 @@ impl PathFinder {
         let start = start_x + start_y * self.width;
         let initial_dist_squared = self.dist_squared(start);
 
-        if initial_dist_squared <= dest_dist_squared {
+        if dest.is_reached(start_x, start_y) {
             debug!("Mover is already inside the destination");
             return None;
         }
@@ -22,9 +22,10 @@ use std::rc::Rc;
 
 use crate::rules::Time;
 use sulis_core::image::Image;
-use sulis_core::resource::ResourceSet;
+use sulis_core::resource::{ResourceSet, YamlResourceKind};
 use sulis_core::util::{unable_to_create_error, Point};
 
+use crate::validator::Validator;
 use crate::{on_trigger, Conversation, Module};
 
 pub struct WorldMap {
@@ -83,6 +84,7 @@ impl PartialOrd for CampaignGroup {
 
 pub struct Campaign {
     pub id: String,
+    pub version: Option<String>,
     pub starting_time: Time,
     pub starting_area: String,
     pub starting_location: Point,
@@ -95,6 +97,7 @@ pub struct Campaign {
     pub on_round_elapsed_script: Option<on_trigger::ScriptData>,
     pub world_map: WorldMap,
     pub group: Option<CampaignGroup>,
+    pub reseed_combat_rand_on_load: bool,
 }
 
 impl Campaign {
@@ -142,10 +145,12 @@ impl Campaign {
             description: builder.description,
             backstory_conversation,
             id: builder.id,
+            version: builder.version,
             max_starting_level: builder.max_starting_level,
             on_party_death_script: builder.on_party_death_script,
             on_tick_script: builder.on_tick_script,
             on_round_elapsed_script: builder.on_round_elapsed_script,
+            reseed_combat_rand_on_load: builder.reseed_combat_rand_on_load,
             world_map: WorldMap {
                 size: builder.world_map.size,
                 offset: builder.world_map.offset,
@@ -159,6 +164,9 @@ impl Campaign {
 #[serde(deny_unknown_fields)]
 pub struct CampaignBuilder {
     pub id: String,
+
+    #[serde(default)]
+    pub version: Option<String>,
     pub group: Option<CampaignGroup>,
     pub starting_time: Time,
     pub starting_area: String,
@@ -171,6 +179,35 @@ pub struct CampaignBuilder {
     pub on_tick_script: Option<on_trigger::ScriptData>,
     pub on_round_elapsed_script: Option<on_trigger::ScriptData>,
     pub world_map: WorldMapBuilder,
+
+    /// The state of the combat random generator is kept in saves, so that
+    /// reloading a save and repeating the same actions gives the same combat
+    /// rolls.  If this is set, it is instead reseeded each time a save is
+    /// loaded, so that reloading may give different rolls
+    #[serde(default)]
+    pub reseed_combat_rand_on_load: bool,
+}
+
+impl CampaignBuilder {
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        v.check(YamlResourceKind::Area, &self.starting_area);
+        v.check(YamlResourceKind::Conversation, &self.backstory_conversation);
+        v.check_script_data(&self.on_party_death_script);
+
+        if let Some(ref script) = self.on_tick_script {
+            v.check_script_data(script);
+        }
+
+        if let Some(ref script) = self.on_round_elapsed_script {
+            v.check_script_data(script);
+        }
+
+        for location in self.world_map.locations.values() {
+            if let Some(ref area) = location.linked_area {
+                v.check(YamlResourceKind::Area, area);
+            }
+        }
+    }
 }
 
 #[derive(Deserialize, Debug)]
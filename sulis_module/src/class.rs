@@ -19,8 +19,10 @@ use std::io::Error;
 use std::rc::Rc;
 
 use crate::rules::{AttributeList, BonusList};
+use sulis_core::resource::YamlResourceKind;
 use sulis_core::util::{unable_to_create_error, ExtInt};
 
+use crate::validator::Validator;
 use crate::{Ability, AbilityList, InventoryBuilder, Module};
 
 #[derive(Debug)]
@@ -242,3 +244,24 @@ pub struct ClassBuilder {
     #[serde(default)]
     pub stats: Vec<ClassStat>,
 }
+
+impl ClassBuilder {
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        for ability_id in self.starting_abilities.iter() {
+            v.check(YamlResourceKind::Ability, ability_id);
+        }
+
+        for kit in self.kits.iter() {
+            kit.starting_inventory.validate(v);
+            for ability_id in kit.starting_abilities.iter() {
+                v.check(YamlResourceKind::Ability, ability_id);
+            }
+        }
+
+        for upgrades in self.upgrades.values() {
+            for list_id in upgrades.ability_choices.iter() {
+                v.check(YamlResourceKind::AbilityList, list_id);
+            }
+        }
+    }
+}
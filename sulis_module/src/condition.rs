@@ -0,0 +1,161 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::io::Error;
+
+use crate::rules::BonusList;
+use crate::validator::Validator;
+use sulis_core::resource::{ResourceSet, YamlResourceKind};
+use sulis_core::util::unable_to_create_error;
+
+/// How a newly applied condition or effect interacts with those of the same kind
+/// already on the target entity.  For conditions this is the same condition, and
+/// for other effects it is normally those with the same tag.  Conditions refresh
+/// by default, while effects created by scripts stack by default.  Surfaces
+/// always stack.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub enum ConditionStacking {
+    /// The existing condition is kept, with its remaining duration extended to the
+    /// new duration if that is longer
+    #[default]
+    Refresh,
+
+    /// The existing condition is removed and the new one takes its place
+    Replace,
+
+    /// Each application is a separate effect, and their bonuses add together
+    Stack,
+
+    /// As `Stack`, but at most the specified number of applications are kept,
+    /// with the oldest removed first
+    StackUpTo(u32),
+
+    /// Only the application with the highest strength is kept.  Weaker
+    /// applications are discarded, while equal or stronger ones replace the
+    /// existing effects
+    HighestWins(f32),
+}
+
+impl ConditionStacking {
+    /// Parses a stacking policy from a script.  `amount` is the maximum number
+    /// of effects for `stack_up_to` and the strength for `highest_wins`
+    pub fn from_str(kind: &str, amount: Option<f32>) -> Option<ConditionStacking> {
+        let amount = amount.unwrap_or(1.0);
+        Some(match kind {
+            "stack" => ConditionStacking::Stack,
+            "stack_up_to" => ConditionStacking::StackUpTo(amount as u32),
+            "refresh" => ConditionStacking::Refresh,
+            "replace" => ConditionStacking::Replace,
+            "highest_wins" => ConditionStacking::HighestWins(amount),
+            _ => return None,
+        })
+    }
+}
+
+/// A hint to the AI on how an entity under a condition should behave
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(deny_unknown_fields)]
+pub enum ConditionAIHint {
+    /// The AI script runs as normal
+    #[default]
+    None,
+
+    /// The AI attempts to move away from hostiles rather than fight
+    Flee,
+
+    /// The AI script is not run at all and the entity's turn ends immediately
+    SkipTurn,
+}
+
+/// A named status such as stunned or petrified.  Conditions are applied to
+/// entities via effects, and provide a consistent set of bonuses, icon, and
+/// AI behavior wherever they are used.
+#[derive(Debug)]
+pub struct Condition {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+
+    /// The image ID of the icon shown for entities with this condition
+    pub icon: String,
+
+    pub bonuses: BonusList,
+
+    /// IDs of other conditions that an entity with this condition is immune to
+    pub immunities: Vec<String>,
+
+    pub stacking: ConditionStacking,
+    pub ai_hint: ConditionAIHint,
+}
+
+impl Condition {
+    pub fn new(builder: ConditionBuilder) -> Result<Condition, Error> {
+        if ResourceSet::image(&builder.icon).is_none() {
+            warn!("No image found for icon '{}'", builder.icon);
+            return unable_to_create_error("condition", &builder.id);
+        }
+
+        Ok(Condition {
+            id: builder.id,
+            name: builder.name,
+            description: builder.description,
+            icon: builder.icon,
+            bonuses: builder.bonuses,
+            immunities: builder.immunities,
+            stacking: builder.stacking,
+            ai_hint: builder.ai_hint,
+        })
+    }
+}
+
+impl PartialEq for Condition {
+    fn eq(&self, other: &Condition) -> bool {
+        self.id == other.id
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ConditionBuilder {
+    pub id: String,
+    pub name: String,
+
+    #[serde(default)]
+    pub description: String,
+
+    pub icon: String,
+
+    #[serde(default)]
+    pub bonuses: BonusList,
+
+    #[serde(default)]
+    pub immunities: Vec<String>,
+
+    #[serde(default)]
+    pub stacking: ConditionStacking,
+
+    #[serde(default)]
+    pub ai_hint: ConditionAIHint,
+}
+
+impl ConditionBuilder {
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        for id in self.immunities.iter() {
+            v.check(YamlResourceKind::Condition, id);
+        }
+    }
+}
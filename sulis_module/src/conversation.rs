@@ -20,6 +20,7 @@ use std::slice::Iter;
 
 use sulis_core::util::unable_to_create_error;
 
+use crate::validator::Validator;
 use crate::{Module, OnTrigger};
 
 #[derive(Deserialize, Debug)]
@@ -147,3 +148,33 @@ pub struct ConversationBuilder {
     nodes: HashMap<String, Node>,
     initial_nodes: Vec<InitialNode>,
 }
+
+impl ConversationBuilder {
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        if self.initial_nodes.is_empty() {
+            v.error("Must specify at least one initial node".to_string());
+        }
+
+        for node in self.initial_nodes.iter() {
+            if !self.nodes.contains_key(&node.id) {
+                v.error(format!("Invalid initial node '{}'", node.id));
+            }
+            v.check_triggers(&node.to_view);
+        }
+
+        for (id, node) in self.nodes.iter() {
+            v.check_triggers(&node.on_view);
+
+            for response in node.responses.iter() {
+                if let Some(ref to) = response.to {
+                    if !self.nodes.contains_key(to) {
+                        v.error(format!("Invalid to '{}' for response in node '{}'", to, id));
+                    }
+                }
+
+                v.check_triggers(&response.on_select);
+                v.check_triggers(&response.to_view);
+            }
+        }
+    }
+}
@@ -18,9 +18,10 @@ use std::collections::{HashMap, HashSet};
 use std::io::Error;
 use std::rc::Rc;
 
+use crate::validator::Validator;
 use crate::{Actor, Module};
 use sulis_core::io::SoundSource;
-use sulis_core::resource::ResourceSet;
+use sulis_core::resource::{ResourceSet, YamlResourceKind};
 use sulis_core::util::{gen_rand, unable_to_create_error};
 
 struct Entry {
@@ -31,10 +32,40 @@ struct Entry {
     limit: Option<u32>,
 }
 
+/// How the members of a spawned encounter coordinate with each other in
+/// combat.  All tactics are disabled by default, so each member of the
+/// group decides on its own.  An encounter opts in with a `tactics` section,
+/// for example:
+///
+/// ```yaml
+/// tactics:
+///   focus_fire: true
+///   ranged_behind_melee: true
+///   flank: true
+///   retreat_threshold: 0.25
+/// ```
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(deny_unknown_fields, default)]
+pub struct GroupTactics {
+    /// Members prefer to attack the same target as the rest of the group
+    pub focus_fire: bool,
+
+    /// Members with ranged attacks keep behind the group's melee fighters
+    pub ranged_behind_melee: bool,
+
+    /// Members with melee attacks approach targets from a flanking position
+    pub flank: bool,
+
+    /// Once the group's combined hit points drop to this fraction of its
+    /// combined maximum, all members retreat.  Zero disables retreating
+    pub retreat_threshold: f32,
+}
+
 pub struct Encounter {
     pub id: String,
     pub music: Option<SoundSource>,
     pub auto_spawn: bool,
+    pub tactics: GroupTactics,
     min_gen_actors: u32,
     max_gen_actors: u32,
     entries: Vec<Entry>,
@@ -90,6 +121,11 @@ impl Encounter {
             });
         }
 
+        if !(0.0..=1.0).contains(&builder.tactics.retreat_threshold) {
+            warn!("Retreat threshold must be between 0.0 and 1.0");
+            return unable_to_create_error("encounter", &builder.id);
+        }
+
         let music = match &builder.music {
             None => None,
             Some(id) => Some(ResourceSet::sound(id)?),
@@ -99,6 +135,7 @@ impl Encounter {
             id: builder.id,
             music,
             auto_spawn: builder.auto_spawn,
+            tactics: builder.tactics,
             min_gen_actors: builder.min_gen_actors,
             max_gen_actors: builder.max_gen_actors,
             entries,
@@ -189,6 +226,9 @@ pub struct EncounterBuilder {
     min_gen_actors: u32,
     max_gen_actors: u32,
     entries: Vec<EntryBuilder>,
+
+    #[serde(default)]
+    tactics: GroupTactics,
 }
 
 #[derive(Deserialize, Debug)]
@@ -207,3 +247,11 @@ pub struct EntryBuilder {
     #[serde(default)]
     unique_id: Option<String>,
 }
+
+impl EncounterBuilder {
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        for entry in self.entries.iter() {
+            v.check(YamlResourceKind::Actor, &entry.id);
+        }
+    }
+}
@@ -51,6 +51,7 @@ use std::io::{Error, ErrorKind};
 use std::rc::Rc;
 
 use crate::area::{EncounterDataBuilder, Layer, LocationChecker, PathFinderGrid, PropDataBuilder};
+use crate::validator::Validator;
 use crate::{ObjectSize, WallKind};
 use sulis_core::util::{Point, ReproducibleRandom};
 
@@ -344,6 +345,13 @@ pub struct GeneratorBuilder {
     transitions: TransitionParamsBuilder,
 }
 
+impl GeneratorBuilder {
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        self.props.validate(v);
+        self.encounters.validate(v);
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, Copy)]
 #[serde(deny_unknown_fields)]
 pub struct WeightedEntry {
@@ -22,10 +22,12 @@ use crate::generator::{
     maze::Room, overlaps_any, GenModel, Maze, Rect, RegionKind, RegionKinds, WeightedEntry,
     WeightedList,
 };
+use crate::validator::Validator;
 use crate::{
     area::{EncounterDataBuilder, Layer},
     Encounter, Module,
 };
+use sulis_core::resource::YamlResourceKind;
 use sulis_core::util::{Point, Size};
 
 pub struct EncounterGen<'a, 'b> {
@@ -196,6 +198,16 @@ pub struct EncounterParamsBuilder {
     passes: Vec<EncounterPassBuilder>,
 }
 
+impl EncounterParamsBuilder {
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        for pass in self.passes.iter() {
+            for id in pass.kinds.keys() {
+                v.check(YamlResourceKind::Encounter, id);
+            }
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct EncounterPassBuilder {
@@ -21,10 +21,12 @@ use std::rc::Rc;
 use crate::generator::{
     overlaps_any, GenModel, Maze, Rect, RegionKind, RegionKinds, WeightedEntry, WeightedList,
 };
+use crate::validator::Validator;
 use crate::{
     area::{Layer, PropDataBuilder},
     Module, Prop,
 };
+use sulis_core::resource::YamlResourceKind;
 use sulis_core::util::Point;
 
 pub struct PropGen<'a, 'b> {
@@ -182,6 +184,16 @@ pub struct PropParamsBuilder {
     passes: Vec<PropPassBuilder>,
 }
 
+impl PropParamsBuilder {
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        for pass in self.passes.iter() {
+            for id in pass.kinds.keys() {
+                v.check(YamlResourceKind::Prop, id);
+            }
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct PropPassBuilder {
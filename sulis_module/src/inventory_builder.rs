@@ -17,7 +17,10 @@
 use std::collections::HashMap;
 use std::rc::Rc;
 
+use sulis_core::resource::YamlResourceKind;
+
 use crate::rules::{QuickSlot, Slot};
+use crate::validator::Validator;
 use crate::{Item, ItemState, Module, Race};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -62,6 +65,13 @@ impl ItemSaveState {
             variant: item.variant,
         }
     }
+
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        v.check(YamlResourceKind::Item, &self.id);
+        for adjective in self.adjectives.iter() {
+            v.check(YamlResourceKind::ItemAdjective, adjective);
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -116,6 +126,20 @@ impl InventoryBuilder {
         }
     }
 
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        for item in self.equipped.values() {
+            item.validate(v);
+        }
+
+        for item in self.quick.values() {
+            item.validate(v);
+        }
+
+        for entry in self.pc_starting_items.iter() {
+            entry.item.validate(v);
+        }
+    }
+
     pub fn remove_invalid_items(&mut self, race: &Rc<Race>) {
         for slot in race.disabled_slots.iter() {
             self.equipped.remove(slot);
@@ -22,9 +22,10 @@ use std::rc::Rc;
 
 use crate::rules::{bonus::AttackBuilder, BonusList, ItemKind, Slot};
 use sulis_core::image::Image;
-use sulis_core::resource::ResourceSet;
+use sulis_core::resource::{ResourceSet, YamlResourceKind};
 use sulis_core::util::unable_to_create_error;
 
+use crate::validator::Validator;
 use crate::{
     ability::{AIData, Duration},
     Actor, ImageLayer, ItemAdjective, Module, PrereqList, PrereqListBuilder,
@@ -442,6 +443,22 @@ pub struct ItemBuilder {
     variants: Vec<VariantBuilder>,
 }
 
+impl ItemBuilder {
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        if let Some(ref prereqs) = self.prereqs {
+            prereqs.validate(v);
+        }
+
+        if let Some(ref usable) = self.usable {
+            v.check_script(&usable.script);
+        }
+
+        for adjective in self.adjectives.iter() {
+            v.check(YamlResourceKind::ItemAdjective, adjective);
+        }
+    }
+}
+
 pub fn format_item_value(value: i32) -> String {
     let display_factor = Module::rules().item_value_display_factor;
 
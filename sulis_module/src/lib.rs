@@ -34,7 +34,7 @@ pub use self::actor::Faction;
 pub use self::actor::Sex;
 
 pub mod ai;
-pub use self::ai::AITemplate;
+pub use self::ai::{AIPlanner, AITemplate};
 
 pub mod area;
 pub use self::area::Area;
@@ -42,6 +42,9 @@ pub use self::area::Area;
 pub mod class;
 pub use self::class::Class;
 
+pub mod condition;
+pub use self::condition::{Condition, ConditionAIHint, ConditionStacking};
+
 pub mod conversation;
 pub use self::conversation::Conversation;
 
@@ -57,7 +60,7 @@ pub use self::on_trigger::MerchantData;
 pub use self::on_trigger::OnTrigger;
 
 pub mod encounter;
-pub use self::encounter::Encounter;
+pub use self::encounter::{Encounter, GroupTactics};
 
 pub mod campaign;
 pub use self::campaign::Campaign;
@@ -108,18 +111,24 @@ pub use self::race::Race;
 pub mod rules;
 pub use self::rules::bonus;
 pub use self::rules::{
-    AccuracyKind, Armor, ArmorKind, Attack, AttackBonuses, AttackKind, Attribute, AttributeList,
-    Bonus, BonusKind, BonusList, Damage, DamageKind, DamageList, HitFlags, HitKind, ItemKind,
-    QuickSlot, Resistance, Rules, Slot, StatList, Time, WeaponKind, WeaponStyle, ROUND_TIME_MILLIS,
+    AccuracyKind, Armor, ArmorKind, Attack, AttackBonuses, AttackKind, Attribute, AttributeData,
+    AttributeList, AttackRoll, Bonus, BonusKind, BonusList, Cover, Damage, DamageKind,
+    DamageKindData, DamageList, DamageRoll, DerivedStat, Expression, Formulas, HitChances,
+    HitFlags, HitKind, ItemKind, QuickSlot, Resistance, Rules, Slot, StatList, Time, WeaponKind,
+    WeaponStyle, ROUND_TIME_MILLIS,
 };
 
+pub mod validator;
+pub use self::validator::ValidationReport;
+use self::validator::Validator;
+
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::fmt::{self, Display};
 use std::fs;
 use std::io::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::time;
 
@@ -137,6 +146,7 @@ use self::area::{
 use self::area::{AreaBuilder, Tile};
 use self::campaign::CampaignBuilder;
 use self::class::ClassBuilder;
+use self::condition::ConditionBuilder;
 use self::conversation::ConversationBuilder;
 use self::cutscene::CutsceneBuilder;
 use self::encounter::EncounterBuilder;
@@ -161,6 +171,7 @@ pub struct Module {
     ai_templates: HashMap<String, Rc<AITemplate>>,
     areas: HashMap<String, Rc<Area>>,
     classes: HashMap<String, Rc<Class>>,
+    conditions: HashMap<String, Rc<Condition>>,
     conversations: HashMap<String, Rc<Conversation>>,
     cutscenes: HashMap<String, Rc<Cutscene>>,
     encounters: HashMap<String, Rc<Encounter>>,
@@ -183,6 +194,7 @@ pub struct Module {
     generators: HashMap<String, Rc<AreaGenerator>>,
 
     root_dir: Option<String>,
+    dirs: Vec<String>,
     init: bool,
 }
 
@@ -276,6 +288,18 @@ impl Module {
         modules
     }
 
+    /// Returns the directory of the available module with the specified ID.
+    /// If there is no such module, `campaign` is assumed to be a directory
+    pub fn campaign_dir(campaign: &str) -> String {
+        for module in Module::get_available_modules() {
+            if module.id == campaign {
+                return module.dir;
+            }
+        }
+
+        campaign.to_string()
+    }
+
     pub fn delete_character(id: &str) {
         // TODO don't assume ID = filename
         let mut path = config::USER_DIR.clone();
@@ -365,7 +389,27 @@ impl Module {
         actors
     }
 
-    pub fn load_resources(mut yaml: YamlResourceSet, dirs: Vec<String>) -> Result<(), Error> {
+    pub fn load_resources(yaml: YamlResourceSet, dirs: Vec<String>) -> Result<(), Error> {
+        Module::load_resources_internal(yaml, dirs, None)
+    }
+
+    /// Loads the module in the same way as `load_resources`, but also checks
+    /// every reference between resources.  Rather than stopping at the first
+    /// problem, all problems found are collected into the returned report.
+    pub fn validate_resources(yaml: YamlResourceSet, dirs: Vec<String>) -> ValidationReport {
+        let mut validator = Validator::new(&yaml, &dirs);
+        if let Err(e) = Module::load_resources_internal(yaml, dirs, Some(&mut validator)) {
+            validator.fatal(e);
+        }
+
+        validator.into_report()
+    }
+
+    fn load_resources_internal(
+        mut yaml: YamlResourceSet,
+        dirs: Vec<String>,
+        mut validator: Option<&mut Validator>,
+    ) -> Result<(), Error> {
         assert!(dirs.len() > 1);
         debug!("Creating module from parsed data.");
 
@@ -417,6 +461,9 @@ impl Module {
 
         let rules: Rules = read_builder(rules_yaml)?;
         rules.validate()?;
+        // attributes and damage kinds must be known before reading anything that refers to them
+        Attribute::set_attributes(&rules.attributes);
+        DamageKind::set_kinds(&rules.damage_kinds);
 
         let campaign_builder: CampaignBuilder = read_builder(campaign_yaml)?;
 
@@ -429,6 +476,7 @@ impl Module {
             module.ai_templates.clear();
             module.areas.clear();
             module.classes.clear();
+            module.conditions.clear();
             module.conversations.clear();
             module.cutscenes.clear();
             module.encounters.clear();
@@ -452,7 +500,12 @@ impl Module {
             module.scripts = read_to_string(&dirs, "scripts");
             expand_include_directives(&mut module.scripts);
 
+            if let Some(validator) = validator.as_mut() {
+                validator.check_builders(&builder_set, &campaign_builder, &module.scripts);
+            }
+
             module.root_dir = Some(dirs[1].to_string());
+            module.dirs = dirs.clone();
 
             for (id, builder) in builder_set.item_adjectives {
                 insert_if_ok(
@@ -482,6 +535,10 @@ impl Module {
                 module.ai_templates.insert(id, Rc::new(builder));
             }
 
+            for (id, builder) in builder_set.condition_builders {
+                insert_if_ok("condition", id, Condition::new(builder), &mut module.conditions);
+            }
+
             for (id, builder) in builder_set.ability_builders {
                 insert_if_ok(
                     "ability",
@@ -590,6 +647,10 @@ impl Module {
             });
         }
 
+        if let Some(validator) = validator {
+            MODULE.with(|module| validator.check_loaded(&module.borrow()));
+        }
+
         let campaign = Campaign::new(campaign_builder)?;
 
         MODULE.with(move |m| {
@@ -753,6 +814,7 @@ impl Module {
         ai_template, ai_templates, AITemplate;
         area, areas, Area;
         class, classes, Class;
+        condition, conditions, Condition;
         conversation, conversations, Conversation;
         cutscene, cutscenes, Cutscene;
         encounter, encounters, Encounter;
@@ -776,6 +838,95 @@ impl Module {
         })
     }
 
+    /// Returns the directories that all resources were read from, in the
+    /// order they were read
+    pub fn resource_dirs() -> Vec<String> {
+        MODULE.with(|r| r.borrow().dirs.clone())
+    }
+
+    /// Returns the directories that scripts are read from, in the order
+    /// they are read
+    pub fn script_dirs() -> Vec<PathBuf> {
+        Module::resource_dirs()
+            .iter()
+            .map(|dir| [dir, "scripts"].iter().collect())
+            .collect()
+    }
+
+    /// Reads all scripts from disk again, replacing the currently loaded
+    /// scripts.  Returns the IDs of all scripts that are new or whose
+    /// source has changed, including changes to any included scripts.
+    pub fn reload_scripts() -> Vec<String> {
+        MODULE.with(|r| {
+            let mut module = r.borrow_mut();
+
+            let mut scripts = read_to_string(&module.dirs, "scripts");
+            expand_include_directives(&mut scripts);
+
+            let mut changed: Vec<String> = scripts
+                .iter()
+                .filter(|(id, script)| module.scripts.get(*id) != Some(script))
+                .map(|(id, _)| id.to_string())
+                .collect();
+            changed.sort();
+
+            module.scripts = scripts;
+            changed
+        })
+    }
+
+    /// Reads the resource YAML from disk again, and rebuilds only the items,
+    /// abilities, and actors defined in any of the specified `files`,
+    /// replacing the currently loaded versions.  A resource that fails to
+    /// parse or build keeps its currently loaded version.  Returns the IDs of
+    /// all resources that were replaced.
+    pub fn reload_resources(files: &[PathBuf]) -> Result<Vec<String>, Error> {
+        let dirs = Module::resource_dirs();
+        let (root, layers) = match dirs.split_first() {
+            None => return invalid_data_error("No resource directories are loaded"),
+            Some(dirs) => dirs,
+        };
+        let mut yaml = YamlResourceSet::new(Path::new(root))?;
+        for dir in layers {
+            yaml.append(Path::new(dir));
+        }
+
+        MODULE.with(|m| {
+            let mut module = m.borrow_mut();
+            let mut reloaded = Vec::new();
+
+            // abilities first, as items and actors refer to them
+            let builders = changed_builders(&mut yaml, YamlResourceKind::Ability, files);
+            for (id, builder) in builders {
+                let ability = Ability::new(builder, &module);
+                if replace_if_ok("ability", &id, ability, &mut module.abilities) {
+                    reloaded.push(id);
+                }
+            }
+
+            let builders = changed_builders(&mut yaml, YamlResourceKind::Item, files);
+            for (id, builder) in builders {
+                let item = Item::new(builder, &module);
+                if replace_if_ok("item", &id, item, &mut module.items) {
+                    // versions with adjectives are created again from the new base item
+                    let prefix = format!("{}__ADJ__", id);
+                    module.items.retain(|key, _| !key.starts_with(&prefix));
+                    reloaded.push(id);
+                }
+            }
+
+            let builders = changed_builders(&mut yaml, YamlResourceKind::Actor, files);
+            for (id, builder) in builders {
+                let actor = Actor::new(builder, &mut module);
+                if replace_if_ok("actor", &id, actor, &mut module.actors) {
+                    reloaded.push(id);
+                }
+            }
+
+            Ok(reloaded)
+        })
+    }
+
     pub fn all_sizes() -> Vec<Rc<ObjectSize>> {
         MODULE.with(|r| all_resources(&r.borrow().sizes))
     }
@@ -805,6 +956,10 @@ impl Module {
         MODULE.with(|r| all_resources(&r.borrow().classes))
     }
 
+    pub fn all_conditions() -> Vec<Rc<Condition>> {
+        MODULE.with(|r| all_resources(&r.borrow().conditions))
+    }
+
     pub fn all_encounters() -> Vec<Rc<Encounter>> {
         MODULE.with(|r| all_resources(&r.borrow().encounters))
     }
@@ -834,6 +989,7 @@ impl Default for Module {
     fn default() -> Module {
         Module {
             root_dir: None,
+            dirs: Vec::new(),
             rules: None,
             campaign: None,
             abilities: HashMap::new(),
@@ -842,6 +998,7 @@ impl Default for Module {
             ai_templates: HashMap::new(),
             areas: HashMap::new(),
             classes: HashMap::new(),
+            conditions: HashMap::new(),
             conversations: HashMap::new(),
             cutscenes: HashMap::new(),
             items: HashMap::new(),
@@ -872,6 +1029,7 @@ struct ModuleBuilder {
     ai_builders: HashMap<String, AITemplate>,
     area_builders: HashMap<String, AreaBuilder>,
     class_builders: HashMap<String, ClassBuilder>,
+    condition_builders: HashMap<String, ConditionBuilder>,
     cutscene_builders: HashMap<String, CutsceneBuilder>,
     conversation_builders: HashMap<String, ConversationBuilder>,
     encounter_builders: HashMap<String, EncounterBuilder>,
@@ -897,6 +1055,7 @@ impl ModuleBuilder {
             ai_builders: read_builders(resources, AiTemplate)?,
             area_builders: read_builders(resources, Area)?,
             class_builders: read_builders(resources, Class)?,
+            condition_builders: read_builders(resources, Condition)?,
             conversation_builders: read_builders(resources, Conversation)?,
             cutscene_builders: read_builders(resources, Cutscene)?,
             encounter_builders: read_builders(resources, Encounter)?,
@@ -913,6 +1072,63 @@ impl ModuleBuilder {
     }
 }
 
+/// Reads the builders of the specified `kind` that are defined, in whole or in
+/// part, by any of `files`.  Builders that fail to parse are skipped.
+fn changed_builders<T: serde::de::DeserializeOwned>(
+    yaml: &mut YamlResourceSet,
+    kind: YamlResourceKind,
+    files: &[PathBuf],
+) -> Vec<(String, T)> {
+    let file_key = serde_yaml::Value::String(yaml_resource_set::FILE_VAL_STR.to_string());
+    let is_changed = |value: &serde_yaml::Value| match value {
+        serde_yaml::Value::String(file) => files.iter().any(|f| f == Path::new(file)),
+        _ => false,
+    };
+
+    let mut builders = Vec::new();
+    for (id, value) in yaml.resources.remove(&kind).unwrap_or_default() {
+        let changed = match &value {
+            serde_yaml::Value::Mapping(map) => match map.get(&file_key) {
+                Some(serde_yaml::Value::Sequence(seq)) => seq.iter().any(is_changed),
+                _ => false,
+            },
+            _ => false,
+        };
+
+        if !changed {
+            continue;
+        }
+
+        match read_builder(value) {
+            Ok(builder) => builders.push((id, builder)),
+            Err(e) => {
+                warn!("Unable to reload {:?} '{}', keeping the loaded version", kind, id);
+                warn!("{}", e);
+            }
+        }
+    }
+    builders
+}
+
+fn replace_if_ok<V>(
+    type_str: &str,
+    id: &str,
+    val: Result<V, Error>,
+    map: &mut HashMap<String, Rc<V>>,
+) -> bool {
+    match val {
+        Ok(val) => {
+            map.insert(id.to_string(), Rc::new(val));
+            true
+        }
+        Err(e) => {
+            warn!("Unable to reload {} '{}', keeping the loaded version", type_str, id);
+            warn!("{}", e);
+            false
+        }
+    }
+}
+
 struct IncludeExpansion {
     start_index: usize,
     end_index: usize,
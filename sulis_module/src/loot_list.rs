@@ -17,8 +17,10 @@
 use std::collections::HashMap;
 use std::io::Error;
 
+use sulis_core::resource::YamlResourceKind;
 use sulis_core::util::{gen_rand, unable_to_create_error};
 
+use crate::validator::Validator;
 use crate::{ItemState, Module};
 
 const MAX_DEPTH: u32 = 10;
@@ -424,3 +426,24 @@ pub struct LootListBuilder {
     #[serde(default)]
     sub_lists: HashMap<String, EntryBuilder>,
 }
+
+impl LootListBuilder {
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        let entries = self
+            .weighted_entries
+            .iter()
+            .chain(self.probability_entries.iter());
+        for (id, entry) in entries {
+            v.check(YamlResourceKind::Item, id);
+
+            let adjectives = entry.adjective1.keys().chain(entry.adjective2.keys());
+            for adjective in adjectives.filter(|adj| *adj != "none") {
+                v.check(YamlResourceKind::ItemAdjective, adjective);
+            }
+        }
+
+        for id in self.sub_lists.keys() {
+            v.check(YamlResourceKind::LootList, id);
+        }
+    }
+}
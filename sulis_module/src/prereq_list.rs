@@ -18,7 +18,10 @@ use std::collections::{HashMap, HashSet};
 use std::io::Error;
 use std::rc::Rc;
 
+use sulis_core::resource::YamlResourceKind;
+
 use crate::rules::Attribute;
+use crate::validator::Validator;
 
 use crate::{Actor, Module};
 
@@ -194,3 +197,23 @@ pub struct PrereqListBuilder {
     pub race: Option<String>,
     pub abilities: Option<Vec<String>>,
 }
+
+impl PrereqListBuilder {
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        if let Some(ref levels) = self.levels {
+            for class_id in levels.keys() {
+                v.check(YamlResourceKind::Class, class_id);
+            }
+        }
+
+        if let Some(ref race) = self.race {
+            v.check(YamlResourceKind::Race, race);
+        }
+
+        if let Some(ref abilities) = self.abilities {
+            for ability_id in abilities.iter() {
+                v.check(YamlResourceKind::Ability, ability_id);
+            }
+        }
+    }
+}
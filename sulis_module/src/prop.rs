@@ -19,11 +19,12 @@ use std::rc::Rc;
 
 use sulis_core::image::Image;
 use sulis_core::io::DrawList;
-use sulis_core::resource::ResourceSet;
+use sulis_core::resource::{ResourceSet, YamlResourceKind};
 use sulis_core::ui::AnimationState;
 use sulis_core::util::{unable_to_create_error, Offset, Point, Rect};
 
 use crate::area::tile::verify_point;
+use crate::validator::Validator;
 use crate::{LootList, Module, ObjectSize, OnTrigger};
 
 #[derive(Debug)]
@@ -230,3 +231,17 @@ pub struct PropBuilder {
     pub interactive: InteractiveBuilder,
     pub status_text: Option<String>,
 }
+
+impl PropBuilder {
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        v.check(YamlResourceKind::Size, &self.size);
+
+        match self.interactive {
+            InteractiveBuilder::Container { loot: Some(ref loot) } => {
+                v.check(YamlResourceKind::LootList, loot);
+            }
+            InteractiveBuilder::Door { ref on_activate, .. } => v.check_triggers(on_activate),
+            _ => (),
+        }
+    }
+}
@@ -21,11 +21,12 @@ use std::rc::Rc;
 
 use crate::rules::{bonus::AttackBuilder, BonusList, Slot};
 use sulis_core::image::Image;
-use sulis_core::resource::ResourceSet;
+use sulis_core::resource::{ResourceSet, YamlResourceKind};
 use sulis_core::ui::Color;
 use sulis_core::util::{gen_rand, unable_to_create_error, Point};
 
 use crate::actor::Sex;
+use crate::validator::Validator;
 
 use crate::{ImageLayer, ImageLayerSet, Module, ObjectSize, Prop};
 
@@ -268,3 +269,13 @@ pub struct RaceBuilder {
     #[serde(default)]
     disabled_slots: Vec<Slot>,
 }
+
+impl RaceBuilder {
+    pub(crate) fn validate(&self, v: &mut Validator) {
+        v.check(YamlResourceKind::Size, &self.size);
+
+        if let Some(ref prop) = self.pc_death_prop {
+            v.check(YamlResourceKind::Prop, prop);
+        }
+    }
+}
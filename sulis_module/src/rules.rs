@@ -14,7 +14,7 @@
 //  You should have received a copy of the GNU General Public License
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
-use std::cmp::max;
+use std::cmp::{max, Ordering};
 use std::collections::HashMap;
 use std::{
     fmt,
@@ -32,7 +32,9 @@ pub use self::attack::AttackKind;
 
 pub mod attribute;
 pub use self::attribute::Attribute;
+pub use self::attribute::AttributeData;
 pub use self::attribute::AttributeList;
+pub use self::attribute::DerivedStat;
 
 pub mod bonus;
 pub use self::bonus::AttackBonuses;
@@ -43,17 +45,26 @@ pub use self::bonus::BonusList;
 pub mod damage;
 pub use self::damage::Damage;
 pub use self::damage::DamageKind;
+pub use self::damage::DamageKindData;
 pub use self::damage::DamageList;
 
+pub mod expression;
+pub use self::expression::Expression;
+
+pub mod formulas;
+pub use self::formulas::Formulas;
+
 pub mod resistance;
 pub use self::resistance::Resistance;
 
 pub mod stat_list;
+pub use self::stat_list::AttackRoll;
+pub use self::stat_list::HitChances;
 pub use self::stat_list::StatList;
 
 use crate::area::LocationKind;
 use sulis_core::ui::{color, Color};
-use sulis_core::util::{gen_rand, invalid_data_error};
+use sulis_core::util::{gen_rand, invalid_data_error, ReproducibleRandom};
 
 #[derive(Deserialize, Debug)]
 #[serde(deny_unknown_fields)]
@@ -69,6 +80,12 @@ pub struct Rules {
     pub attack_ap: u32,
     pub display_ap: u32,
     pub swap_weapons_ap: u32,
+    #[serde(default = "default_reaction_ap")]
+    pub reaction_ap: u32,
+    #[serde(default = "default_reactions_per_round")]
+    pub reactions_per_round: u32,
+    #[serde(default)]
+    pub attacks_of_opportunity: bool,
     pub initiative_roll_max: i32,
     pub base_flanking_angle: i32,
     pub graze_percentile: u32,
@@ -78,11 +95,53 @@ pub struct Rules {
     pub flanking_accuracy_bonus: i32,
     pub hidden_accuracy_bonus: i32,
 
+    #[serde(default = "default_partial_cover_defense_bonus")]
+    pub partial_cover_defense_bonus: i32,
+    #[serde(default)]
+    pub partial_cover_concealment: i32,
+    #[serde(default = "default_full_cover_defense_bonus")]
+    pub full_cover_defense_bonus: i32,
+    #[serde(default = "default_full_cover_concealment")]
+    pub full_cover_concealment: i32,
+
+    #[serde(default)]
+    pub higher_ground_accuracy_bonus: i32,
+    #[serde(default)]
+    pub higher_ground_damage_multiplier: f32,
+    #[serde(default)]
+    pub higher_ground_range_bonus: f32,
+    #[serde(default)]
+    pub lower_ground_accuracy_penalty: i32,
+    #[serde(default)]
+    pub lower_ground_range_penalty: f32,
+
+    #[serde(default = "default_base_perception")]
+    pub base_perception: i32,
+    #[serde(default = "default_detection_roll_max")]
+    pub detection_roll_max: i32,
+    #[serde(default = "default_detection_alert_margin")]
+    pub detection_alert_margin: i32,
+    #[serde(default = "default_detection_break_even_distance")]
+    pub detection_break_even_distance: f32,
+    #[serde(default = "default_detection_distance_factor")]
+    pub detection_distance_factor: f32,
+    #[serde(default = "default_detection_darkness_bonus")]
+    pub detection_darkness_bonus: i32,
+    #[serde(default = "default_detection_suspicious_bonus")]
+    pub detection_suspicious_bonus: i32,
+    #[serde(default = "default_detection_suspicious_rounds")]
+    pub detection_suspicious_rounds: u32,
+    #[serde(default = "default_attack_noise_radius")]
+    pub attack_noise_radius: f32,
+    #[serde(default = "default_door_noise_radius")]
+    pub door_noise_radius: f32,
+
     pub graze_damage_multiplier: f32,
     pub crit_damage_multiplier: f32,
 
     pub dual_wield_damage_multiplier: f32,
 
+    pub attributes: Vec<AttributeData>,
     pub base_attribute: i32,
     pub builder_max_attribute: i32,
     pub builder_min_attribute: i32,
@@ -115,6 +174,11 @@ pub struct Rules {
 
     pub area_colors: HashMap<LocationKind, Vec<Color>>,
 
+    pub damage_kinds: Vec<DamageKindData>,
+
+    #[serde(default)]
+    pub formulas: Formulas,
+
     pub hints: Vec<String>,
 
     pub main_menu_music: Option<String>,
@@ -192,6 +256,36 @@ impl Rules {
             }
         }
 
+        if self.attributes.is_empty() {
+            return invalid_data_error("Must specify at least one attribute.");
+        }
+
+        for (index, attr) in self.attributes.iter().enumerate() {
+            let prev = &self.attributes[..index];
+            if prev.iter().any(|other| other.id == attr.id) {
+                return invalid_data_error(&format!("Duplicate attribute '{}'", attr.id));
+            }
+
+            if prev.iter().any(|other| other.short_name == attr.short_name) {
+                return invalid_data_error(&format!(
+                    "Duplicate attribute short name '{}'",
+                    attr.short_name
+                ));
+            }
+        }
+
+        for (index, kind) in self.damage_kinds.iter().enumerate() {
+            if self.damage_kinds[..index].iter().any(|other| other.id == kind.id) {
+                return invalid_data_error(&format!("Duplicate damage kind '{}'", kind.id));
+            }
+        }
+
+        if self.damage_kinds.iter().filter(|kind| kind.raw).count() != 1 {
+            return invalid_data_error("Exactly one damage kind must be marked as raw.");
+        }
+
+        self.formulas.validate(&self.attributes)?;
+
         Ok(())
     }
 
@@ -242,7 +336,7 @@ impl Rules {
     }
 
     /// Computes the amount of damage that this damage list will apply to the given
-    /// `armor`.  Each damage component of this list is rolled randomly, with the resulting
+    /// `armor`.  Each damage component of this list is rolled using `rand`, with the resulting
     /// damage then multiplied by the `multiplier`, rounded down.  The damage is then
     /// modified by the percentage resistance, if any.  The armor against
     /// the base damage kind of this damage is then subtracted from the damage, capped
@@ -256,7 +350,26 @@ impl Rules {
         armor: &Armor,
         resistance: &Resistance,
         multiplier: f32,
+        rand: &mut ReproducibleRandom,
     ) -> Vec<(DamageKind, u32)> {
+        self.roll_damage_detail(damage, armor, resistance, multiplier, rand)
+            .into_iter()
+            .filter(|roll| roll.amount > 0)
+            .map(|roll| (roll.kind, roll.amount))
+            .collect()
+    }
+
+    /// Rolls damage in the same way as `roll_damage`, but returns the full
+    /// breakdown of each damage component, including the amount removed by
+    /// resistance and armor.  Components reduced to zero damage are included.
+    pub fn roll_damage_detail(
+        &self,
+        damage: &DamageList,
+        armor: &Armor,
+        resistance: &Resistance,
+        multiplier: f32,
+        rand: &mut ReproducibleRandom,
+    ) -> Vec<DamageRoll> {
         debug!(
             "Rolling damage from {} to {} vs {} base armor",
             damage.min(),
@@ -270,31 +383,74 @@ impl Rules {
 
         let mut output = Vec::new();
         for damage in damage.iter() {
-            let kind = damage.kind.unwrap();
+            let rolled = damage.roll(rand);
+            output.push(self.apply_damage(damage, rolled, armor, resistance, multiplier));
+        }
 
-            let resistance = (100 - resistance.amount(kind)) as f32 / 100.0;
-            let amount = damage.roll() as f32 * multiplier * resistance;
+        output
+    }
 
-            let armor = max(0, armor.amount(kind) as i32 - damage.ap as i32) as u32;
-            let armor_max = self.armor_damage_reduction_cap(armor) as f32 * amount / 100.0;
-            let armor = armor as f32;
+    /// Computes the minimum and maximum total damage that `roll_damage` could
+    /// produce with the given parameters, without rolling any random numbers.
+    pub fn damage_range(
+        &self,
+        damage: &DamageList,
+        armor: &Armor,
+        resistance: &Resistance,
+        multiplier: f32,
+    ) -> (u32, u32) {
+        let mut min = 0;
+        let mut max = 0;
+        for damage in damage.iter() {
+            min += self.apply_damage(damage, damage.min, armor, resistance, multiplier).amount;
+            max += self.apply_damage(damage, damage.max, armor, resistance, multiplier).amount;
+        }
 
-            let armor = if armor_max > armor { armor } else { armor_max };
-            let armor = if armor > amount { amount } else { armor };
+        (min, max)
+    }
 
-            let amount = amount - armor;
-            if amount > 0.0 {
-                output.push((kind, amount.ceil() as u32));
-            }
+    fn apply_damage(
+        &self,
+        damage: &Damage,
+        rolled: u32,
+        armor: &Armor,
+        resistance: &Resistance,
+        multiplier: f32,
+    ) -> DamageRoll {
+        let kind = damage.kind.unwrap();
+
+        let resistance = resistance.amount(kind);
+        let before_armor = rolled as f32 * multiplier * (100 - resistance) as f32 / 100.0;
+        let amount = before_armor;
+
+        let armor = max(0, armor.amount(kind) as i32 - damage.ap as i32) as u32;
+        let armor_max = self.armor_damage_reduction_cap(armor) as f32 * amount / 100.0;
+        let armor = armor as f32;
+
+        let armor = if armor_max > armor { armor } else { armor_max };
+        let armor = if armor > amount { amount } else { armor };
+
+        let amount = amount - armor;
+        DamageRoll {
+            kind,
+            rolled,
+            multiplier,
+            resistance,
+            before_armor,
+            armor,
+            amount: if amount > 0.0 { amount.ceil() as u32 } else { 0 },
         }
-
-        output
     }
 
     /// Returns the percentile armor reduction cap for the given armor value.  this
     /// is the maximum percentage that the armor of that level can reduce a damage
     /// amount by.  the remaining damage is rounded up.
     pub fn armor_damage_reduction_cap(&self, armor: u32) -> u32 {
+        if let Some(formula) = &self.formulas.armor_damage_reduction_cap {
+            let vars = |name: &str| if name == "armor" { Some(armor as f32) } else { None };
+            return formula.eval(&vars).clamp(0.0, 100.0) as u32;
+        }
+
         *self
             .armor_damage_reduction_cap
             .get(armor as usize)
@@ -305,6 +461,12 @@ impl Rules {
         if cur_level < 1 {
             return 0;
         }
+
+        if let Some(formula) = &self.formulas.xp_for_level {
+            let vars = |name: &str| if name == "level" { Some(cur_level as f32) } else { None };
+            return formula.eval(&vars).max(0.0) as u32;
+        }
+
         if cur_level > self.experience_for_level.len() as u32 {
             return 0;
         }
@@ -312,16 +474,116 @@ impl Rules {
         self.experience_for_level[(cur_level - 1) as usize]
     }
 
-    pub fn concealment_roll(&self, concealment: i32) -> bool {
+    /// The bonus to defense and reflex granted to a target with the
+    /// specified cover
+    pub fn cover_defense_bonus(&self, cover: Cover) -> i32 {
+        match cover {
+            Cover::None => 0,
+            Cover::Partial => self.partial_cover_defense_bonus,
+            Cover::Full => self.full_cover_defense_bonus,
+        }
+    }
+
+    /// The concealment granted to a target with the specified cover
+    pub fn cover_concealment(&self, cover: Cover) -> i32 {
+        match cover {
+            Cover::None => 0,
+            Cover::Partial => self.partial_cover_concealment,
+            Cover::Full => self.full_cover_concealment,
+        }
+    }
+
+    /// The accuracy bonus for an attacker standing `elevation` above its
+    /// target.  Attacks from lower ground, with a negative `elevation`, are
+    /// penalized instead
+    pub fn elevation_accuracy_bonus(&self, elevation: i32) -> i32 {
+        match elevation.cmp(&0) {
+            Ordering::Greater => self.higher_ground_accuracy_bonus,
+            Ordering::Less => -self.lower_ground_accuracy_penalty,
+            Ordering::Equal => 0,
+        }
+    }
+
+    /// The bonus to ranged attack distance for an attacker standing
+    /// `elevation` above its target
+    pub fn elevation_range_bonus(&self, elevation: i32) -> f32 {
+        match elevation.cmp(&0) {
+            Ordering::Greater => self.higher_ground_range_bonus,
+            Ordering::Less => -self.lower_ground_range_penalty,
+            Ordering::Equal => 0.0,
+        }
+    }
+
+    pub fn concealment_roll(&self, concealment: i32, rand: &mut ReproducibleRandom) -> bool {
         if concealment == 0 {
             return true;
         }
-        let roll = gen_rand(1, 101);
+        let roll = rand.gen(1, 101);
         debug!("Concealment roll: {} against {}", roll, concealment);
         roll > concealment
     }
 }
 
+fn default_reaction_ap() -> u32 {
+    2000
+}
+
+fn default_reactions_per_round() -> u32 {
+    1
+}
+
+fn default_partial_cover_defense_bonus() -> i32 {
+    10
+}
+
+fn default_full_cover_defense_bonus() -> i32 {
+    20
+}
+
+fn default_full_cover_concealment() -> i32 {
+    20
+}
+
+fn default_base_perception() -> i32 {
+    50
+}
+
+fn default_detection_roll_max() -> i32 {
+    20
+}
+
+fn default_detection_alert_margin() -> i32 {
+    30
+}
+
+fn default_detection_break_even_distance() -> f32 {
+    5.0
+}
+
+fn default_detection_distance_factor() -> f32 {
+    10.0
+}
+
+fn default_detection_darkness_bonus() -> i32 {
+    30
+}
+
+fn default_detection_suspicious_bonus() -> i32 {
+    10
+}
+
+fn default_detection_suspicious_rounds() -> u32 {
+    3
+}
+
+fn default_attack_noise_radius() -> f32 {
+    8.0
+}
+
+fn default_door_noise_radius() -> f32 {
+    6.0
+}
+
 pub const ROUND_TIME_MILLIS: u32 = 5000;
 
 #[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Eq)]
@@ -503,6 +765,31 @@ pub enum ItemKind {
     Other,
 }
 
+/// The breakdown of a single damage component, as rolled by
+/// `Rules::roll_damage_detail`
+#[derive(Debug, Clone, Copy)]
+pub struct DamageRoll {
+    pub kind: DamageKind,
+
+    /// The damage initially rolled, before any modifiers
+    pub rolled: u32,
+
+    /// The multiplier applied to the rolled damage, based on the hit kind
+    pub multiplier: f32,
+
+    /// The percentage resistance of the target to this damage kind
+    pub resistance: i32,
+
+    /// The damage after the multiplier and resistance were applied
+    pub before_armor: f32,
+
+    /// The amount of damage absorbed by the target's armor
+    pub armor: f32,
+
+    /// The final damage amount, which may be zero
+    pub amount: u32,
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
 pub struct HitFlags {
     pub flanking: bool,
@@ -520,6 +807,16 @@ impl Default for HitFlags {
     }
 }
 
+/// How much of a target is shielded from an attacker by props and walls
+/// between them.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Default)]
+pub enum Cover {
+    #[default]
+    None,
+    Partial,
+    Full,
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
 pub enum HitKind {
     Miss,
@@ -16,20 +16,14 @@
 
 use crate::rules::DamageKind;
 
-#[derive(Deserialize, Debug, Clone)]
+// The armor against each damage kind, by kind index.  Kinds past the end
+// of the list have not been modified individually and so are equal to the
+// base armor.
+#[derive(Deserialize, Debug, Clone, Default)]
 #[serde(deny_unknown_fields)]
 pub struct Armor {
     base: i32,
-    kinds: [i32; 7],
-}
-
-impl Default for Armor {
-    fn default() -> Armor {
-        Armor {
-            base: 0,
-            kinds: [0; 7],
-        }
-    }
+    kinds: Vec<i32>,
 }
 
 impl Armor {
@@ -54,22 +48,25 @@ impl Armor {
     }
 
     pub fn add_kind(&mut self, kind: DamageKind, amount: i32) {
-        if kind == DamageKind::Raw {
+        if kind.is_raw() {
             return;
         }
 
         let index = kind.index();
+        if index >= self.kinds.len() {
+            self.kinds.resize(index + 1, self.base);
+        }
         self.kinds[index] += amount;
     }
 
     /// Returns the amount of armor that this Armor value
     /// applies to the specified damage kind.
     pub fn amount(&self, check_kind: DamageKind) -> i32 {
-        if check_kind == DamageKind::Raw {
+        if check_kind.is_raw() {
             return 0;
         }
 
-        self.kinds[check_kind.index()]
+        *self.kinds.get(check_kind.index()).unwrap_or(&self.base)
     }
 
     pub fn base(&self) -> i32 {
@@ -91,10 +88,10 @@ impl Armor {
     }
 
     pub fn differs_from_base(&self, kind: DamageKind) -> bool {
-        if kind == DamageKind::Raw {
+        if kind.is_raw() {
             return true;
         }
 
-        self.kinds[kind.index()] != self.base
+        self.amount(kind) != self.base
     }
 }
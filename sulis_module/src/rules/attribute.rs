@@ -14,31 +14,71 @@
 //  You should have received a copy of the GNU General Public License
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::slice::Iter;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{Error, ErrorKind};
 
-use self::Attribute::*;
+use serde::ser::{Serialize, SerializeMap, Serializer};
 
-#[derive(Deserialize, Serialize, Debug, Copy, Clone)]
+thread_local! {
+    static ATTRIBUTES: RefCell<Vec<AttributeData>> = RefCell::new(Vec::new());
+}
+
+/// A stat which attributes contribute to, as declared in the rules.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum DerivedStat {
+    Initiative,
+    MeleeAccuracy,
+    RangedAccuracy,
+    SpellAccuracy,
+    Defense,
+    Fortitude,
+    Reflex,
+    Will,
+    Perception,
+
+    /// Hit points gained for each character level
+    HpPerLevel,
+
+    MeleeGrazeMultiplier,
+    MeleeHitMultiplier,
+    MeleeCritMultiplier,
+    RangedGrazeMultiplier,
+    RangedHitMultiplier,
+    RangedCritMultiplier,
+}
+
+#[derive(Deserialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
-pub struct AttributeList {
-    #[serde(rename = "str")]
-    pub strength: u8,
+pub struct AttributeData {
+    pub id: String,
 
-    #[serde(rename = "dex")]
-    pub dexterity: u8,
+    /// The name shown to the player, defaulting to the ID
+    #[serde(default)]
+    pub name: Option<String>,
 
-    #[serde(rename = "end")]
-    pub endurance: u8,
+    /// The short name used as a key in attribute lists and in theme text args
+    pub short_name: String,
 
-    #[serde(rename = "per")]
-    pub perception: u8,
+    #[serde(default)]
+    pub description: String,
 
-    #[serde(rename = "int")]
-    pub intellect: u8,
+    /// The amount added to each derived stat for each point this attribute is
+    /// above (or subtracted for each point below) the base attribute value.
+    #[serde(default)]
+    pub stats: HashMap<DerivedStat, f32>,
+}
 
-    #[serde(rename = "wis")]
-    pub wisdom: u8,
+/// The value of each attribute, indexed by `Attribute`.  Attribute lists are
+/// serialized as a map from each attribute's short name to its value, and
+/// must contain every attribute declared in the rules.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(try_from = "HashMap<String, u8>")]
+pub struct AttributeList {
+    values: Vec<u8>,
 }
 
 fn get_new_val(cur: u8, delta: i8) -> u8 {
@@ -52,42 +92,32 @@ fn get_new_val(cur: u8, delta: i8) -> u8 {
     }
 }
 
+// Derived stat contributions are truncated to integers individually,
+// after rounding to remove float error from fractional factors
+fn truncate(value: f32) -> i32 {
+    (value * 1000.0).round() as i32 / 1000
+}
+
 impl AttributeList {
     pub fn new(base_value: u8) -> AttributeList {
         AttributeList {
-            strength: base_value,
-            dexterity: base_value,
-            endurance: base_value,
-            perception: base_value,
-            intellect: base_value,
-            wisdom: base_value,
+            values: vec![base_value; Attribute::count()],
         }
     }
 
-    pub fn bonus(self, attr: Attribute, base_attr: i32) -> i32 {
+    pub fn bonus(&self, attr: Attribute, base_attr: i32) -> i32 {
         self.get(attr) as i32 - base_attr
     }
 
-    pub fn get(self, attr: Attribute) -> u8 {
-        match attr {
-            Strength => self.strength,
-            Dexterity => self.dexterity,
-            Endurance => self.endurance,
-            Perception => self.perception,
-            Intellect => self.intellect,
-            Wisdom => self.wisdom,
-        }
+    pub fn get(&self, attr: Attribute) -> u8 {
+        self.values.get(attr.index).copied().unwrap_or(0)
     }
 
     pub fn set(&mut self, attr: Attribute, value: u8) {
-        match attr {
-            Strength => self.strength = value,
-            Dexterity => self.dexterity = value,
-            Endurance => self.endurance = value,
-            Perception => self.perception = value,
-            Intellect => self.intellect = value,
-            Wisdom => self.wisdom = value,
+        if attr.index >= self.values.len() {
+            self.values.resize(attr.index + 1, 0);
         }
+        self.values[attr.index] = value;
     }
 
     pub fn add_all(&mut self, attrs: &HashMap<Attribute, i8>) {
@@ -97,79 +127,166 @@ impl AttributeList {
     }
 
     pub fn add(&mut self, attr: Attribute, value: i8) {
-        match attr {
-            Strength => self.strength = get_new_val(self.strength, value),
-            Dexterity => self.dexterity = get_new_val(self.dexterity, value),
-            Endurance => self.endurance = get_new_val(self.endurance, value),
-            Perception => self.perception = get_new_val(self.perception, value),
-            Intellect => self.intellect = get_new_val(self.intellect, value),
-            Wisdom => self.wisdom = get_new_val(self.wisdom, value),
+        let new_val = get_new_val(self.get(attr), value);
+        self.set(attr, new_val);
+    }
+
+    pub fn sum(&self, other: &AttributeList) -> AttributeList {
+        let mut result = AttributeList::new(0);
+        for attr in Attribute::iter() {
+            result.set(attr, self.get(attr).saturating_add(other.get(attr)));
         }
+        result
     }
 
-    pub fn sum(self, other: AttributeList) -> AttributeList {
-        AttributeList {
-            strength: self.strength + other.strength,
-            dexterity: self.dexterity + other.dexterity,
-            endurance: self.endurance + other.endurance,
-            perception: self.perception + other.perception,
-            intellect: self.intellect + other.intellect,
-            wisdom: self.wisdom + other.wisdom,
+    /// Returns the total integer contribution of all attributes to the
+    /// specified stat.  Each attribute's bonus is scaled by `multiplier`,
+    /// such as the character level for `HpPerLevel`.
+    pub fn derived_int(&self, stat: DerivedStat, base_attr: i32, multiplier: i32) -> i32 {
+        Attribute::iter()
+            .map(|attr| {
+                let bonus = self.bonus(attr, base_attr) * multiplier;
+                truncate(bonus as f32 * attr.stat_factor(stat))
+            })
+            .sum()
+    }
+
+    /// Returns the total contribution of all attributes to the specified
+    /// stat, without any truncation.
+    pub fn derived(&self, stat: DerivedStat, base_attr: i32) -> f32 {
+        Attribute::iter()
+            .map(|attr| self.bonus(attr, base_attr) as f32 * attr.stat_factor(stat))
+            .sum()
+    }
+}
+
+impl TryFrom<HashMap<String, u8>> for AttributeList {
+    type Error = Error;
+
+    fn try_from(map: HashMap<String, u8>) -> Result<Self, Self::Error> {
+        for key in map.keys() {
+            if Attribute::from_short_name(key).is_none() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Unknown attribute '{}' in attribute list", key),
+                ));
+            }
         }
+
+        let mut values = Vec::new();
+        for attr in Attribute::iter() {
+            match map.get(&attr.short_name()) {
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("Attribute list is missing '{}'", attr.short_name()),
+                    ))
+                }
+                Some(value) => values.push(*value),
+            }
+        }
+
+        Ok(AttributeList { values })
     }
 }
 
-#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
-#[serde(deny_unknown_fields)]
-pub enum Attribute {
-    Strength,
-    Dexterity,
-    Endurance,
-    Perception,
-    Intellect,
-    Wisdom,
+impl Serialize for AttributeList {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(Attribute::count()))?;
+        for attr in Attribute::iter() {
+            map.serialize_entry(&attr.short_name(), &self.get(attr))?;
+        }
+        map.end()
+    }
 }
 
-const ATTRS_LIST: [Attribute; 6] = [
-    Strength, Dexterity, Endurance, Perception, Intellect, Wisdom,
-];
+/// An attribute, such as Strength.  The set of attributes is declared in the
+/// rules, and each attribute is identified by its position in that
+/// declaration.  Attributes are serialized using their ID.
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+pub struct Attribute {
+    index: usize,
+}
 
 impl Attribute {
+    /// Sets the attributes that may be referenced.  This must be called
+    /// when the rules are loaded, before any other resources are read.
+    pub(crate) fn set_attributes(attributes: &[AttributeData]) {
+        ATTRIBUTES.with(|a| *a.borrow_mut() = attributes.to_vec());
+    }
+
     pub fn from(text: &str) -> Option<Attribute> {
-        Some(match text {
-            "Strength" => Strength,
-            "Dexterity" => Dexterity,
-            "Endurance" => Endurance,
-            "Perception" => Perception,
-            "Intellect" => Intellect,
-            "Wisdom" => Wisdom,
-            _ => return None,
-        })
-    }
-
-    pub fn name(&self) -> &str {
-        match *self {
-            Strength => "Strength",
-            Dexterity => "Dexterity",
-            Endurance => "Endurance",
-            Perception => "Perception",
-            Intellect => "Intellect",
-            Wisdom => "Wisdom",
-        }
+        let index = ATTRIBUTES.with(|a| a.borrow().iter().position(|attr| attr.id == text));
+        index.map(|index| Attribute { index })
+    }
+
+    pub fn from_short_name(text: &str) -> Option<Attribute> {
+        let index =
+            ATTRIBUTES.with(|a| a.borrow().iter().position(|attr| attr.short_name == text));
+        index.map(|index| Attribute { index })
+    }
+
+    pub fn iter() -> impl Iterator<Item = Attribute> {
+        (0..Attribute::count()).map(|index| Attribute { index })
+    }
+
+    pub fn count() -> usize {
+        ATTRIBUTES.with(|a| a.borrow().len())
+    }
+
+    pub fn index(self) -> usize {
+        self.index
+    }
+
+    pub fn id(self) -> String {
+        self.with_data(|data| data.id.to_string())
+    }
+
+    pub fn name(self) -> String {
+        self.with_data(|data| data.name.as_ref().unwrap_or(&data.id).to_string())
     }
 
-    pub fn short_name(&self) -> &str {
-        match *self {
-            Strength => "str",
-            Dexterity => "dex",
-            Endurance => "end",
-            Perception => "per",
-            Intellect => "int",
-            Wisdom => "wis",
+    pub fn short_name(self) -> String {
+        self.with_data(|data| data.short_name.to_string())
+    }
+
+    pub fn description(self) -> String {
+        self.with_data(|data| data.description.to_string())
+    }
+
+    /// The amount `stat` changes for each point of this attribute
+    pub fn stat_factor(self, stat: DerivedStat) -> f32 {
+        self.with_data(|data| *data.stats.get(&stat).unwrap_or(&0.0))
+    }
+
+    fn with_data<T, F: FnOnce(&AttributeData) -> T>(self, f: F) -> T {
+        ATTRIBUTES.with(|a| f(&a.borrow()[self.index]))
+    }
+}
+
+impl fmt::Debug for Attribute {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.id())
+    }
+}
+
+impl TryFrom<String> for Attribute {
+    type Error = Error;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        match Attribute::from(&id) {
+            Some(attr) => Ok(attr),
+            None => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Unable to parse Attribute from '{}'", id),
+            )),
         }
     }
+}
 
-    pub fn iter() -> Iter<'static, Attribute> {
-        ATTRS_LIST.iter()
+impl From<Attribute> for String {
+    fn from(attr: Attribute) -> String {
+        attr.id()
     }
 }
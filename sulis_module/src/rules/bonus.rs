@@ -46,6 +46,8 @@ pub enum BonusKind {
     Will(i32),
     Concealment(i32),
     ConcealmentIgnore(i32),
+    Stealth(i32),
+    Perception(i32),
     CritChance(i32),
     HitThreshold(i32),
     GrazeThreshold(i32),
@@ -66,6 +68,7 @@ pub enum BonusKind {
     FlankedImmunity,
     SneakAttackImmunity,
     CritImmunity,
+    ConditionImmunity(String),
     GroupUsesPerEncounter { group: String, amount: ExtInt },
     GroupUsesPerDay { group: String, amount: ExtInt },
     ClassStat { id: String, amount: i32 },
@@ -102,6 +105,10 @@ pub enum Contingent {
     /// Bonuses that are only applied to attacks when the attacker is flanking
     AttackWhenFlanking,
 
+    /// Bonuses that are only applied to attacks when the attacker is standing
+    /// on higher ground than the target
+    AttackFromHigherGround,
+
     /// Bonuses that are only applied to attacks with the specified base damage kind
     AttackWithDamageKind(DamageKind),
 
@@ -218,6 +225,8 @@ fn apply_modifiers(bonus: &mut Bonus, neg: f32, pos: f32) {
         Will(val) => get_mod!(Will(val): i32, neg, pos),
         Concealment(val) => get_mod!(Concealment(val): i32, neg, pos),
         ConcealmentIgnore(val) => get_mod!(ConcealmentIgnore(val): i32, neg, pos),
+        Stealth(val) => get_mod!(Stealth(val): i32, neg, pos),
+        Perception(val) => get_mod!(Perception(val): i32, neg, pos),
         CritChance(val) => get_mod!(CritChance(val): i32, neg, pos),
         HitThreshold(val) => get_mod!(HitThreshold(val): i32, neg, pos),
         GrazeThreshold(val) => get_mod!(GrazeThreshold(val): i32, neg, pos),
@@ -248,6 +257,7 @@ fn apply_modifiers(bonus: &mut Bonus, neg: f32, pos: f32) {
         | FlankedImmunity
         | SneakAttackImmunity
         | CritImmunity
+        | ConditionImmunity(_)
         | AbilitiesDisabled
         | FreeAbilityGroupUse => return,
     };
@@ -351,6 +361,13 @@ pub fn merge_if_dup(first: &Bonus, sec: &Bonus) -> Option<Bonus> {
         SneakAttackImmunity => merge_dup!(SneakAttackImmunity: sec, when),
         CritImmunity => merge_dup!(CritImmunity: sec, when),
         FreeAbilityGroupUse => merge_dup!(FreeAbilityGroupUse: sec, when),
+        ConditionImmunity(ref id) => match sec.kind {
+            ConditionImmunity(ref other) if id == other => Some(Bonus {
+                when,
+                kind: ConditionImmunity(id.clone()),
+            }),
+            _ => None,
+        },
 
         GroupUsesPerEncounter { ref group, amount } => {
             merge_dup!(GroupUsesPerEncounter{ref group, amount}: sec, when)
@@ -376,6 +393,8 @@ pub fn merge_if_dup(first: &Bonus, sec: &Bonus) -> Option<Bonus> {
         Will(val) => merge_dup!(Will(val): sec, when),
         Concealment(val) => merge_dup!(Concealment(val): sec, when),
         ConcealmentIgnore(val) => merge_dup!(ConcealmentIgnore(val): sec, when),
+        Stealth(val) => merge_dup!(Stealth(val): sec, when),
+        Perception(val) => merge_dup!(Perception(val): sec, when),
         CritChance(val) => merge_dup!(CritChance(val): sec, when),
         HitThreshold(val) => merge_dup!(HitThreshold(val): sec, when),
         GrazeThreshold(val) => merge_dup!(GrazeThreshold(val): sec, when),
@@ -14,10 +14,18 @@
 //  You should have received a copy of the GNU General Public License
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
+use std::cell::RefCell;
+use std::convert::TryFrom;
 use std::fmt::{self, Display};
-use std::slice::Iter;
+use std::io::{Error, ErrorKind};
+use std::str::FromStr;
 
-use sulis_core::util::gen_rand;
+use sulis_core::ui::Color;
+use sulis_core::util::ReproducibleRandom;
+
+thread_local! {
+    static DAMAGE_KINDS: RefCell<Vec<DamageKindData>> = RefCell::new(Vec::new());
+}
 
 #[derive(Clone)]
 pub struct DamageList {
@@ -171,78 +179,132 @@ impl DamageList {
     }
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+/// The definition of a kind of damage, as declared in the rules file
+#[derive(Deserialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
-pub enum DamageKind {
-    Slashing,
-    Piercing,
-    Crushing,
-    Acid,
-    Cold,
-    Shock,
-    Fire,
-    Raw,
-}
+pub struct DamageKindData {
+    pub id: String,
 
-use crate::rules::DamageKind::*;
+    #[serde(default)]
+    pub color: Color,
 
-// This array MUST be in the same order as the ordering on the DamageKind enum
-// This is top to bottom declaration order for derived.
-const DAMAGE_KINDS: [DamageKind; 8] = [Slashing, Piercing, Crushing, Acid, Cold, Shock, Fire, Raw];
+    /// An optional image shown alongside damage of this kind
+    #[serde(default)]
+    pub icon: Option<String>,
+
+    /// Raw damage is not reduced by armor or resistance.  Exactly one kind
+    /// must be raw; it is also used when a kind cannot be parsed.
+    #[serde(default)]
+    pub raw: bool,
+}
+
+/// A kind of damage, such as Slashing or Fire.  The set of damage kinds is
+/// declared in the rules, and each kind is identified by its position in
+/// that declaration.  Kinds are ordered by declaration order, and are
+/// serialized using their ID.
+#[derive(Deserialize, Serialize, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+pub struct DamageKind {
+    index: usize,
+}
 
 impl DamageKind {
-    pub fn iter() -> Iter<'static, DamageKind> {
-        DAMAGE_KINDS.iter()
+    /// Sets the damage kinds that may be referenced.  This must be called
+    /// when the rules are loaded, before any other resources are read.
+    pub(crate) fn set_kinds(kinds: &[DamageKindData]) {
+        DAMAGE_KINDS.with(|k| *k.borrow_mut() = kinds.to_vec());
     }
 
-    pub fn index(self) -> usize {
-        use crate::rules::DamageKind::*;
-        match self {
-            Slashing => 0,
-            Piercing => 1,
-            Crushing => 2,
-            Acid => 3,
-            Cold => 4,
-            Shock => 5,
-            Fire => 6,
-            Raw => 7,
+    pub fn iter() -> impl Iterator<Item = DamageKind> {
+        (0..DamageKind::count()).map(|index| DamageKind { index })
+    }
+
+    pub fn count() -> usize {
+        DAMAGE_KINDS.with(|k| k.borrow().len())
+    }
+
+    /// Returns the raw damage kind, which ignores armor and resistance
+    pub fn raw() -> DamageKind {
+        let index = DAMAGE_KINDS.with(|k| k.borrow().iter().position(|kind| kind.raw));
+        DamageKind {
+            index: index.unwrap_or(0),
         }
     }
 
+    pub fn index(self) -> usize {
+        self.index
+    }
+
+    pub fn is_raw(self) -> bool {
+        self.with_data(|data| data.raw)
+    }
+
     pub fn unwrap_from_str(s: &str) -> DamageKind {
-        match s {
-            "Slashing" => DamageKind::Slashing,
-            "Piercing" => DamageKind::Piercing,
-            "Crushing" => DamageKind::Crushing,
-            "Acid" => DamageKind::Acid,
-            "Cold" => DamageKind::Cold,
-            "Shock" => DamageKind::Shock,
-            "Fire" => DamageKind::Fire,
-            "Raw" => DamageKind::Raw,
-            _ => {
+        match DamageKind::from_str(s) {
+            Ok(kind) => kind,
+            Err(_) => {
                 warn!("Unable to parse '{}' as damage kind", s);
-                DamageKind::Raw
+                DamageKind::raw()
             }
         }
     }
 
-    pub fn to_str(self) -> &'static str {
-        match self {
-            Slashing => "Slashing",
-            Piercing => "Piercing",
-            Crushing => "Crushing",
-            Acid => "Acid",
-            Cold => "Cold",
-            Shock => "Shock",
-            Fire => "Fire",
-            Raw => "Raw",
+    pub fn id(self) -> String {
+        self.with_data(|data| data.id.to_string())
+    }
+
+    pub fn color(self) -> Color {
+        self.with_data(|data| data.color)
+    }
+
+    pub fn icon(self) -> Option<String> {
+        self.with_data(|data| data.icon.clone())
+    }
+
+    fn with_data<T, F: FnOnce(&DamageKindData) -> T>(self, f: F) -> T {
+        DAMAGE_KINDS.with(|k| f(&k.borrow()[self.index]))
+    }
+}
+
+impl FromStr for DamageKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let index = DAMAGE_KINDS.with(|k| k.borrow().iter().position(|kind| kind.id == s));
+
+        match index {
+            Some(index) => Ok(DamageKind { index }),
+            None => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Unable to parse DamageKind from '{}'", s),
+            )),
         }
     }
 }
 
 impl Display for DamageKind {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        write!(fmt, "{:?}", self)
+        write!(fmt, "{}", self.id())
+    }
+}
+
+impl fmt::Debug for DamageKind {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.id())
+    }
+}
+
+impl TryFrom<String> for DamageKind {
+    type Error = Error;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        DamageKind::from_str(&id)
+    }
+}
+
+impl From<DamageKind> for String {
+    fn from(kind: DamageKind) -> String {
+        kind.id()
     }
 }
 
@@ -294,8 +356,8 @@ impl Damage {
         (self.min as f32 + self.max as f32) / 2.0
     }
 
-    pub fn roll(&self) -> u32 {
-        gen_rand(self.min, self.max + 1)
+    pub fn roll(&self, rand: &mut ReproducibleRandom) -> u32 {
+        rand.gen(self.min, self.max + 1)
     }
 }
 
@@ -0,0 +1,340 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// A simple arithmetic expression, such as `base_defense + 2 * (dex - 10)`.
+/// Expressions support numbers, named variables, the `+`, `-`, `*`, `/`, and
+/// `%` operators, parentheses, and the functions `min`, `max`, `floor`,
+/// `ceil`, `round`, and `abs`.  Variables are looked up by name at evaluation
+/// time.  Expressions are deserialized from either a string or a number.
+#[derive(Deserialize, Clone)]
+#[serde(try_from = "ExpressionSource")]
+pub struct Expression {
+    source: String,
+    root: Node,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExpressionSource {
+    Number(f32),
+    Text(String),
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Number(f32),
+    Variable(String),
+    Negate(Box<Node>),
+    Binary(Op, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Function {
+    Min,
+    Max,
+    Floor,
+    Ceil,
+    Round,
+    Abs,
+}
+
+impl Function {
+    fn from(name: &str) -> Option<Function> {
+        use self::Function::*;
+        Some(match name {
+            "min" => Min,
+            "max" => Max,
+            "floor" => Floor,
+            "ceil" => Ceil,
+            "round" => Round,
+            "abs" => Abs,
+            _ => return None,
+        })
+    }
+
+    fn num_args(self) -> usize {
+        use self::Function::*;
+        match self {
+            Min | Max => 2,
+            Floor | Ceil | Round | Abs => 1,
+        }
+    }
+}
+
+impl Expression {
+    pub fn parse(source: &str) -> Result<Expression, Error> {
+        let mut parser = Parser {
+            source,
+            chars: source.chars().peekable(),
+        };
+        let root = parser.expression()?;
+        parser.skip_whitespace();
+        if let Some(c) = parser.chars.peek().copied() {
+            return parser.error(&format!("Unexpected '{}'", c));
+        }
+
+        Ok(Expression {
+            source: source.to_string(),
+            root,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the names of all variables referenced by this expression
+    pub fn variables(&self) -> Vec<&str> {
+        let mut vars = Vec::new();
+        self.root.variables(&mut vars);
+        vars
+    }
+
+    /// Evaluates this expression, looking up each variable with `vars`.
+    /// Unknown variables evaluate to zero; use `variables` to check for these
+    /// when validating.
+    pub fn eval<F: Fn(&str) -> Option<f32>>(&self, vars: &F) -> f32 {
+        self.root.eval(vars)
+    }
+}
+
+impl fmt::Debug for Expression {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.source)
+    }
+}
+
+impl TryFrom<ExpressionSource> for Expression {
+    type Error = Error;
+
+    fn try_from(source: ExpressionSource) -> Result<Self, Self::Error> {
+        match source {
+            ExpressionSource::Number(value) => Ok(Expression {
+                source: value.to_string(),
+                root: Node::Number(value),
+            }),
+            ExpressionSource::Text(text) => Expression::parse(&text),
+        }
+    }
+}
+
+impl Node {
+    fn variables<'a>(&'a self, vars: &mut Vec<&'a str>) {
+        match self {
+            Node::Number(_) => (),
+            Node::Variable(name) => vars.push(name),
+            Node::Negate(node) => node.variables(vars),
+            Node::Binary(_, left, right) => {
+                left.variables(vars);
+                right.variables(vars);
+            }
+            Node::Call(_, args) => {
+                for arg in args {
+                    arg.variables(vars);
+                }
+            }
+        }
+    }
+
+    fn eval<F: Fn(&str) -> Option<f32>>(&self, vars: &F) -> f32 {
+        match self {
+            Node::Number(value) => *value,
+            Node::Variable(name) => vars(name).unwrap_or(0.0),
+            Node::Negate(node) => -node.eval(vars),
+            Node::Binary(op, left, right) => {
+                let left = left.eval(vars);
+                let right = right.eval(vars);
+                match op {
+                    Op::Add => left + right,
+                    Op::Sub => left - right,
+                    Op::Mul => left * right,
+                    Op::Div => left / right,
+                    Op::Rem => left % right,
+                }
+            }
+            Node::Call(func, args) => {
+                let a = args[0].eval(vars);
+                match func {
+                    Function::Min => a.min(args[1].eval(vars)),
+                    Function::Max => a.max(args[1].eval(vars)),
+                    Function::Floor => a.floor(),
+                    Function::Ceil => a.ceil(),
+                    Function::Round => a.round(),
+                    Function::Abs => a.abs(),
+                }
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T>(&self, message: &str) -> Result<T, Error> {
+        Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Invalid expression '{}': {}", self.source, message),
+        ))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.chars.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.chars.next();
+        }
+    }
+
+    fn next_is(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&c) {
+            self.chars.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    // expression := term (('+' | '-') term)*
+    fn expression(&mut self) -> Result<Node, Error> {
+        let mut node = self.term()?;
+        loop {
+            let op = if self.next_is('+') {
+                Op::Add
+            } else if self.next_is('-') {
+                Op::Sub
+            } else {
+                return Ok(node);
+            };
+            node = Node::Binary(op, Box::new(node), Box::new(self.term()?));
+        }
+    }
+
+    // term := unary (('*' | '/' | '%') unary)*
+    fn term(&mut self) -> Result<Node, Error> {
+        let mut node = self.unary()?;
+        loop {
+            let op = if self.next_is('*') {
+                Op::Mul
+            } else if self.next_is('/') {
+                Op::Div
+            } else if self.next_is('%') {
+                Op::Rem
+            } else {
+                return Ok(node);
+            };
+            node = Node::Binary(op, Box::new(node), Box::new(self.unary()?));
+        }
+    }
+
+    // unary := '-' unary | primary
+    fn unary(&mut self) -> Result<Node, Error> {
+        if self.next_is('-') {
+            Ok(Node::Negate(Box::new(self.unary()?)))
+        } else {
+            self.primary()
+        }
+    }
+
+    // primary := number | '(' expression ')' | name | name '(' args ')'
+    fn primary(&mut self) -> Result<Node, Error> {
+        if self.next_is('(') {
+            let node = self.expression()?;
+            if !self.next_is(')') {
+                return self.error("Expected ')'");
+            }
+            return Ok(node);
+        }
+
+        let c = match self.chars.peek() {
+            None => return self.error("Unexpected end of expression"),
+            Some(c) => *c,
+        };
+
+        if c.is_ascii_digit() || c == '.' {
+            let text = self.take_while(|c| c.is_ascii_digit() || c == '.');
+            return match text.parse::<f32>() {
+                Ok(value) => Ok(Node::Number(value)),
+                Err(_) => self.error(&format!("Invalid number '{}'", text)),
+            };
+        }
+
+        if !(c.is_ascii_alphabetic() || c == '_') {
+            return self.error(&format!("Unexpected '{}'", c));
+        }
+
+        let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !self.next_is('(') {
+            return Ok(Node::Variable(name));
+        }
+
+        let func = match Function::from(&name) {
+            None => return self.error(&format!("Unknown function '{}'", name)),
+            Some(func) => func,
+        };
+
+        let mut args = vec![self.expression()?];
+        while self.next_is(',') {
+            args.push(self.expression()?);
+        }
+
+        if !self.next_is(')') {
+            return self.error("Expected ')'");
+        }
+
+        if args.len() != func.num_args() {
+            return self.error(&format!(
+                "'{}' takes {} argument(s)",
+                name,
+                func.num_args()
+            ));
+        }
+
+        Ok(Node::Call(func, args))
+    }
+
+    fn take_while<F: Fn(char) -> bool>(&mut self, f: F) -> String {
+        let mut out = String::new();
+        while let Some(c) = self.chars.peek() {
+            if !f(*c) {
+                break;
+            }
+            out.push(*c);
+            self.chars.next();
+        }
+        out
+    }
+}
@@ -0,0 +1,158 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::io::Error;
+
+use sulis_core::util::invalid_data_error;
+
+use crate::rules::{AttributeData, Expression};
+
+/// The variables available to stat formulas, in addition to the short name
+/// of each attribute, which evaluates to that attribute's value.  `melee` is
+/// 1 when the actor is wielding a melee weapon and 0 otherwise.
+pub const STAT_VARIABLES: [&str; 7] = [
+    "level",
+    "melee",
+    "base_accuracy",
+    "base_defense",
+    "base_perception",
+    "base_attribute",
+    "base_flanking_angle",
+];
+
+/// Formulas which may optionally replace the default calculation of derived
+/// values.  When a formula is not specified, the value is computed from the
+/// other rules fields and the attribute stat factors, as before.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Formulas {
+    #[serde(default)]
+    pub initiative: Option<Expression>,
+
+    #[serde(default)]
+    pub melee_accuracy: Option<Expression>,
+
+    #[serde(default)]
+    pub ranged_accuracy: Option<Expression>,
+
+    #[serde(default)]
+    pub spell_accuracy: Option<Expression>,
+
+    #[serde(default)]
+    pub defense: Option<Expression>,
+
+    #[serde(default)]
+    pub fortitude: Option<Expression>,
+
+    #[serde(default)]
+    pub reflex: Option<Expression>,
+
+    #[serde(default)]
+    pub will: Option<Expression>,
+
+    #[serde(default)]
+    pub perception: Option<Expression>,
+
+    /// Hit points gained in addition to those from race and class bonuses
+    #[serde(default)]
+    pub max_hp: Option<Expression>,
+
+    /// The graze, hit, and crit multipliers are added to the base damage
+    /// multipliers for each hit kind
+    #[serde(default)]
+    pub graze_multiplier: Option<Expression>,
+
+    #[serde(default)]
+    pub hit_multiplier: Option<Expression>,
+
+    #[serde(default)]
+    pub crit_multiplier: Option<Expression>,
+
+    #[serde(default)]
+    pub flanking_angle: Option<Expression>,
+
+    /// The experience needed to advance past `level`, replacing the
+    /// `experience_for_level` table
+    #[serde(default)]
+    pub xp_for_level: Option<Expression>,
+
+    /// The maximum percentage of damage removed by `armor`, replacing the
+    /// `armor_damage_reduction_cap` table
+    #[serde(default)]
+    pub armor_damage_reduction_cap: Option<Expression>,
+}
+
+impl Formulas {
+    /// Verifies that each formula only references variables that will be
+    /// available when it is evaluated.
+    pub fn validate(&self, attributes: &[AttributeData]) -> Result<(), Error> {
+        let stats = [
+            ("initiative", &self.initiative),
+            ("melee_accuracy", &self.melee_accuracy),
+            ("ranged_accuracy", &self.ranged_accuracy),
+            ("spell_accuracy", &self.spell_accuracy),
+            ("defense", &self.defense),
+            ("fortitude", &self.fortitude),
+            ("reflex", &self.reflex),
+            ("will", &self.will),
+            ("perception", &self.perception),
+            ("max_hp", &self.max_hp),
+            ("graze_multiplier", &self.graze_multiplier),
+            ("hit_multiplier", &self.hit_multiplier),
+            ("crit_multiplier", &self.crit_multiplier),
+            ("flanking_angle", &self.flanking_angle),
+        ];
+
+        for (id, formula) in stats.iter() {
+            check(id, formula, |var| {
+                STAT_VARIABLES.contains(&var) || attributes.iter().any(|a| a.short_name == var)
+            })?;
+        }
+
+        check("xp_for_level", &self.xp_for_level, |var| var == "level")?;
+        check(
+            "armor_damage_reduction_cap",
+            &self.armor_damage_reduction_cap,
+            |var| var == "armor",
+        )?;
+
+        Ok(())
+    }
+}
+
+fn check<F: Fn(&str) -> bool>(
+    id: &str,
+    formula: &Option<Expression>,
+    is_valid: F,
+) -> Result<(), Error> {
+    let formula = match formula {
+        None => return Ok(()),
+        Some(formula) => formula,
+    };
+
+    for var in formula.variables() {
+        if !is_valid(var) {
+            return invalid_data_error(&format!(
+                "Unknown variable '{}' in {} formula '{}'",
+                var,
+                id,
+                formula.source()
+            ));
+        }
+    }
+
+    Ok(())
+}
@@ -16,36 +16,35 @@
 
 use crate::rules::DamageKind;
 
-#[derive(Deserialize, Debug, Clone)]
+// The resistance to each damage kind, by kind index.  Kinds past the end
+// of the list have no resistance.
+#[derive(Deserialize, Debug, Clone, Default)]
 #[serde(deny_unknown_fields)]
 pub struct Resistance {
-    kinds: [i32; 8],
-}
-
-impl Default for Resistance {
-    fn default() -> Resistance {
-        Resistance { kinds: [0; 8] }
-    }
+    kinds: Vec<i32>,
 }
 
 impl Resistance {
     pub fn add_kind(&mut self, kind: DamageKind, amount: i32) {
-        if kind == DamageKind::Raw {
+        if kind.is_raw() {
             return;
         }
 
         let index = kind.index();
+        if index >= self.kinds.len() {
+            self.kinds.resize(index + 1, 0);
+        }
         self.kinds[index] += amount;
     }
 
     /// Returns the amount of damage resistance that this armor value
     /// applies to the specified damage kind.
     pub fn amount(&self, check_kind: DamageKind) -> i32 {
-        if check_kind == DamageKind::Raw {
+        if check_kind.is_raw() {
             return 0;
         }
 
-        self.kinds[check_kind.index()]
+        *self.kinds.get(check_kind.index()).unwrap_or(&0)
     }
 
     pub fn is_empty(&self) -> bool {
@@ -19,12 +19,109 @@ use std::rc::Rc;
 
 use crate::rules::bonus::{AttackBonuses, AttackBuilder, Bonus, BonusKind, BonusList};
 use crate::rules::{
-    AccuracyKind, Armor, ArmorKind, Attack, AttributeList, Damage, HitKind, Resistance, Slot,
-    WeaponKind, WeaponStyle,
+    AccuracyKind, Armor, ArmorKind, Attack, Attribute, AttributeList, Damage, Expression, HitKind,
+    Resistance, Slot, WeaponKind, WeaponStyle,
 };
 use crate::{Actor, Module};
 use sulis_core::image::Image;
-use sulis_core::util::{gen_rand, ExtInt};
+use sulis_core::util::{ExtInt, ReproducibleRandom};
+
+/// The breakdown of a single accuracy roll against a defense, as rolled
+/// by `StatList::attack_roll_detail`
+#[derive(Debug, Clone, Copy)]
+pub struct AttackRoll {
+    pub accuracy_kind: AccuracyKind,
+    pub roll: i32,
+    pub accuracy: i32,
+    pub defense: i32,
+
+    /// The second roll made to confirm a critical hit, if any
+    pub crit_roll: Option<i32>,
+
+    /// The amount the result must exceed to be a graze
+    pub graze_threshold: i32,
+
+    /// The amount the result must exceed to be a hit
+    pub hit_threshold: i32,
+
+    pub hit_kind: HitKind,
+}
+
+impl AttackRoll {
+    /// The margin by which the roll plus accuracy beat the defense.  This
+    /// is compared against the graze and hit thresholds
+    pub fn result(&self) -> i32 {
+        self.crit_roll.unwrap_or(self.roll) + self.accuracy - self.defense
+    }
+}
+
+/// The chance of each result of an attack, between 0 and 1, as computed
+/// by `StatList::hit_chances`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HitChances {
+    pub miss: f32,
+    pub graze: f32,
+    pub hit: f32,
+    pub crit: f32,
+}
+
+impl HitChances {
+    /// Applies the given percentage chance for concealment to cause a miss
+    /// before the attack is rolled
+    pub fn with_concealment(self, concealment: i32) -> HitChances {
+        let pass = (100 - concealment.clamp(0, 100)) as f32 / 100.0;
+        HitChances {
+            miss: 1.0 - pass + self.miss * pass,
+            graze: self.graze * pass,
+            hit: self.hit * pass,
+            crit: self.crit * pass,
+        }
+    }
+}
+
+// The result of the first attack roll.  A potential crit is confirmed with
+// a second roll, becoming a crit if that roll would have been a graze
+enum FirstRoll {
+    Result(HitKind),
+    CritCheck,
+}
+
+struct RollThresholds {
+    accuracy: i32,
+    defense: i32,
+    crit_chance: i32,
+    crit_immunity: bool,
+    graze_threshold: i32,
+    hit_threshold: i32,
+}
+
+impl RollThresholds {
+    fn first_roll(&self, roll: i32) -> FirstRoll {
+        if roll + self.accuracy < self.defense {
+            return FirstRoll::Result(HitKind::Miss);
+        }
+
+        let result = roll + self.accuracy - self.defense;
+
+        if !self.crit_immunity && (100 - roll) < self.crit_chance {
+            FirstRoll::CritCheck
+        } else if result > self.hit_threshold {
+            FirstRoll::Result(HitKind::Hit)
+        } else if result > self.graze_threshold {
+            FirstRoll::Result(HitKind::Graze)
+        } else {
+            FirstRoll::Result(HitKind::Miss)
+        }
+    }
+
+    fn confirm_crit(&self, roll: i32) -> HitKind {
+        if roll + self.accuracy - self.defense > self.graze_threshold {
+            HitKind::Crit
+        } else {
+            HitKind::Hit
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct StatList {
@@ -39,6 +136,8 @@ pub struct StatList {
     pub contingent_bonuses: BonusList,
     // bonuses contingent on flanking that are only applied to some attacks
     pub flanking_bonuses: BonusList,
+    // bonuses contingent on attacking from higher ground
+    pub higher_ground_bonuses: BonusList,
 
     // these bonuses are applied only to the attack itself of the given weaponkind
     pub attack_bonuses: Vec<Bonus>,
@@ -63,6 +162,8 @@ pub struct StatList {
     pub will: i32,
     pub concealment: i32,
     pub concealment_ignore: i32,
+    pub stealth: i32,
+    pub perception: i32,
     pub crit_chance: i32,
     pub hit_threshold: i32,
     pub graze_threshold: i32,
@@ -82,6 +183,7 @@ pub struct StatList {
     pub free_ability_group_use: bool,
     pub caster_level: i32,
     has_shield: bool,
+    condition_immunities: Vec<String>,
     group_uses_per_encounter: HashMap<String, ExtInt>,
     group_uses_per_day: HashMap<String, ExtInt>,
     class_stats: HashMap<String, ExtInt>,
@@ -95,6 +197,7 @@ impl StatList {
             weapon_proficiencies: Vec::new(),
             contingent_bonuses: BonusList::default(),
             flanking_bonuses: BonusList::default(),
+            higher_ground_bonuses: BonusList::default(),
             attack_bonuses: Vec::new(),
             bonus_ap: 0,
             bonus_ability_action_point_cost: 0,
@@ -118,6 +221,8 @@ impl StatList {
             will: 0,
             concealment: 0,
             concealment_ignore: 0,
+            stealth: 0,
+            perception: 0,
             crit_chance: 0,
             hit_threshold: 0,
             graze_threshold: 0,
@@ -137,6 +242,7 @@ impl StatList {
             free_ability_group_use: false,
             caster_level: 0,
             has_shield: false,
+            condition_immunities: Vec::new(),
             group_uses_per_encounter: HashMap::new(),
             group_uses_per_day: HashMap::new(),
             class_stats: HashMap::new(),
@@ -172,6 +278,17 @@ impl StatList {
             .unwrap_or(&ExtInt::Int(0))
     }
 
+    /// Returns true if the parent cannot be affected by the condition with the
+    /// specified ID, either from a `ConditionImmunity` bonus or from another
+    /// condition the parent is under
+    pub fn is_immune_to_condition(&self, id: &str) -> bool {
+        self.condition_immunities.iter().any(|i| i == id)
+    }
+
+    pub fn condition_immunities_iter(&self) -> impl Iterator<Item = &String> {
+        self.condition_immunities.iter()
+    }
+
     pub fn has_armor_proficiency(&self, prof: ArmorKind) -> bool {
         self.armor_proficiencies.contains(&prof)
     }
@@ -186,38 +303,111 @@ impl StatList {
         crit_immunity: bool,
         defense: i32,
         bonuses: &AttackBonuses,
+        rand: &mut ReproducibleRandom,
     ) -> HitKind {
-        let accuracy = match accuracy_kind {
-            AccuracyKind::Melee => self.melee_accuracy + bonuses.melee_accuracy,
-            AccuracyKind::Ranged => self.ranged_accuracy + bonuses.ranged_accuracy,
-            AccuracyKind::Spell => self.spell_accuracy + bonuses.spell_accuracy,
-        };
-        let roll = gen_rand(1, 101);
+        self.attack_roll_detail(accuracy_kind, crit_immunity, defense, bonuses, rand)
+            .hit_kind
+    }
+
+    /// Performs an attack roll in the same way as `attack_roll`, but returns
+    /// the full breakdown of the roll along with the resulting `HitKind`
+    pub fn attack_roll_detail(
+        &self,
+        accuracy_kind: AccuracyKind,
+        crit_immunity: bool,
+        defense: i32,
+        bonuses: &AttackBonuses,
+        rand: &mut ReproducibleRandom,
+    ) -> AttackRoll {
+        let thresholds = self.roll_thresholds(accuracy_kind, crit_immunity, defense, bonuses);
+        let roll = rand.gen(1, 101);
         debug!(
             "Attack roll: {} with accuracy {} against {}",
-            roll, accuracy, defense
+            roll, thresholds.accuracy, defense
         );
 
-        if roll + accuracy < defense {
-            return HitKind::Miss;
-        }
+        let mut output = AttackRoll {
+            accuracy_kind,
+            roll,
+            accuracy: thresholds.accuracy,
+            defense,
+            crit_roll: None,
+            graze_threshold: thresholds.graze_threshold,
+            hit_threshold: thresholds.hit_threshold,
+            hit_kind: HitKind::Miss,
+        };
 
-        let result = roll + accuracy - defense;
+        output.hit_kind = match thresholds.first_roll(roll) {
+            FirstRoll::Result(hit_kind) => hit_kind,
+            FirstRoll::CritCheck => {
+                let roll2 = rand.gen(1, 101);
+                output.crit_roll = Some(roll2);
+                thresholds.confirm_crit(roll2)
+            }
+        };
 
-        if !crit_immunity && (100 - roll) < self.crit_chance + bonuses.crit_chance {
-            let roll2 = gen_rand(1, 101);
-            let result2 = roll2 + accuracy - defense;
-            if result2 > self.graze_threshold + bonuses.graze_threshold {
-                HitKind::Crit
-            } else {
-                HitKind::Hit
+        output
+    }
+
+    /// Computes the chance of each result for an attack roll with the given
+    /// parameters, as made by `attack_roll`.  This does not roll any
+    /// random numbers.
+    pub fn hit_chances(
+        &self,
+        accuracy_kind: AccuracyKind,
+        crit_immunity: bool,
+        defense: i32,
+        bonuses: &AttackBonuses,
+    ) -> HitChances {
+        let thresholds = self.roll_thresholds(accuracy_kind, crit_immunity, defense, bonuses);
+
+        // each roll is between 1 and 100, so count the outcomes of each roll
+        let crit_confirm = (1..=100)
+            .filter(|roll| thresholds.confirm_crit(*roll) == HitKind::Crit)
+            .count() as f32
+            / 100.0;
+
+        let mut counts = HitChances::default();
+        for roll in 1..=100 {
+            match thresholds.first_roll(roll) {
+                FirstRoll::Result(HitKind::Graze) => counts.graze += 1.0,
+                FirstRoll::Result(HitKind::Hit) => counts.hit += 1.0,
+                FirstRoll::Result(_) => counts.miss += 1.0,
+                FirstRoll::CritCheck => {
+                    counts.crit += crit_confirm;
+                    counts.hit += 1.0 - crit_confirm;
+                }
             }
-        } else if result > self.hit_threshold + bonuses.hit_threshold {
-            HitKind::Hit
-        } else if result > self.graze_threshold + bonuses.graze_threshold {
-            HitKind::Graze
-        } else {
-            HitKind::Miss
+        }
+
+        HitChances {
+            miss: counts.miss / 100.0,
+            graze: counts.graze / 100.0,
+            hit: counts.hit / 100.0,
+            crit: counts.crit / 100.0,
+        }
+    }
+
+    fn roll_thresholds(
+        &self,
+        accuracy_kind: AccuracyKind,
+        crit_immunity: bool,
+        defense: i32,
+        bonuses: &AttackBonuses,
+    ) -> RollThresholds {
+        let accuracy = match accuracy_kind {
+            AccuracyKind::Melee => self.melee_accuracy + bonuses.melee_accuracy,
+            AccuracyKind::Ranged => self.ranged_accuracy + bonuses.ranged_accuracy,
+            AccuracyKind::Spell => self.spell_accuracy + bonuses.spell_accuracy,
+        };
+
+        RollThresholds {
+            accuracy,
+            defense,
+            crit_chance: self.crit_chance + bonuses.crit_chance,
+            crit_immunity,
+            graze_threshold: self.graze_threshold + bonuses.graze_threshold,
+            hit_threshold: self.hit_threshold + bonuses.hit_threshold,
         }
     }
 
@@ -318,6 +508,7 @@ impl StatList {
                     self.attack_bonuses.push(bonus.clone())
                 }
                 AttackWhenFlanking => self.flanking_bonuses.add(bonus.clone()),
+                AttackFromHigherGround => self.higher_ground_bonuses.add(bonus.clone()),
                 WeaponEquipped(_) | ArmorEquipped { .. } | WeaponStyle(_) | Threatened => {
                     self.contingent_bonuses.add(bonus.clone())
                 }
@@ -363,6 +554,8 @@ impl StatList {
             Will(amount) => self.will += amount * times_i32,
             Concealment(amount) => self.concealment += amount * times_i32,
             ConcealmentIgnore(amount) => self.concealment_ignore += amount * times_i32,
+            Stealth(amount) => self.stealth += amount * times_i32,
+            Perception(amount) => self.perception += amount * times_i32,
             CritChance(amount) => self.crit_chance += amount * times_i32,
             HitThreshold(amount) => self.hit_threshold -= amount * times_i32,
             GrazeThreshold(amount) => self.graze_threshold -= amount * times_i32,
@@ -382,6 +575,11 @@ impl StatList {
             FlankedImmunity => self.flanked_immunity = true,
             SneakAttackImmunity => self.sneak_attack_immunity = true,
             CritImmunity => self.crit_immunity = true,
+            ConditionImmunity(id) => {
+                if !self.condition_immunities.contains(id) {
+                    self.condition_immunities.push(id.to_string());
+                }
+            }
             GroupUsesPerEncounter { group, amount } => {
                 self.add_single_group_uses_per_encounter(group, *amount)
             }
@@ -410,6 +608,7 @@ impl StatList {
                 | AttackWithWeapon(_)
                 | AttackWhenHidden
                 | AttackWhenFlanking
+                | AttackFromHigherGround
                 | AttackWithDamageKind(_) => unreachable!(),
                 WeaponEquipped(weapon_kind) => {
                     for (_, attack_weapon_kind) in attacks.iter() {
@@ -475,29 +674,76 @@ impl StatList {
         let base_defense = rules.base_defense as i32;
         let base_attr = rules.base_attribute;
 
-        use crate::rules::Attribute::*;
+        use crate::rules::DerivedStat::*;
         let attrs = &self.attributes;
-        let str_bonus = attrs.bonus(Strength, base_attr);
-        let dex_bonus = attrs.bonus(Dexterity, base_attr);
-        let end_bonus = attrs.bonus(Endurance, base_attr);
-        let per_bonus = attrs.bonus(Perception, base_attr);
-        let int_bonus = attrs.bonus(Intellect, base_attr);
-        let wis_bonus = attrs.bonus(Wisdom, base_attr);
-        self.initiative += dex_bonus / 2 + per_bonus / 2;
-        self.melee_accuracy += base_accuracy + per_bonus + str_bonus * 2;
-        self.ranged_accuracy += base_accuracy + per_bonus + dex_bonus * 2;
-        self.spell_accuracy += base_accuracy + wis_bonus + int_bonus * 2;
-        self.defense += base_defense + dex_bonus * 2;
-        self.fortitude += base_defense + end_bonus * 2;
-        self.reflex += base_defense + dex_bonus * 2;
-        self.will += base_defense + wis_bonus * 2;
-        self.max_hp += (actor.total_level as i32 * end_bonus) / 3;
-
-        let damage_stat_bonus = if is_melee { str_bonus } else { dex_bonus } as f32;
-
-        self.graze_multiplier += 0.02 * damage_stat_bonus;
-        self.hit_multiplier += 0.03 * damage_stat_bonus;
-        self.crit_multiplier += 0.06 * damage_stat_bonus;
+        let formulas = &rules.formulas;
+        let level = actor.total_level as i32;
+        let vars = |name: &str| {
+            Some(match name {
+                "level" => level as f32,
+                "melee" => if is_melee { 1.0 } else { 0.0 },
+                "base_accuracy" => base_accuracy as f32,
+                "base_defense" => base_defense as f32,
+                "base_perception" => rules.base_perception as f32,
+                "base_attribute" => base_attr as f32,
+                "base_flanking_angle" => rules.base_flanking_angle as f32,
+                _ => return Attribute::from_short_name(name).map(|attr| attrs.get(attr) as f32),
+            })
+        };
+
+        // use the formula for a stat if there is one, otherwise the default
+        let int_stat = |formula: &Option<Expression>, default: i32| match formula {
+            None => default,
+            Some(formula) => formula.eval(&vars) as i32,
+        };
+        let float_stat = |formula: &Option<Expression>, default: f32| match formula {
+            None => default,
+            Some(formula) => formula.eval(&vars),
+        };
+        let derived = |stat| attrs.derived_int(stat, base_attr, 1);
+
+        self.initiative += int_stat(&formulas.initiative, derived(Initiative));
+        self.melee_accuracy += int_stat(
+            &formulas.melee_accuracy,
+            base_accuracy + derived(MeleeAccuracy),
+        );
+        self.ranged_accuracy += int_stat(
+            &formulas.ranged_accuracy,
+            base_accuracy + derived(RangedAccuracy),
+        );
+        self.spell_accuracy += int_stat(
+            &formulas.spell_accuracy,
+            base_accuracy + derived(SpellAccuracy),
+        );
+        self.defense += int_stat(&formulas.defense, base_defense + derived(Defense));
+        self.fortitude += int_stat(&formulas.fortitude, base_defense + derived(Fortitude));
+        self.reflex += int_stat(&formulas.reflex, base_defense + derived(Reflex));
+        self.will += int_stat(&formulas.will, base_defense + derived(Will));
+        self.perception += int_stat(
+            &formulas.perception,
+            rules.base_perception + derived(Perception),
+        );
+        self.max_hp += int_stat(
+            &formulas.max_hp,
+            attrs.derived_int(HpPerLevel, base_attr, level),
+        );
+
+        let (graze, hit, crit) = if is_melee {
+            (MeleeGrazeMultiplier, MeleeHitMultiplier, MeleeCritMultiplier)
+        } else {
+            (RangedGrazeMultiplier, RangedHitMultiplier, RangedCritMultiplier)
+        };
+
+        self.graze_multiplier += float_stat(
+            &formulas.graze_multiplier,
+            attrs.derived(graze, base_attr),
+        );
+        self.hit_multiplier += float_stat(&formulas.hit_multiplier, attrs.derived(hit, base_attr));
+        self.crit_multiplier += float_stat(
+            &formulas.crit_multiplier,
+            attrs.derived(crit, base_attr),
+        );
+        self.flanking_angle += int_stat(&formulas.flanking_angle, rules.base_flanking_angle);
 
         if self.hit_multiplier < self.graze_multiplier {
             self.hit_multiplier = self.graze_multiplier;
@@ -515,7 +761,6 @@ impl StatList {
             self.graze_threshold = self.hit_threshold;
         }
 
-        self.flanking_angle += rules.base_flanking_angle;
         self.crit_chance += rules.crit_chance as i32;
         self.hit_threshold += rules.hit_percentile as i32;
         self.graze_threshold += rules.graze_percentile as i32;
@@ -0,0 +1,570 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display};
+use std::io::Error;
+use std::path::Path;
+
+use sulis_core::resource::{yaml_resource_set, YamlResourceKind, YamlResourceSet};
+use sulis_core::serde_yaml;
+
+use crate::campaign::CampaignBuilder;
+use crate::on_trigger::{Kind, QuestStateData, ScriptData};
+use crate::{Module, ModuleBuilder, OnTrigger};
+
+/// Resource kinds that are checked for being unused.  Other kinds, such as
+/// tiles and sizes, are commonly defined in bulk and are not reported.
+const UNUSED_KINDS: [YamlResourceKind; 15] = [
+    YamlResourceKind::Ability,
+    YamlResourceKind::AbilityList,
+    YamlResourceKind::Actor,
+    YamlResourceKind::AiTemplate,
+    YamlResourceKind::Area,
+    YamlResourceKind::Class,
+    YamlResourceKind::Condition,
+    YamlResourceKind::Conversation,
+    YamlResourceKind::Cutscene,
+    YamlResourceKind::Encounter,
+    YamlResourceKind::Item,
+    YamlResourceKind::LootList,
+    YamlResourceKind::Prop,
+    YamlResourceKind::Quest,
+    YamlResourceKind::Race,
+];
+
+/// A single problem found while validating a module, along with the resource
+/// it was found in and the file(s) that resource was read from.
+#[derive(Debug, Clone)]
+pub struct ValidationError {
+    pub kind: YamlResourceKind,
+    pub id: String,
+    pub files: Vec<String>,
+    pub message: String,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} '{}'", kind_name(self.kind), self.id)?;
+        if !self.files.is_empty() {
+            write!(f, " ({})", self.files.join(", "))?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// The result of validating a module.  `errors` are broken references and
+/// resources that failed to load, while `unused` lists resources defined by
+/// the campaign (or a mod) that nothing refers to.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub errors: Vec<ValidationError>,
+    pub unused: Vec<ValidationError>,
+}
+
+impl ValidationReport {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Collects every broken reference between module resources, rather than
+/// stopping at the first one as the resource constructors do.  Each builder
+/// reports its references via a `validate` method.
+pub(crate) struct Validator {
+    defined: HashMap<YamlResourceKind, HashSet<String>>,
+    referenced: HashMap<YamlResourceKind, HashSet<String>>,
+    files: HashMap<(YamlResourceKind, String), Vec<String>>,
+    module_dirs: Vec<String>,
+    script_funcs: HashMap<String, HashSet<String>>,
+    script_literals: HashSet<String>,
+    quests: HashMap<String, HashSet<String>>,
+    source: (YamlResourceKind, String),
+    errors: Vec<ValidationError>,
+}
+
+impl Validator {
+    /// Creates a validator for the given, not yet loaded, YAML.  `dirs` are the
+    /// directories the YAML was read from; resources from any directory after
+    /// the first (the base data directory) are checked for being unused.
+    pub(crate) fn new(yaml: &YamlResourceSet, dirs: &[String]) -> Validator {
+        let file_key = serde_yaml::Value::String(yaml_resource_set::FILE_VAL_STR.to_string());
+
+        let mut files = HashMap::new();
+        for (kind, resources) in yaml.resources.iter() {
+            for (id, value) in resources.iter() {
+                let mut resource_files = Vec::new();
+                if let Some(serde_yaml::Value::Sequence(seq)) = value.get(&file_key) {
+                    for file in seq {
+                        if let serde_yaml::Value::String(file) = file {
+                            resource_files.push(file.to_string());
+                        }
+                    }
+                }
+                files.insert((*kind, id.to_string()), resource_files);
+            }
+        }
+
+        Validator {
+            defined: HashMap::new(),
+            referenced: HashMap::new(),
+            files,
+            module_dirs: dirs.iter().skip(1).cloned().collect(),
+            script_funcs: HashMap::new(),
+            script_literals: HashSet::new(),
+            quests: HashMap::new(),
+            source: (YamlResourceKind::TopLevel, String::new()),
+            errors: Vec::new(),
+        }
+    }
+
+    pub(crate) fn check_builders(
+        &mut self,
+        builders: &ModuleBuilder,
+        campaign: &CampaignBuilder,
+        scripts: &HashMap<String, String>,
+    ) {
+        use self::YamlResourceKind::*;
+
+        for (id, script) in scripts.iter() {
+            self.script_funcs
+                .insert(id.to_string(), parse_script_functions(script));
+            parse_string_literals(script, &mut self.script_literals);
+        }
+
+        for (id, quest) in builders.quests.iter() {
+            self.quests
+                .insert(id.to_string(), quest.entries.keys().cloned().collect());
+        }
+
+        self.define(Ability, builders.ability_builders.keys());
+        self.define(AbilityList, builders.ability_list_builders.keys());
+        self.define(Actor, builders.actor_builders.keys());
+        self.define(AiTemplate, builders.ai_builders.keys());
+        self.define(Area, builders.area_builders.keys());
+        self.define(Class, builders.class_builders.keys());
+        self.define(Condition, builders.condition_builders.keys());
+        self.define(Conversation, builders.conversation_builders.keys());
+        self.define(Cutscene, builders.cutscene_builders.keys());
+        self.define(Encounter, builders.encounter_builders.keys());
+        self.define(Generator, builders.generator_builders.keys());
+        self.define(Item, builders.item_builders.keys());
+        self.define(ItemAdjective, builders.item_adjectives.keys());
+        self.define(LootList, builders.loot_builders.keys());
+        self.define(Prop, builders.prop_builders.keys());
+        self.define(Quest, builders.quests.keys());
+        self.define(Race, builders.race_builders.keys());
+        self.define(Size, builders.size_builders.keys());
+
+        for (id, builder) in builders.ability_builders.iter() {
+            self.set_source(Ability, id);
+            builder.validate(self);
+        }
+
+        for (id, builder) in builders.ability_list_builders.iter() {
+            self.set_source(AbilityList, id);
+            builder.validate(self);
+        }
+
+        for (id, builder) in builders.actor_builders.iter() {
+            self.set_source(Actor, id);
+            builder.validate(self);
+        }
+
+        for (id, builder) in builders.ai_builders.iter() {
+            self.set_source(AiTemplate, id);
+            builder.validate(self);
+        }
+
+        for (id, builder) in builders.area_builders.iter() {
+            self.set_source(Area, id);
+            builder.validate(self);
+        }
+
+        for (id, builder) in builders.class_builders.iter() {
+            self.set_source(Class, id);
+            builder.validate(self);
+        }
+
+        for (id, builder) in builders.condition_builders.iter() {
+            self.set_source(Condition, id);
+            builder.validate(self);
+        }
+
+        for (id, builder) in builders.conversation_builders.iter() {
+            self.set_source(Conversation, id);
+            builder.validate(self);
+        }
+
+        for (id, builder) in builders.cutscene_builders.iter() {
+            self.set_source(Cutscene, id);
+            self.check_triggers(&builder.on_end);
+        }
+
+        for (id, builder) in builders.encounter_builders.iter() {
+            self.set_source(Encounter, id);
+            builder.validate(self);
+        }
+
+        for (id, builder) in builders.generator_builders.iter() {
+            self.set_source(Generator, id);
+            builder.validate(self);
+        }
+
+        for (id, builder) in builders.item_builders.iter() {
+            self.set_source(Item, id);
+            builder.validate(self);
+        }
+
+        for (id, builder) in builders.loot_builders.iter() {
+            self.set_source(LootList, id);
+            builder.validate(self);
+        }
+
+        for (id, builder) in builders.prop_builders.iter() {
+            self.set_source(Prop, id);
+            builder.validate(self);
+        }
+
+        for (id, builder) in builders.race_builders.iter() {
+            self.set_source(Race, id);
+            builder.validate(self);
+        }
+
+        self.set_source(TopLevel, &campaign.id);
+        campaign.validate(self);
+    }
+
+    /// Reports every resource that was defined but could not be created.  The
+    /// underlying cause is normally also reported as a broken reference.
+    pub(crate) fn check_loaded(&mut self, module: &Module) {
+        use self::YamlResourceKind::*;
+
+        let mut failed = Vec::new();
+        for (kind, ids) in self.defined.iter() {
+            for id in ids.iter() {
+                let loaded = match kind {
+                    Ability => module.abilities.contains_key(id),
+                    AbilityList => module.ability_lists.contains_key(id),
+                    Actor => module.actors.contains_key(id),
+                    AiTemplate => module.ai_templates.contains_key(id),
+                    Area => module.areas.contains_key(id),
+                    Class => module.classes.contains_key(id),
+                    Condition => module.conditions.contains_key(id),
+                    Conversation => module.conversations.contains_key(id),
+                    Cutscene => module.cutscenes.contains_key(id),
+                    Encounter => module.encounters.contains_key(id),
+                    Generator => module.generators.contains_key(id),
+                    Item => module.items.contains_key(id),
+                    ItemAdjective => module.item_adjectives.contains_key(id),
+                    LootList => module.loot_lists.contains_key(id),
+                    Prop => module.props.contains_key(id),
+                    Quest => module.quests.contains_key(id),
+                    Race => module.races.contains_key(id),
+                    Size => module.sizes.contains_key(id),
+                    _ => true,
+                };
+
+                if !loaded {
+                    failed.push((*kind, id.to_string()));
+                }
+            }
+        }
+
+        for (kind, id) in failed {
+            self.set_source(kind, &id);
+            self.error("Unable to create resource.  See the log for details.".to_string());
+        }
+    }
+
+    /// Records an error that prevented the module from loading at all.
+    pub(crate) fn fatal(&mut self, error: Error) {
+        self.set_source(YamlResourceKind::TopLevel, "module");
+        self.error(error.to_string());
+    }
+
+    pub(crate) fn into_report(mut self) -> ValidationReport {
+        let mut unused = Vec::new();
+        for kind in UNUSED_KINDS.iter() {
+            let defined = match self.defined.get(kind) {
+                None => continue,
+                Some(defined) => defined,
+            };
+
+            for id in defined.iter() {
+                if self.is_referenced(*kind, id) || !self.is_module_resource(*kind, id) {
+                    continue;
+                }
+
+                unused.push(ValidationError {
+                    kind: *kind,
+                    id: id.to_string(),
+                    files: self.files_for(*kind, id),
+                    message: "Not referenced by any resource or script".to_string(),
+                });
+            }
+        }
+
+        self.errors.sort_by(|a, b| sort_key(a).cmp(&sort_key(b)));
+        unused.sort_by(|a, b| sort_key(a).cmp(&sort_key(b)));
+
+        ValidationReport {
+            errors: self.errors,
+            unused,
+        }
+    }
+
+    pub(crate) fn error(&mut self, message: String) {
+        let (kind, id) = self.source.clone();
+
+        // the same problem is often hit several times within one resource
+        let duplicate = self
+            .errors
+            .iter()
+            .any(|e| e.kind == kind && e.id == id && e.message == message);
+        if duplicate {
+            return;
+        }
+
+        let files = self.files_for(kind, &id);
+        self.errors.push(ValidationError {
+            kind,
+            id,
+            files,
+            message,
+        });
+    }
+
+    /// Checks that the resource of the specified kind and ID exists.
+    pub(crate) fn check(&mut self, kind: YamlResourceKind, id: &str) {
+        self.referenced
+            .entry(kind)
+            .or_default()
+            .insert(id.to_string());
+
+        let defined = match self.defined.get(&kind) {
+            None => false,
+            Some(ids) => ids.contains(id),
+        };
+
+        if !defined {
+            self.error(format!("Invalid {} '{}'", kind_name(kind), id));
+        }
+    }
+
+    pub(crate) fn check_script(&mut self, id: &str) {
+        if !self.script_funcs.contains_key(id) {
+            self.error(format!("Invalid script '{}'", id));
+        }
+    }
+
+    /// Checks that the given function is defined in the script with the
+    /// given ID.
+    pub(crate) fn check_script_func(&mut self, id: &str, func: &str) {
+        let found = match self.script_funcs.get(id) {
+            None => {
+                self.error(format!("Invalid script '{}'", id));
+                return;
+            }
+            Some(funcs) => funcs.contains(func),
+        };
+
+        if !found {
+            self.error(format!("Function '{}' is not defined in script '{}'", func, id));
+        }
+    }
+
+    pub(crate) fn check_script_data(&mut self, data: &ScriptData) {
+        self.check_script_func(&data.id, &data.func);
+    }
+
+    fn check_quest_state(&mut self, data: &QuestStateData) {
+        self.referenced
+            .entry(YamlResourceKind::Quest)
+            .or_default()
+            .insert(data.quest.to_string());
+
+        let valid_entry = match self.quests.get(&data.quest) {
+            None => {
+                self.error(format!("Invalid quest '{}'", data.quest));
+                return;
+            }
+            Some(entries) => match data.entry {
+                None => true,
+                Some(ref entry) => entries.contains(entry),
+            },
+        };
+
+        if !valid_entry {
+            self.error(format!(
+                "Entry '{}' is not defined in quest '{}'",
+                data.entry.as_ref().unwrap(),
+                data.quest
+            ));
+        }
+    }
+
+    pub(crate) fn check_triggers(&mut self, triggers: &[OnTrigger]) {
+        use self::YamlResourceKind::*;
+
+        for trigger in triggers {
+            match trigger {
+                OnTrigger::PartyMember(id) => self.check(Actor, id),
+                OnTrigger::PartyItem(id) => self.check(Item, id),
+                OnTrigger::PlayerAbility(id) => self.check(Ability, id),
+                OnTrigger::ShowMerchant(data) => self.check(LootList, &data.loot_list),
+                OnTrigger::ShowCutscene(id) => self.check(Cutscene, id),
+                OnTrigger::StartConversation(id) => self.check(Conversation, id),
+                OnTrigger::FireScript(data) => self.check_script_data(data),
+                OnTrigger::ShowConfirm(data) => {
+                    if let Some(ref on_accept) = data.on_accept {
+                        self.check_script_data(on_accept);
+                    }
+                }
+                OnTrigger::ShowMenu(data) => {
+                    if let Kind::Script(ref id) = data.cb_kind {
+                        self.check_script_func(id, &data.cb_func);
+                    }
+                }
+                OnTrigger::QuestState(data) | OnTrigger::NotQuestState(data) => {
+                    self.check_quest_state(data)
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn define<'a, I: Iterator<Item = &'a String>>(&mut self, kind: YamlResourceKind, ids: I) {
+        let defined = self.defined.entry(kind).or_default();
+        defined.extend(ids.cloned());
+    }
+
+    fn set_source(&mut self, kind: YamlResourceKind, id: &str) {
+        self.source = (kind, id.to_string());
+    }
+
+    fn files_for(&self, kind: YamlResourceKind, id: &str) -> Vec<String> {
+        self.files
+            .get(&(kind, id.to_string()))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn is_referenced(&self, kind: YamlResourceKind, id: &str) -> bool {
+        // scripts may refer to any resource by ID, so treat any matching
+        // string literal as a reference
+        if self.script_literals.contains(id) {
+            return true;
+        }
+
+        match self.referenced.get(&kind) {
+            None => false,
+            Some(ids) => ids.contains(id),
+        }
+    }
+
+    fn is_module_resource(&self, kind: YamlResourceKind, id: &str) -> bool {
+        let files = self.files_for(kind, id);
+        !files.is_empty()
+            && files.iter().all(|file| {
+                self.module_dirs
+                    .iter()
+                    .any(|dir| Path::new(file).starts_with(dir))
+            })
+    }
+}
+
+fn sort_key(error: &ValidationError) -> (&'static str, &str) {
+    (kind_name(error.kind), &error.id)
+}
+
+fn kind_name(kind: YamlResourceKind) -> &'static str {
+    use self::YamlResourceKind::*;
+    match kind {
+        TopLevel => "campaign",
+        Ability => "ability",
+        AbilityList => "ability list",
+        Actor => "actor",
+        AiTemplate => "ai template",
+        Area => "area",
+        Class => "class",
+        Condition => "condition",
+        Conversation => "conversation",
+        Cutscene => "cutscene",
+        Encounter => "encounter",
+        Generator => "generator",
+        Item => "item",
+        ItemAdjective => "item adjective",
+        LootList => "loot list",
+        Prop => "prop",
+        Quest => "quest",
+        Race => "race",
+        Size => "size",
+        Tile => "tile",
+        _ => "resource",
+    }
+}
+
+/// Finds the names of all functions defined in the specified Lua source,
+/// either as `function name(` or `name = function(`.
+fn parse_script_functions(script: &str) -> HashSet<String> {
+    let mut funcs = HashSet::new();
+    for line in script.lines() {
+        let line = line.trim();
+        let line = line.strip_prefix("local ").unwrap_or(line);
+
+        let name = if let Some(rest) = line.strip_prefix("function ") {
+            rest.split('(').next()
+        } else if line.contains("= function") {
+            line.split('=').next()
+        } else {
+            None
+        };
+
+        if let Some(name) = name {
+            let name = name.trim();
+            if !name.is_empty() {
+                funcs.insert(name.to_string());
+            }
+        }
+    }
+    funcs
+}
+
+fn parse_string_literals(script: &str, literals: &mut HashSet<String>) {
+    for line in script.lines() {
+        let mut quote = None;
+        let mut cur = String::new();
+        for c in line.chars() {
+            match quote {
+                None => {
+                    if c == '"' || c == '\'' {
+                        quote = Some(c);
+                        cur.clear();
+                    }
+                }
+                Some(q) => {
+                    if c == q {
+                        literals.insert(cur.clone());
+                        quote = None;
+                    } else {
+                        cur.push(c);
+                    }
+                }
+            }
+        }
+    }
+}
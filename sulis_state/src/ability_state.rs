@@ -14,6 +14,7 @@
 //  You should have received a copy of the GNU General Public License
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
+use std::mem;
 use std::rc::Rc;
 use std::u32;
 
@@ -100,6 +101,19 @@ impl AbilityState {
         }
     }
 
+    /// Replaces the ability with a newly loaded version of it, keeping the
+    /// current duration and cooldown
+    pub(crate) fn reload(&mut self, ability: &Rc<Ability>) {
+        let mut state = AbilityState::new(ability);
+        state.remaining_duration = self.remaining_duration;
+        state.cur_duration = self.cur_duration;
+        state.newly_added_ability = self.newly_added_ability;
+        state.listeners = mem::take(&mut self.listeners);
+
+        *self = state;
+        self.listeners.notify(self);
+    }
+
     pub fn update(&mut self, millis_elapsed: u32) {
         let cur_mod = self.cur_duration / ROUND_TIME_MILLIS;
         self.cur_duration += millis_elapsed;
@@ -54,7 +54,7 @@ impl ActorState {
             Some(builder) => Rc::new(Module::load_actor(builder)?),
         };
 
-        let attrs = actor.attributes;
+        let attrs = actor.attributes.clone();
 
         let image = LayeredImage::new(
             actor
@@ -128,7 +128,7 @@ impl ActorState {
                 .get_list(actor.sex, actor.hair_color, actor.skin_color),
             actor.hue,
         );
-        let attrs = actor.attributes;
+        let attrs = actor.attributes.clone();
 
         let mut ability_states = HashMap::new();
         for ability in actor.abilities.iter() {
@@ -834,9 +834,40 @@ impl ActorState {
         self.listeners.notify(self);
     }
 
+    /// Replaces the actor, abilities, and items referenced by this state with
+    /// the versions currently loaded in the module.  The actor is only
+    /// replaced if a new version is specified.  Abilities and items that can
+    /// no longer be found are kept as they are.
+    pub(crate) fn reload_resources(&mut self, actor: Option<Rc<Actor>>) {
+        if let Some(actor) = actor {
+            for owned in actor.abilities.iter() {
+                let ability = &owned.ability;
+                if ability.active.is_none() || self.ability_states.contains_key(&ability.id) {
+                    continue;
+                }
+
+                self.ability_states
+                    .insert(ability.id.to_string(), AbilityState::new(ability));
+            }
+
+            self.actor = actor;
+            self.texture_cache_invalid = true;
+        }
+
+        for (id, state) in self.ability_states.iter_mut() {
+            match Module::ability(id) {
+                Some(ref ability) if ability.active.is_some() => state.reload(ability),
+                _ => warn!("Unable to reload ability '{}' for '{}'", id, self.actor.id),
+            }
+        }
+
+        self.inventory.reload_resources();
+        self.compute_stats();
+    }
+
     pub fn compute_stats(&mut self) {
         debug!("Compute stats for '{}'", self.actor.name);
-        self.stats = StatList::new(self.actor.attributes);
+        self.stats = StatList::new(self.actor.attributes.clone());
 
         let mut layers_override = self.inventory().get_image_layers();
         for (layer, image) in self.anim_image_layers.iter() {
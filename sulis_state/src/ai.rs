@@ -17,9 +17,12 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
+mod utility;
+use self::utility::UtilityAI;
+
 use crate::script::script_callback;
 use crate::{animation::Anim, EntityState, GameState, Script};
-use sulis_module::ai::FuncKind;
+use sulis_module::{ai::FuncKind, ConditionAIHint};
 use sulis_core::config::Config;
 
 pub struct AI {
@@ -46,7 +49,11 @@ impl AI {
             return;
         }
 
-        if entity.borrow().is_party_member() {
+        if GameState::turn_manager().borrow().has_reaction_prompt() {
+            return;
+        }
+
+        if entity.borrow().is_party_member() && !GameState::is_party_ai_enabled() {
             self.ai = None;
             return;
         }
@@ -61,6 +68,7 @@ impl AI {
                 "Initialize round AI for '{}'",
                 entity.borrow().actor.actor.name
             );
+            GameState::ai_trace().borrow_mut().begin(&entity.borrow());
             self.ai = Some(EntityAI::new(&entity));
             self.next_state = State::Wait(20);
         }
@@ -84,6 +92,7 @@ fn end(ai: &mut EntityAI) -> State {
         "AI for '{}' is ending.",
         ai.entity.borrow().actor.actor.name
     );
+    GameState::ai_trace().borrow_mut().end();
     let turn_mgr = GameState::turn_manager();
     let cbs = turn_mgr.borrow_mut().next();
     script_callback::fire_round_elapsed(cbs);
@@ -106,14 +115,21 @@ struct EntityAI {
     entity: Rc<RefCell<EntityState>>,
     actions_taken_this_turn: u32,
     cur_wait_time: u32,
+    planner: Option<UtilityAI>,
 }
 
 impl EntityAI {
     fn new(entity: &Rc<RefCell<EntityState>>) -> EntityAI {
+        let planner = match &entity.borrow().actor.actor.ai {
+            Some(template) if template.uses_native_planner() => Some(UtilityAI::new(template)),
+            _ => None,
+        };
+
         EntityAI {
             entity: Rc::clone(entity),
             actions_taken_this_turn: 0,
             cur_wait_time: 0,
+            planner,
         }
     }
 
@@ -152,9 +168,23 @@ impl EntityAI {
             Some(template) => Rc::clone(template),
         };
 
-        let func = ai_template.hooks.get(&FuncKind::AiAction).map(|f| f.as_str()).unwrap_or("ai_action");
+        let hint = self.entity.borrow().condition_ai_hint(&GameState::turn_manager().borrow());
+        if hint == ConditionAIHint::SkipTurn {
+            debug!(
+                "AI for '{}' skipping turn due to condition",
+                self.entity.borrow().actor.actor.name
+            );
+            return State::End;
+        }
 
         self.actions_taken_this_turn += 1;
+        GameState::ai_trace().borrow_mut().next_step();
+
+        if let Some(planner) = &mut self.planner {
+            return planner.run(&self.entity);
+        }
+
+        let func = ai_template.hooks.get(&FuncKind::AiAction).map(|f| f.as_str()).unwrap_or("ai_action");
 
         Script::ai(&self.entity, func)
     }
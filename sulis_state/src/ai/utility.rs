@@ -0,0 +1,959 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+//! A native utility scoring planner.  Each step, every action available to
+//! the parent - attacking a hostile, activating an ability, or moving into
+//! range for either - is scored, and the highest scoring action that can
+//! actually be carried out is taken.  This mirrors the behavior of the
+//! `ai_basic` Lua script, without requiring a round trip through Lua for
+//! each decision.
+//!
+//! The following `AITemplate` params are read, all as percentages:
+//! - `AttackWeight` (default 100) scales the score of standard attacks.
+//! - `AbilityWeight` (default 100) scales the score of ability uses.
+//! - `PriorityWeight` (default 100) scales how much lower `ai` priority
+//!   values on an ability increase its score.
+//! - `MoveWeight` (default 75) scales the score of moving towards a target
+//!   relative to acting on it immediately.
+//! - `ThreatenedWeight` (default 35) is added to the weight of targets
+//!   threatening the parent.
+//! - `DistanceWeight` (default 100) scales how strongly closer targets are
+//!   preferred.
+//! - `HealThreshold` (default 50) is the hit point percentage below which
+//!   a friendly is considered for healing.
+//! - `FocusFireWeight` (default 100) scales up the weight of the target the
+//!   parent's AI group is focusing on, if the group uses focus fire.
+//! - `AttackWhenHasAbilitiesChance` and `AlwaysUseAbilityPriority` behave
+//!   as they do for `ai_basic`.
+//!
+//! The tactics of the parent's AI group are also followed: the group's
+//! focus target is preferred, melee attackers approach from flanking
+//! positions, ranged attackers step back behind the melee members of the
+//! group, and all members run away once the group is retreating.
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use sulis_module::ability::{AIData, AIGroup, AIKind, AIRange, AITarget};
+use sulis_module::{area::Destination, AITemplate, Ability, ConditionAIHint, Module};
+
+use crate::ai::State;
+use crate::script::{AreaTargeter, Script};
+use crate::{center, dist, is_within_attack_dist, is_within_touch_dist};
+use crate::{AIDecision, AIDecisionTrace, AIGroupState, EntityState, GameState};
+
+const MIN_MULTIPLE_SCORE: f32 = 1.5;
+const MOVE_THRESHOLD: f32 = 0.1;
+const WAIT_TIME: u32 = 10;
+const MAX_MOVE_LEN: u32 = 60;
+const MAX_ABILITY_FAILS: u32 = 3;
+const RUN_AWAY_DIST: f32 = 12.0;
+
+/// An action the planner may take, against the target of its `Candidate`
+#[derive(Clone)]
+pub enum Action {
+    Attack,
+    Ability(Rc<Ability>),
+
+    /// Move until within `dist` of the target, in order to attack it if
+    /// `attack` is set, or use an ability on it otherwise
+    Move { dist: f32, attack: bool },
+}
+
+/// A scored action, as considered by the planner
+#[derive(Clone)]
+pub struct Candidate {
+    pub action: Action,
+    pub target: Rc<RefCell<EntityState>>,
+    pub score: f32,
+}
+
+impl Candidate {
+    fn decision(&self, chosen: bool) -> AIDecision {
+        let (action, priority) = match &self.action {
+            Action::Attack => ("Attack", None),
+            Action::Move { .. } => ("Move", None),
+            Action::Ability(ability) => {
+                let priority = ability.active.as_ref().map(|active| active.ai.priority);
+                (ability.name.as_str(), priority)
+            }
+        };
+
+        let target = self.target.borrow();
+        AIDecision::new(action, Some(&target), Some(self.score), priority, chosen)
+    }
+}
+
+/// The native planner for a single entity's turn.  Failed actions are
+/// remembered for the remainder of the turn so they are not retried
+pub struct UtilityAI {
+    attack_weight: f32,
+    ability_weight: f32,
+    priority_weight: f32,
+    move_weight: f32,
+    threatened_weight: f32,
+    distance_weight: f32,
+    heal_threshold: f32,
+    focus_weight: f32,
+    always_use_priority: u32,
+    force_attack: bool,
+
+    group: Option<AIGroupState>,
+    repositioned: bool,
+    ability_fails: HashMap<String, u32>,
+    unreachable: HashSet<usize>,
+}
+
+struct Targets {
+    hostiles: Vec<Rc<RefCell<EntityState>>>,
+    friendlies: Vec<Rc<RefCell<EntityState>>>,
+    weights: HashMap<usize, f32>,
+}
+
+impl UtilityAI {
+    pub fn new(template: &AITemplate) -> UtilityAI {
+        let pct = |id: &str, default: i32| template.param(id, default) as f32 / 100.0;
+
+        // only roll when needed, so templates without the chance do not
+        // advance the combat random state
+        let attack_chance = template.param("AttackWhenHasAbilitiesChance", 0);
+        let force_attack = attack_chance > 0
+            && GameState::with_combat_rand(|rand| rand.gen(0, 100)) < attack_chance;
+        UtilityAI {
+            attack_weight: pct("AttackWeight", 100),
+            ability_weight: pct("AbilityWeight", 100),
+            priority_weight: pct("PriorityWeight", 100),
+            move_weight: pct("MoveWeight", 75),
+            threatened_weight: pct("ThreatenedWeight", 35),
+            distance_weight: pct("DistanceWeight", 100),
+            heal_threshold: pct("HealThreshold", 50),
+            focus_weight: pct("FocusFireWeight", 100),
+            always_use_priority: template.param("AlwaysUseAbilityPriority", 0).max(0) as u32,
+            force_attack,
+            group: None,
+            repositioned: false,
+            ability_fails: HashMap::new(),
+            unreachable: HashSet::new(),
+        }
+    }
+
+    /// Scores all candidate actions for the parent and takes the best one
+    /// that succeeds, returning the resulting AI state
+    pub fn run(&mut self, parent: &Rc<RefCell<EntityState>>) -> State {
+        let group_index = parent.borrow().ai_group();
+        self.group = group_index.and_then(|i| GameState::turn_manager().borrow_mut().ai_group(i));
+        let targets = self.find_targets(parent);
+
+        let hint = parent.borrow().condition_ai_hint(&GameState::turn_manager().borrow());
+        if hint == ConditionAIHint::Flee {
+            debug!("  Running away due to condition");
+            run_away(parent, &targets.hostiles);
+            return State::End;
+        }
+
+        if self.group.as_ref().is_some_and(|group| group.is_retreating()) {
+            debug!("  Running away with AI group");
+            run_away(parent, &targets.hostiles);
+            return State::End;
+        }
+
+        let candidates = self.candidates(parent, &targets);
+        debug!("  Scored {} candidate actions", candidates.len());
+
+        let chosen = candidates
+            .iter()
+            .position(|candidate| self.execute(parent, candidate, &targets));
+
+        if AIDecisionTrace::is_enabled() {
+            let trace = GameState::ai_trace();
+            let mut trace = trace.borrow_mut();
+            for (index, candidate) in candidates.iter().enumerate() {
+                trace.add(candidate.decision(chosen == Some(index)));
+            }
+        }
+
+        match chosen {
+            Some(index) => {
+                self.update_focus_target(parent, &candidates[index]);
+                State::Wait(WAIT_TIME)
+            }
+            None => {
+                debug!("  No candidate action succeeded.  End");
+                State::End
+            }
+        }
+    }
+
+    /// Makes the target of `candidate` the focus of the parent's AI group, if
+    /// the group uses focus fire and is not already focusing on a target
+    fn update_focus_target(&self, parent: &Rc<RefCell<EntityState>>, candidate: &Candidate) {
+        let group = match &self.group {
+            None => return,
+            Some(group) => group,
+        };
+
+        if !group.tactics().focus_fire || group.focus_target().is_some() {
+            return;
+        }
+
+        if !parent.borrow().is_hostile(&candidate.target.borrow()) {
+            return;
+        }
+
+        let target = candidate.target.borrow().index();
+        debug!("  Setting AI group {} focus target to {}", group.index(), target);
+        let mgr = GameState::turn_manager();
+        mgr.borrow_mut().set_ai_group_focus_target(group.index(), Some(target));
+    }
+
+    fn find_targets(&self, parent: &Rc<RefCell<EntityState>>) -> Targets {
+        let mut hostiles = Vec::new();
+        let mut friendlies = Vec::new();
+        let mut weights = HashMap::new();
+        let focus = self.group.as_ref().and_then(|group| group.focus_target());
+
+        let mgr = GameState::turn_manager();
+        let parent_ref = parent.borrow();
+        for entity in mgr.borrow().entity_iter() {
+            {
+                let other = entity.borrow();
+                if other.actor.is_dead() {
+                    continue;
+                }
+
+                if !other.location.is_in_area_id(&parent_ref.location.area_id) {
+                    continue;
+                }
+
+                let hostile = parent_ref.is_hostile(&other);
+                if hostile && other.actor.stats.hidden {
+                    continue;
+                }
+
+                let mut weight = self.compute_weight(&parent_ref, &other);
+                if hostile && focus == Some(other.index()) {
+                    weight *= 1.0 + self.focus_weight;
+                }
+                weights.insert(other.index(), weight);
+                if !hostile {
+                    friendlies.push(Rc::clone(&entity));
+                    continue;
+                }
+            }
+            hostiles.push(entity);
+        }
+
+        Targets {
+            hostiles,
+            friendlies,
+            weights,
+        }
+    }
+
+    /// Computes the weight of `target` from the point of view of `parent`.
+    /// This is positive for friendlies and negative for hostiles, with
+    /// larger magnitudes representing more important targets
+    fn compute_weight(&self, parent: &EntityState, target: &EntityState) -> f32 {
+        let hostile = parent.is_hostile(target);
+        let threatened = parent.actor.p_stats().is_threatened_by(target.index());
+
+        // weaker hostiles and stronger friendlies are higher priority
+        let mut modifier = if hostile {
+            -defensive_strength(target)
+        } else {
+            offensive_strength(target)
+        };
+
+        // these flags are set by the `ai_basic` on_damaged and after_attack hooks
+        let id = target.unique_id();
+        modifier += parent.get_num_flag(&format!("__damage_taken_from_{}", id));
+        modifier += parent.get_num_flag(&format!("__hard_target_for_{}", id));
+
+        self.target_weight(hostile, threatened, dist(parent, target), modifier)
+    }
+
+    /// Combines the parts of a target's weight.  `modifier` is the sum of the
+    /// target specific modifiers, such as its strength
+    fn target_weight(&self, hostile: bool, threatened: bool, distance: f32, modifier: f32) -> f32 {
+        let base = if hostile { -1.0 } else { 1.0 };
+
+        let mut modifiers = modifier;
+        if threatened {
+            modifiers += self.threatened_weight;
+        }
+
+        modifiers += self.distance_weight * (20.0 - distance) / 60.0;
+
+        base * (1.0 + modifiers)
+    }
+
+    fn weight(&self, parent: &Rc<RefCell<EntityState>>, target: &Rc<RefCell<EntityState>>,
+              targets: &Targets) -> f32 {
+        let index = target.borrow().index();
+        match targets.weights.get(&index) {
+            Some(weight) => *weight,
+            None => self.compute_weight(&parent.borrow(), &target.borrow()),
+        }
+    }
+
+    /// Builds the list of candidate actions, sorted with the highest score first.
+    /// Only candidates with a positive score are included
+    fn candidates(&self, parent: &Rc<RefCell<EntityState>>, targets: &Targets) -> Vec<Candidate> {
+        let mut candidates = Vec::new();
+
+        self.add_ability_candidates(parent, targets, &mut candidates);
+        self.add_attack_candidates(parent, targets, &mut candidates);
+
+        let can_move = parent.borrow().can_move();
+        candidates.retain(|candidate| match candidate.action {
+            Action::Move { .. } => {
+                can_move && !self.unreachable.contains(&candidate.target.borrow().index())
+            }
+            _ => true,
+        });
+
+        rank_by_score(&mut candidates, |candidate| candidate.score);
+        candidates
+    }
+
+    fn add_attack_candidates(
+        &self,
+        parent: &Rc<RefCell<EntityState>>,
+        targets: &Targets,
+        candidates: &mut Vec<Candidate>,
+    ) {
+        {
+            let parent = parent.borrow();
+            if parent.actor.stats.attack_disabled || !parent.actor.has_ap_to_attack() {
+                return;
+            }
+        }
+
+        for target in targets.hostiles.iter() {
+            let score = -self.weight(parent, target, targets) * self.attack_weight;
+
+            let action = attack_action(&parent.borrow(), &target.borrow());
+            let score = match action {
+                Action::Move { .. } => score * self.move_weight,
+                _ => score,
+            };
+
+            candidates.push(Candidate {
+                action,
+                target: Rc::clone(target),
+                score,
+            });
+        }
+    }
+
+    fn add_ability_candidates(
+        &self,
+        parent: &Rc<RefCell<EntityState>>,
+        targets: &Targets,
+        candidates: &mut Vec<Candidate>,
+    ) {
+        let ids: Vec<String> = parent.borrow().actor.ability_states.keys().cloned().collect();
+        for id in ids {
+            if !parent.borrow().actor.can_activate(&id) {
+                continue;
+            }
+
+            let fails = self.ability_fails.get(&id).copied().unwrap_or(0);
+            if fails >= MAX_ABILITY_FAILS {
+                continue;
+            }
+
+            let ability = match Module::ability(&id) {
+                None => continue,
+                Some(ability) => ability,
+            };
+
+            let ai_data = match &ability.active {
+                None => continue,
+                Some(active) => &active.ai,
+            };
+
+            if ai_data.kind == AIKind::Special {
+                continue;
+            }
+
+            if self.force_attack && ai_data.priority > self.always_use_priority {
+                continue;
+            }
+
+            let priority = 1.0 + self.priority_weight * 10.0 / ai_data.priority.max(1) as f32;
+
+            for (target, value) in self.ability_targets(parent, ai_data, targets) {
+                let score = value * self.ability_weight * priority;
+
+                let range = ability_range(&parent.borrow(), &target.borrow(), ai_data, fails);
+                let action = match range {
+                    None => Action::Ability(Rc::clone(&ability)),
+                    Some(dist) => Action::Move { dist, attack: false },
+                };
+
+                let score = match action {
+                    Action::Move { .. } => score * self.move_weight,
+                    _ => score,
+                };
+
+                candidates.push(Candidate {
+                    action,
+                    target,
+                    score,
+                });
+            }
+        }
+    }
+
+    /// Returns the potential targets for an ability with the specified `ai_data`,
+    /// along with the value of using the ability on each
+    fn ability_targets(
+        &self,
+        parent: &Rc<RefCell<EntityState>>,
+        ai_data: &AIData,
+        targets: &Targets,
+    ) -> Vec<(Rc<RefCell<EntityState>>, f32)> {
+        let heal_value = |target: &Rc<RefCell<EntityState>>| {
+            let frac = hp_frac(&target.borrow());
+            if frac < self.heal_threshold {
+                Some(self.weight(parent, target, targets) * 2.0 * (1.0 - frac))
+            } else {
+                None
+            }
+        };
+
+        if ai_data.range == AIRange::Personal {
+            let value = match ai_data.kind {
+                AIKind::Heal => heal_value(parent),
+                _ => Some(self.weight(parent, parent, targets)),
+            };
+            return value.map(|v| vec![(Rc::clone(parent), v)]).unwrap_or_default();
+        }
+
+        match ai_data.kind {
+            AIKind::Damage | AIKind::Debuff => targets
+                .hostiles
+                .iter()
+                .map(|t| (Rc::clone(t), -self.weight(parent, t, targets)))
+                .collect(),
+            AIKind::Summon => closest(parent, &targets.hostiles)
+                .map(|t| vec![(t, 1.0)])
+                .unwrap_or_default(),
+            AIKind::Heal => targets
+                .friendlies
+                .iter()
+                .filter_map(|t| heal_value(t).map(|v| (Rc::clone(t), v)))
+                .collect(),
+            AIKind::Buff | AIKind::Special => closest(parent, &targets.friendlies)
+                .map(|t| {
+                    let value = self.weight(parent, &t, targets);
+                    vec![(t, value)]
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    fn execute(
+        &mut self,
+        parent: &Rc<RefCell<EntityState>>,
+        candidate: &Candidate,
+        targets: &Targets,
+    ) -> bool {
+        let target = &candidate.target;
+        match &candidate.action {
+            Action::Attack => {
+                if self.move_behind_melee(parent, target) {
+                    return true;
+                }
+
+                debug!("  Attack '{}'", target.borrow().unique_id());
+                EntityState::attack(parent, target, None, true);
+                true
+            }
+            Action::Move { dist, attack } => {
+                if *attack && self.move_to_flank(parent, target) {
+                    return true;
+                }
+
+                debug!("  Move towards '{}'", target.borrow().unique_id());
+                let mut dest = GameState::get_target_dest(&parent.borrow(), &target.borrow());
+                dest.dist = *dist;
+                dest.max_path_len = Some(MAX_MOVE_LEN);
+
+                if move_towards(parent, dest) {
+                    return true;
+                }
+
+                debug!("    Unable to path towards '{}'", target.borrow().unique_id());
+                self.unreachable.insert(target.borrow().index());
+                false
+            }
+            Action::Ability(ability) => {
+                debug!("  Use ability '{}'", ability.id);
+                let ai_data = match &ability.active {
+                    None => return false,
+                    Some(active) => &active.ai,
+                };
+
+                let (index, func) = {
+                    let parent = parent.borrow();
+                    (parent.index(), on_activate_fn(parent.is_party_member(), ai_data))
+                };
+                Script::ability_on_activate(index, func, ability);
+
+                if self.handle_targeter(parent, target, ai_data, targets) {
+                    return true;
+                }
+
+                *self.ability_fails.entry(ability.id.to_string()).or_insert(0) += 1;
+                false
+            }
+        }
+    }
+
+    /// Moves a ranged parent back behind the melee members of its AI group
+    /// before attacking `target`, at most once per turn.  Returns true if the
+    /// parent moved
+    fn move_behind_melee(
+        &mut self,
+        parent: &Rc<RefCell<EntityState>>,
+        target: &Rc<RefCell<EntityState>>,
+    ) -> bool {
+        if self.repositioned {
+            return false;
+        }
+
+        let group = match &self.group {
+            None => return false,
+            Some(group) => group,
+        };
+
+        let members = GameState::turn_manager().borrow().ai_group_members(group.index());
+        let point = match group.ranged_point(&parent.borrow(), &target.borrow(), &members) {
+            None => return false,
+            Some(point) => point,
+        };
+
+        self.repositioned = true;
+        debug!("  Move behind melee to {},{}", point.x, point.y);
+        let dest = GameState::get_point_dest(&parent.borrow(), point.x as f32, point.y as f32);
+        move_towards(parent, dest)
+    }
+
+    /// Moves the parent to a position flanking `target`, if its AI group
+    /// flanks and there is such a position.  Returns true if the parent moved
+    fn move_to_flank(
+        &self,
+        parent: &Rc<RefCell<EntityState>>,
+        target: &Rc<RefCell<EntityState>>,
+    ) -> bool {
+        let group = match &self.group {
+            None => return false,
+            Some(group) => group,
+        };
+
+        let point = match group.flank_point(&parent.borrow(), &target.borrow()) {
+            None => return false,
+            Some(point) => point,
+        };
+
+        debug!("  Move to flank '{}' at {},{}", target.borrow().unique_id(), point.x, point.y);
+        let mut dest = GameState::get_point_dest(&parent.borrow(), point.x as f32, point.y as f32);
+        dest.max_path_len = Some(MAX_MOVE_LEN);
+        move_towards(parent, dest)
+    }
+
+    /// Finds the best position for the current targeter, if there is one,
+    /// and activates it.  Returns false if the targeter was cancelled
+    fn handle_targeter(
+        &self,
+        parent: &Rc<RefCell<EntityState>>,
+        target: &Rc<RefCell<EntityState>>,
+        ai_data: &AIData,
+        targets: &Targets,
+    ) -> bool {
+        let targeter = match GameState::area_state().borrow().targeter() {
+            None => return true,
+            Some(targeter) => targeter,
+        };
+
+        // a cancelled targeter is left over from a previous activation
+        if targeter.borrow().cancel() {
+            return true;
+        }
+
+        let best = match ai_data.kind {
+            AIKind::Heal => Some(position(&target.borrow())),
+            AIKind::Summon => {
+                let (x, y) = position(&target.borrow());
+                ground_points(x, y, -4, 4)
+                    .into_iter()
+                    .find(|(x, y)| check_position(&targeter, *x, *y))
+            }
+            _ => {
+                // want hostiles and not friendlies for damage or debuff, the opposite for others
+                let (relationship, group) = match ai_data.kind {
+                    AIKind::Damage | AIKind::Debuff => (-1.0, &targets.hostiles),
+                    _ => (1.0, &targets.friendlies),
+                };
+
+                let to_check: Vec<(i32, i32)> = if !targeter.borrow().is_free_select() {
+                    targeter.borrow().selectable().iter().map(|e| position(&e.borrow())).collect()
+                } else if ai_data.target == AITarget::Entity {
+                    group.iter().map(|e| position(&e.borrow())).collect()
+                } else {
+                    group
+                        .iter()
+                        .flat_map(|e| {
+                            let (x, y) = position(&e.borrow());
+                            ground_points(x, y, -4, 4)
+                        })
+                        .collect()
+                };
+
+                let mut best_score = 0.0;
+                let mut best = None;
+                for (x, y) in to_check {
+                    if !check_position(&targeter, x, y) {
+                        continue;
+                    }
+
+                    let affected: Vec<_> = targeter.borrow().cur_affected().to_vec();
+                    let score: f32 = affected
+                        .iter()
+                        .map(|e| self.weight(parent, e, targets) * relationship)
+                        .sum();
+
+                    if score > best_score {
+                        best_score = score;
+                        best = Some((x, y));
+                    }
+                }
+
+                debug!("    Best targeter score was {}", best_score);
+                if ai_data.group == AIGroup::Multiple && best_score < MIN_MULTIPLE_SCORE {
+                    None
+                } else {
+                    best
+                }
+            }
+        };
+
+        match best {
+            Some((x, y)) if check_position(&targeter, x, y) => {
+                debug!("    Activate targeter at {},{}", x, y);
+                targeter.borrow_mut().on_activate();
+                true
+            }
+            _ => {
+                debug!("    No valid targeter position.  Cancel");
+                targeter.borrow_mut().on_cancel();
+                false
+            }
+        }
+    }
+}
+
+/// Removes the items without a positive score, and sorts the remainder with the
+/// highest score first.  Items with equal scores keep their relative order
+fn rank_by_score<T, F: Fn(&T) -> f32>(items: &mut Vec<T>, score: F) {
+    items.retain(|item| score(item) > 0.0);
+    items.sort_by(|a, b| score(b).partial_cmp(&score(a)).unwrap_or(Ordering::Equal));
+}
+
+/// Determines whether `parent` can attack `target` from where it is standing.
+/// If not, returns the move needed to get into position
+fn attack_action(parent: &EntityState, target: &EntityState) -> Action {
+    let stats = &parent.actor.stats;
+    if stats.attack_is_melee() {
+        if is_within_attack_dist(parent, target) {
+            return Action::Attack;
+        }
+        return Action::Move {
+            dist: stats.attack_distance() - MOVE_THRESHOLD,
+            attack: true,
+        };
+    }
+
+    let cur_dist = dist(parent, target);
+    let target_dist = stats.attack_distance() - 1.0;
+    if cur_dist > target_dist {
+        return Action::Move {
+            dist: target_dist,
+            attack: true,
+        };
+    }
+
+    let area_state = GameState::area_state();
+    if !area_state.borrow().has_visibility(parent, target) {
+        return Action::Move {
+            dist: cur_dist - 2.0,
+            attack: true,
+        };
+    }
+
+    Action::Attack
+}
+
+/// Returns the distance `parent` must move to in order to use an ability with
+/// `ai_data` on `target`, or None if it is already in range.  Short and visible
+/// ranges shrink as uses of the ability fail
+fn ability_range(
+    parent: &EntityState,
+    target: &EntityState,
+    ai_data: &AIData,
+    fails: u32,
+) -> Option<f32> {
+    let target_dist = match ai_data.range {
+        AIRange::Personal => return None,
+        AIRange::Touch => {
+            if is_within_touch_dist(parent, target) {
+                return None;
+            }
+            return Some(parent.actor.stats.touch_distance() - MOVE_THRESHOLD);
+        }
+        AIRange::Attack => {
+            if is_within_attack_dist(parent, target) {
+                return None;
+            }
+            return Some(parent.actor.stats.attack_distance() - MOVE_THRESHOLD);
+        }
+        AIRange::Short => 8.0 - fails as f32 * 2.0,
+        AIRange::Visible => {
+            let area = GameState::get_area_state(&parent.location.area_id).unwrap();
+            let vis_dist = area.borrow().area.area.vis_dist as f32;
+            vis_dist - 1.0 - fails as f32 * 3.0
+        }
+    };
+
+    if dist(parent, target) <= target_dist {
+        None
+    } else {
+        Some(target_dist - MOVE_THRESHOLD)
+    }
+}
+
+fn on_activate_fn(is_party_member: bool, ai_data: &AIData) -> String {
+    match &ai_data.on_activate_fn {
+        Some(func) if !is_party_member => func.to_string(),
+        _ => "on_activate".to_string(),
+    }
+}
+
+fn check_position(targeter: &Rc<RefCell<AreaTargeter>>, x: i32, y: i32) -> bool {
+    let mut targeter = targeter.borrow_mut();
+    targeter.on_mouse_move(x, y);
+    targeter.is_valid_to_activate()
+}
+
+fn position(entity: &EntityState) -> (i32, i32) {
+    (entity.location.x, entity.location.y)
+}
+
+fn ground_points(x: i32, y: i32, min: i32, max: i32) -> Vec<(i32, i32)> {
+    let mut points = Vec::new();
+    for dy in (min..=max).step_by(2) {
+        for dx in (min..=max).step_by(2) {
+            points.push((x + dx, y + dy));
+        }
+    }
+    points
+}
+
+fn closest(
+    parent: &Rc<RefCell<EntityState>>,
+    targets: &[Rc<RefCell<EntityState>>],
+) -> Option<Rc<RefCell<EntityState>>> {
+    let parent = parent.borrow();
+    targets
+        .iter()
+        .map(|t| (dist(&*parent, &*t.borrow()), t))
+        .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal))
+        .map(|(_, t)| Rc::clone(t))
+}
+
+fn hp_frac(entity: &EntityState) -> f32 {
+    entity.actor.hp() as f32 / entity.actor.stats.max_hp.max(1) as f32
+}
+
+fn defensive_strength(target: &EntityState) -> f32 {
+    let stats = &target.actor.stats;
+    stats.armor.base() as f32 / 100.0 + target.actor.hp() as f32 / 400.0
+}
+
+fn offensive_strength(target: &EntityState) -> f32 {
+    let stats = &target.actor.stats;
+    if stats.caster_level > 0 {
+        return stats.spell_accuracy as f32 / 100.0;
+    }
+
+    let damage = match stats.attacks.first() {
+        None => 0.0,
+        Some(attack) => (attack.damage.min() + attack.damage.max()) as f32 / 2.0,
+    };
+
+    let accuracy = if stats.attack_is_melee() {
+        stats.melee_accuracy
+    } else {
+        stats.ranged_accuracy
+    };
+
+    damage / 100.0 + accuracy as f32 / 200.0
+}
+
+/// Moves away from the visible `hostiles`, trying progressively less
+/// precise destinations until a path is found
+fn run_away(parent: &Rc<RefCell<EntityState>>, hostiles: &[Rc<RefCell<EntityState>>]) {
+    let (px, py) = center(&*parent.borrow());
+
+    let (mut dir_x, mut dir_y) = (0.0, 0.0);
+    {
+        let area_state = GameState::area_state();
+        let area_state = area_state.borrow();
+        for hostile in hostiles {
+            let hostile = hostile.borrow();
+            if !area_state.has_visibility(&parent.borrow(), &hostile) {
+                continue;
+            }
+
+            let (hx, hy) = center(&*hostile);
+            let len = (px - hx).hypot(py - hy);
+            if len > 0.0 {
+                dir_x += (px - hx) / len;
+                dir_y += (py - hy) / len;
+            }
+        }
+    }
+
+    let len = f32::hypot(dir_x, dir_y);
+    if len == 0.0 {
+        return;
+    }
+
+    let (x, y) = {
+        let location = &parent.borrow().location;
+        (
+            location.x as f32 + dir_x / len * RUN_AWAY_DIST,
+            location.y as f32 + dir_y / len * RUN_AWAY_DIST,
+        )
+    };
+
+    for thresh in 1..=10 {
+        let mut dest = GameState::get_point_dest(&parent.borrow(), x, y);
+        dest.dist = thresh as f32;
+        if move_towards(parent, dest) {
+            return;
+        }
+    }
+}
+
+/// Moves towards `dest`, passing through friendlies in the same AI group
+fn move_towards(parent: &Rc<RefCell<EntityState>>, dest: Destination) -> bool {
+    let mgr = GameState::turn_manager();
+    let area = GameState::get_area_state(&parent.borrow().location.area_id).unwrap();
+    let mut to_ignore = vec![parent.borrow().index()];
+
+    for e in area.borrow().entity_iter() {
+        let other = mgr.borrow().entity(*e);
+        if parent.borrow().ai_group() != other.borrow().ai_group() {
+            continue;
+        }
+
+        if parent.borrow().is_friendly(&other.borrow()) {
+            to_ignore.push(*e);
+        }
+    }
+
+    GameState::move_towards_dest(parent, &to_ignore, dest, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn planner(params: &[(&str, i32)]) -> UtilityAI {
+        let template = AITemplate {
+            id: "test".to_string(),
+            script: "ai_basic".to_string(),
+            planner: Default::default(),
+            hooks: HashMap::new(),
+            params: params.iter().map(|(id, value)| (id.to_string(), *value)).collect(),
+        };
+        UtilityAI::new(&template)
+    }
+
+    fn ranked(scores: &[(&'static str, f32)]) -> Vec<&'static str> {
+        let mut items = scores.to_vec();
+        rank_by_score(&mut items, |(_, score)| *score);
+        items.into_iter().map(|(id, _)| id).collect()
+    }
+
+    #[test]
+    fn weight_sign_follows_hostility() {
+        let ai = planner(&[]);
+        assert!(ai.target_weight(true, false, 5.0, 0.0) < 0.0);
+        assert!(ai.target_weight(false, false, 5.0, 0.0) > 0.0);
+        assert_eq!(
+            ai.target_weight(true, true, 5.0, 0.5),
+            -ai.target_weight(false, true, 5.0, 0.5)
+        );
+    }
+
+    #[test]
+    fn weight_prefers_close_and_threatening_targets() {
+        let ai = planner(&[]);
+        let far = ai.target_weight(true, false, 15.0, 0.0);
+        let near = ai.target_weight(true, false, 2.0, 0.0);
+        let threatening = ai.target_weight(true, true, 15.0, 0.0);
+        assert!(near.abs() > far.abs());
+        assert!(threatening.abs() > far.abs());
+    }
+
+    #[test]
+    fn weight_params_scale_modifiers() {
+        let ai = planner(&[("ThreatenedWeight", 0), ("DistanceWeight", 0)]);
+        assert_eq!(ai.target_weight(true, true, 2.0, 0.0), -1.0);
+        assert_eq!(ai.target_weight(false, true, 15.0, 0.5), 1.5);
+
+        let ai = planner(&[("ThreatenedWeight", 100), ("DistanceWeight", 0)]);
+        assert_eq!(ai.target_weight(true, true, 2.0, 0.0), -2.0);
+
+        let ai = planner(&[("ThreatenedWeight", 0), ("DistanceWeight", 300)]);
+        assert_eq!(ai.target_weight(false, false, 0.0, 0.0), 2.0);
+    }
+
+    #[test]
+    fn ranking_sorts_highest_score_first() {
+        let order = ranked(&[("low", 0.5), ("high", 3.0), ("mid", 1.0)]);
+        assert_eq!(order, vec!["high", "mid", "low"]);
+    }
+
+    #[test]
+    fn ranking_drops_non_positive_scores() {
+        let order = ranked(&[("zero", 0.0), ("kept", 0.1), ("negative", -2.0)]);
+        assert_eq!(order, vec!["kept"]);
+    }
+
+    #[test]
+    fn ranking_keeps_candidate_order_for_ties() {
+        // abilities are added before attacks, so win ties against them
+        let order = ranked(&[("ability", 1.0), ("attack", 1.0), ("move", 2.0)]);
+        assert_eq!(order, vec!["move", "ability", "attack"]);
+    }
+}
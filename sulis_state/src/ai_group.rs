@@ -0,0 +1,206 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sulis_core::util::Point;
+use sulis_module::GroupTactics;
+
+use crate::entity_attack_handler::is_flanking_from;
+use crate::{attack_distance, center, dist, EntityState, GameState};
+
+/// Ranged members try to stay at least this much further from their
+/// target than the closest melee member of their group
+const RANGED_MARGIN: f32 = 3.0;
+
+/// Positions are only chosen if they are at least this far inside
+/// of attack range
+const RANGE_THRESHOLD: f32 = 0.1;
+
+/// The state shared by all members of a spawned encounter.  The focus
+/// target and retreat are saved; the tactics are read from the encounter
+/// again when the group is loaded
+#[derive(Debug, Clone)]
+pub struct AIGroupState {
+    index: usize,
+    tactics: GroupTactics,
+    focus_target: Option<usize>,
+    retreating: bool,
+}
+
+impl AIGroupState {
+    pub(crate) fn new(index: usize, tactics: GroupTactics) -> AIGroupState {
+        AIGroupState {
+            index,
+            tactics,
+            focus_target: None,
+            retreating: false,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn tactics(&self) -> GroupTactics {
+        self.tactics
+    }
+
+    /// The entity index of the target that members of this group should
+    /// prefer to attack, if focus fire is enabled and a target is set
+    pub fn focus_target(&self) -> Option<usize> {
+        if !self.tactics.focus_fire {
+            return None;
+        }
+
+        self.focus_target
+    }
+
+    pub fn is_retreating(&self) -> bool {
+        self.retreating
+    }
+
+    pub(crate) fn set_focus_target(&mut self, target: Option<usize>) {
+        self.focus_target = target;
+    }
+
+    pub(crate) fn set_retreating(&mut self, retreating: bool) {
+        self.retreating = retreating;
+    }
+
+    /// Starts the group retreat once the combined hit points of the
+    /// `members`, including dead members, reach the retreat threshold
+    pub(crate) fn check_retreat(&mut self, members: &[Rc<RefCell<EntityState>>]) {
+        if self.retreating || self.tactics.retreat_threshold <= 0.0 {
+            return;
+        }
+
+        let (mut cur_hp, mut max_hp) = (0, 0);
+        for member in members {
+            let member = member.borrow();
+            cur_hp += member.actor.hp().max(0);
+            max_hp += member.actor.stats.max_hp;
+        }
+
+        if max_hp <= 0 {
+            return;
+        }
+
+        if cur_hp as f32 / max_hp as f32 <= self.tactics.retreat_threshold {
+            info!("AI group {} is retreating", self.index);
+            self.retreating = true;
+        }
+    }
+
+    /// Finds the closest position to `parent` where it could attack
+    /// `target` while flanking it.  Returns `None` if flanking is disabled
+    /// for this group, `parent` does not have a melee attack, or there is no
+    /// such position
+    pub fn flank_point(&self, parent: &EntityState, target: &EntityState) -> Option<Point> {
+        if !self.tactics.flank || !parent.actor.stats.attack_is_melee() {
+            return None;
+        }
+
+        let area = GameState::get_area_state(&parent.location.area_id)?;
+        let area = area.borrow();
+
+        let max_dist = attack_distance(parent, target) - RANGE_THRESHOLD;
+        let (w, h) = (parent.size.width as f32, parent.size.height as f32);
+        let reach = max_dist.ceil() as i32 + parent.size.width.max(parent.size.height);
+        let p_start = center(parent);
+
+        let mut best: Option<(f32, Point)> = None;
+        for y in (target.location.y - reach)..=(target.location.y + target.size.height + reach) {
+            for x in (target.location.x - reach)..=(target.location.x + target.size.width + reach) {
+                let p = (x as f32 + w / 2.0, y as f32 + h / 2.0);
+                if dist(&(p.0 - 0.5, p.1 - 0.5), target) > max_dist {
+                    continue;
+                }
+
+                let move_dist = (p.0 - p_start.0).hypot(p.1 - p_start.1);
+                if let Some((best_dist, _)) = best {
+                    if move_dist >= best_dist {
+                        continue;
+                    }
+                }
+
+                if !area.is_passable_for_entity(parent, x, y) {
+                    continue;
+                }
+
+                if !is_flanking_from(parent, p, target) {
+                    continue;
+                }
+
+                best = Some((move_dist, Point::new(x, y)));
+            }
+        }
+
+        best.map(|(_, point)| point)
+    }
+
+    /// Finds a position for `parent` further away from `target` than the
+    /// melee `members` of this group, but still within attack range.
+    /// Returns `None` if this tactic is disabled for this group, `parent`
+    /// does not have a ranged attack, or `parent` is already behind the melee
+    /// members
+    pub fn ranged_point(
+        &self,
+        parent: &EntityState,
+        target: &EntityState,
+        members: &[Rc<RefCell<EntityState>>],
+    ) -> Option<Point> {
+        if !self.tactics.ranged_behind_melee || !parent.actor.stats.attack_is_ranged() {
+            return None;
+        }
+
+        let mut front: Option<f32> = None;
+        for member in members {
+            let member = member.borrow();
+            if member.index() == parent.index() || member.actor.is_dead() {
+                continue;
+            }
+
+            if !member.actor.stats.attack_is_melee() {
+                continue;
+            }
+
+            let member_dist = dist(&*member, target);
+            front = Some(front.map_or(member_dist, |front| front.min(member_dist)));
+        }
+
+        let max_dist = attack_distance(parent, target) - 1.0;
+        let desired = (front? + RANGED_MARGIN).min(max_dist);
+        let cur = dist(parent, target);
+        if cur >= desired - 1.0 {
+            return None;
+        }
+
+        let (tx, ty) = center(target);
+        let (px, py) = center(parent);
+        let (dx, dy) = (px - tx, py - ty);
+        let len = dx.hypot(dy);
+        if len < RANGE_THRESHOLD {
+            return None;
+        }
+
+        let extra = desired - cur;
+        let x = parent.location.x as f32 + dx / len * extra;
+        let y = parent.location.y as f32 + dy / len * extra;
+        Some(Point::new(x.round() as i32, y.round() as i32))
+    }
+}
@@ -0,0 +1,150 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use sulis_core::config::Config;
+use sulis_core::util::Point;
+
+use crate::{ChangeListenerList, EntityState};
+
+/// A single action considered by an AI while deciding what to do
+#[derive(Debug, Clone)]
+pub struct AIDecision {
+    /// The AI step this action was considered in, starting at 1 for the
+    /// first action of the turn
+    pub step: u32,
+    pub action: String,
+    pub target: Option<usize>,
+    pub target_name: Option<String>,
+
+    /// The score given to this action, for the native planner, or the
+    /// `compute_weight` of the target, for Lua AI
+    pub score: Option<f32>,
+
+    /// The `ai` priority of the ability, for ability uses
+    pub priority: Option<u32>,
+
+    /// Whether this action was actually taken
+    pub chosen: bool,
+}
+
+impl AIDecision {
+    pub fn new(
+        action: &str,
+        target: Option<&EntityState>,
+        score: Option<f32>,
+        priority: Option<u32>,
+        chosen: bool,
+    ) -> AIDecision {
+        AIDecision {
+            step: 0,
+            action: action.to_string(),
+            target: target.map(|t| t.index()),
+            target_name: target.map(|t| t.actor.actor.name.to_string()),
+            score,
+            priority,
+            chosen,
+        }
+    }
+}
+
+/// The decisions made by the AI for the most recent entity to take an AI
+/// turn.  Decisions are only recorded when the `show_ai_decisions` debug
+/// option is enabled.  The trace is not saved.
+#[derive(Default)]
+pub struct AIDecisionTrace {
+    entity: Option<usize>,
+    entity_name: String,
+    active: bool,
+    step: u32,
+    decisions: Vec<AIDecision>,
+    paths: Vec<Vec<Point>>,
+    pub listeners: ChangeListenerList<AIDecisionTrace>,
+}
+
+impl AIDecisionTrace {
+    pub fn is_enabled() -> bool {
+        Config::debug().show_ai_decisions
+    }
+
+    /// Clears the trace and starts recording the turn of `entity`
+    pub(crate) fn begin(&mut self, entity: &EntityState) {
+        if !AIDecisionTrace::is_enabled() {
+            return;
+        }
+
+        self.entity = Some(entity.index());
+        self.entity_name = entity.actor.actor.name.to_string();
+        self.active = true;
+        self.step = 0;
+        self.decisions.clear();
+        self.paths.clear();
+        self.listeners.notify(self);
+    }
+
+    pub(crate) fn next_step(&mut self) {
+        self.step += 1;
+    }
+
+    /// Stops recording.  The trace is kept until the next AI turn begins
+    pub(crate) fn end(&mut self) {
+        self.active = false;
+    }
+
+    pub(crate) fn add(&mut self, mut decision: AIDecision) {
+        if !self.active || !AIDecisionTrace::is_enabled() {
+            return;
+        }
+
+        decision.step = self.step;
+        self.decisions.push(decision);
+        self.listeners.notify(self);
+    }
+
+    /// Records the path taken by `entity`, if it is the entity whose turn is
+    /// currently being recorded
+    pub(crate) fn add_path(&mut self, entity: &EntityState, path: &[Point]) {
+        if !self.active || self.entity != Some(entity.index()) {
+            return;
+        }
+
+        self.paths.push(path.to_vec());
+        self.listeners.notify(self);
+    }
+
+    /// The index of the entity this trace was recorded for
+    pub fn entity(&self) -> Option<usize> {
+        self.entity
+    }
+
+    pub fn entity_name(&self) -> &str {
+        &self.entity_name
+    }
+
+    /// The most recent step with recorded decisions
+    pub fn step(&self) -> u32 {
+        self.step
+    }
+
+    /// Iterates over the decisions in this trace, from oldest to newest
+    pub fn decisions(&self) -> impl DoubleEndedIterator<Item = &AIDecision> {
+        self.decisions.iter()
+    }
+
+    /// The paths moved along during the recorded turn, in order
+    pub fn paths(&self) -> &[Vec<Point>] {
+        &self.paths
+    }
+}
@@ -17,9 +17,9 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use crate::{animation::Anim, AreaFeedbackText, EntityState, GameState};
+use crate::{animation::Anim, detection, AreaFeedbackText, EntityState, GameState};
 use crate::{script::ScriptEntitySet, ScriptCallback};
-use sulis_module::{DamageKind, HitFlags, HitKind};
+use sulis_module::{DamageKind, HitFlags, HitKind, Module};
 
 pub(in crate::animation) fn update(
     attacker: &Rc<RefCell<EntityState>>,
@@ -88,6 +88,7 @@ pub(in crate::animation) fn update(
 
 pub(in crate::animation) fn cleanup(owner: &Rc<RefCell<EntityState>>) {
     owner.borrow_mut().sub_pos = (0.0, 0.0);
+    detection::make_noise(owner, Module::rules().attack_noise_radius);
 
     if !GameState::is_combat_active() {
         let area_state = GameState::get_area_state(&owner.borrow().location.area_id).unwrap();
@@ -19,6 +19,7 @@ use std::cmp;
 use std::rc::Rc;
 
 use crate::{animation::Anim, EntityState, GameState, animation::particle_generator::Param};
+use crate::reaction;
 use sulis_core::io::{DrawList, GraphicsRenderer};
 use sulis_core::ui::animation_state;
 use sulis_core::util::{Offset, Point, Rect, Scale, ExtInt};
@@ -57,6 +58,22 @@ pub(in crate::animation) fn update(
         return;
     }
 
+    // the move waits while the player decides on a reaction to it
+    let mgr = GameState::turn_manager();
+    if model.combat_mode && mgr.borrow().has_reaction_prompt() {
+        model.paused_millis += millis - model.last_millis;
+        model.last_millis = millis;
+        return;
+    }
+    model.last_millis = millis;
+    let millis = millis - model.paused_millis;
+
+    let index = mover.borrow().index();
+    if mgr.borrow_mut().take_move_interrupt(index) {
+        marked_for_removal.set(true);
+        return;
+    }
+
     let frame_index = cmp::min(
         (millis / model.frame_time_millis) as usize,
         model.path.len() - 1,
@@ -83,6 +100,11 @@ pub(in crate::animation) fn update(
     model.last_frame_index = frame_index as i32;
 
     let p = model.path[frame_index];
+    let threatened_by = if model.combat_mode {
+        reaction::threatened_by(mover)
+    } else {
+        Vec::new()
+    };
     let area_state = GameState::get_area_state(&mover.borrow().location.area_id).unwrap();
     if !area_state
         .borrow_mut()
@@ -92,6 +114,12 @@ pub(in crate::animation) fn update(
         return;
     }
 
+    // a reaction to the move interrupts it
+    if model.combat_mode && reaction::check_move(mover, &threatened_by) {
+        marked_for_removal.set(true);
+        return;
+    }
+
     if frame_index == model.path.len() - 1 {
         marked_for_removal.set(true);
     }
@@ -221,6 +249,8 @@ pub fn new(mover: &Rc<RefCell<EntityState>>, path: Vec<Point>, base_time_millis:
         frame_time_millis,
         smoothed_path,
         owner_size: Rc::clone(&mover.borrow().size),
+        last_millis: 0,
+        paused_millis: 0,
     };
 
     Anim::new_move(mover, duration_millis, model)
@@ -235,4 +265,6 @@ pub struct MoveAnimModel {
     frame_time_millis: u32,
     smoothed_path: Vec<(f32, f32)>,
     owner_size: Rc<ObjectSize>,
+    last_millis: u32,
+    paused_millis: u32,
 }
@@ -18,11 +18,12 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use crate::{animation::Anim, entity_attack_handler::weapon_attack, AreaFeedbackText};
-use crate::{script::ScriptEntitySet, EntityState, GameState, ScriptCallback};
+use crate::{detection, script::ScriptEntitySet, EntityState, GameState, ScriptCallback};
 use sulis_core::image::Image;
 use sulis_core::io::{DrawList, GraphicsRenderer};
 use sulis_core::ui::animation_state;
 use sulis_core::util::{Offset, Rect, Scale};
+use sulis_module::Module;
 
 pub(in crate::animation) fn update(
     attacker: &Rc<RefCell<EntityState>>,
@@ -92,6 +93,8 @@ pub(in crate::animation) fn update(
 }
 
 pub(in crate::animation) fn cleanup(owner: &Rc<RefCell<EntityState>>) {
+    detection::make_noise(owner, Module::rules().attack_noise_radius);
+
     if !GameState::is_combat_active() {
         let area_state = GameState::get_area_state(&owner.borrow().location.area_id).unwrap();
         let mgr = GameState::turn_manager();
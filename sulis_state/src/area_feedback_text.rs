@@ -36,7 +36,8 @@ pub struct Params {
     pub miss_color: Color,
     pub hit_color: Color,
     pub heal_color: Color,
-    pub damage_colors: [Color; 8],
+    pub damage_colors: Vec<Color>,
+    pub damage_icons: Vec<Option<Rc<dyn Image>>>,
 
     pub concealment_icon: Rc<dyn Image>,
     pub backstab_icon: Rc<dyn Image>,
@@ -44,6 +45,9 @@ pub struct Params {
     pub crit_icon: Rc<dyn Image>,
     pub hit_icon: Rc<dyn Image>,
     pub graze_icon: Rc<dyn Image>,
+
+    pub partial_cover_icon: Rc<dyn Image>,
+    pub full_cover_icon: Rc<dyn Image>,
 }
 
 impl Default for Params {
@@ -58,15 +62,16 @@ impl Default for Params {
             miss_color: LIGHT_GRAY,
             hit_color: RED,
             heal_color: BLUE,
-            damage_colors: [
-                LIGHT_GRAY, LIGHT_GRAY, LIGHT_GRAY, GREEN, CYAN, BLUE, YELLOW, PURPLE,
-            ],
+            damage_colors: Vec::new(),
+            damage_icons: Vec::new(),
             concealment_icon: ResourceSet::empty_image(),
             backstab_icon: ResourceSet::empty_image(),
             flanking_icon: ResourceSet::empty_image(),
             crit_icon: ResourceSet::empty_image(),
             hit_icon: ResourceSet::empty_image(),
             graze_icon: ResourceSet::empty_image(),
+            partial_cover_icon: ResourceSet::empty_image(),
+            full_cover_icon: ResourceSet::empty_image(),
         }
     }
 }
@@ -94,6 +99,7 @@ pub enum IconKind {
     Crit,
     Hit,
     Graze,
+    Damage { kind: DamageKind },
 }
 
 pub struct AreaFeedbackText {
@@ -140,6 +146,9 @@ impl AreaFeedbackText {
 
             let color = ColorKind::Damage { kind: *kind };
             text.add_entry(format!("{}", amount), color);
+            if kind.icon().is_some() {
+                text.add_icon_entry(IconKind::Damage { kind: *kind }, color);
+            }
 
             first = false;
         }
@@ -277,7 +286,7 @@ impl AreaFeedbackText {
                 ColorKind::Heal => params.heal_color,
                 ColorKind::Damage { kind } => {
                     let index = kind.index();
-                    params.damage_colors[index]
+                    params.damage_colors.get(index).copied().unwrap_or(params.info_color)
                 }
             };
             color.a *= self.alpha;
@@ -294,6 +303,10 @@ impl AreaFeedbackText {
                     IconKind::Crit => &params.crit_icon,
                     IconKind::Hit => &params.hit_icon,
                     IconKind::Graze => &params.graze_icon,
+                    IconKind::Damage { kind } => match params.damage_icons.get(kind.index()) {
+                        Some(Some(icon)) => icon,
+                        _ => continue,
+                    },
                 };
 
                 let rect = Rect {
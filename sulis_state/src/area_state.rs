@@ -30,7 +30,7 @@ use sulis_core::io::Audio;
 use sulis_core::config::Config;
 use sulis_core::util::{self, gen_rand, invalid_data_error, Point, Size};
 use sulis_module::area::{Transition, TriggerKind, Trigger};
-use sulis_module::{Actor, Area, LootList, Module, ObjectSize, Time};
+use sulis_module::{Actor, Area, Cover, LootList, Module, ObjectSize, Time};
 
 pub struct TriggerState {
     pub(crate) fired: bool,
@@ -261,7 +261,12 @@ impl AreaState {
             let location = Location::from_point(actor_data.location, &area);
             debug!("Adding actor '{}' at '{:?}'", actor.id, location);
             match self.add_actor(actor, location, Some(unique_id), false, None) {
-                Ok(_) => (),
+                Ok(index) => {
+                    let patrol = actor_data.patrol.clone();
+                    let schedule = actor_data.schedule.clone();
+                    let entity = GameState::turn_manager().borrow().entity(index);
+                    entity.borrow_mut().schedule = ScheduleState::new(patrol, schedule);
+                }
                 Err(e) => {
                     warn!("Error adding actor to area: {}", e);
                 }
@@ -649,6 +654,9 @@ impl AreaState {
             return;
         }
 
+        let center = distance_finder::center(self.props.get(index));
+        detection::make_noise_at(&self.area.area.id, center, Module::rules().door_noise_radius);
+
         self.pc_vis_partial_redraw(0, 0);
         for member in GameState::party().iter() {
             self.compute_pc_visibility(member, 0, 0);
@@ -660,6 +668,18 @@ impl AreaState {
         has_visibility(&self.area, self.props.entire_vis_grid(), parent, target)
     }
 
+    /// Returns the cover that `target` has against attacks from `parent`,
+    /// based on the walls and props between them
+    pub fn cover(&self, parent: &EntityState, target: &EntityState) -> Cover {
+        calculate_cover(
+            &self.area,
+            self.props.entire_vis_grid(),
+            self.props.entire_pass_grid(),
+            parent,
+            target,
+        )
+    }
+
     pub fn compute_pc_visibility(
         &mut self,
         entity: &Rc<RefCell<EntityState>>,
@@ -897,6 +917,16 @@ impl AreaState {
         result
     }
 
+    fn update_entity_elevation(&self, entity: &Rc<RefCell<EntityState>>) {
+        let elevation = {
+            let entity = entity.borrow();
+            let x = entity.location.x + entity.size.width / 2;
+            let y = entity.location.y + entity.size.height / 2;
+            self.area.layer_set.elevation(x, y)
+        };
+        entity.borrow_mut().elevation = elevation;
+    }
+
     fn compute_threatened(
         &self,
         mover: &Rc<RefCell<EntityState>>,
@@ -956,6 +986,7 @@ impl AreaState {
         entity.borrow_mut().actor.compute_stats();
 
         entity.borrow_mut().location = location;
+        self.update_entity_elevation(entity);
         self.entities.push(index);
 
         let mgr = GameState::turn_manager();
@@ -1037,6 +1068,7 @@ impl AreaState {
         let old_surfaces = self.clear_entity_points(&entity.borrow(), old_x, old_y);
         let new_surfaces = self.add_entity_points(&entity.borrow());
 
+        self.update_entity_elevation(entity);
         self.compute_threatened(entity, mgr, false);
         // remove from surfaces in old but not in new
         for surface in old_surfaces.difference(&new_surfaces) {
@@ -0,0 +1,121 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::collections::VecDeque;
+
+use sulis_module::{AttackRoll, Cover, DamageRoll, HitFlags, HitKind, Time};
+
+use crate::{ChangeListenerList, EntityState};
+
+const MAX_ENTRIES: usize = 500;
+
+/// A record of a single attack, including the full breakdown of the
+/// rolls that produced the result
+#[derive(Debug, Clone)]
+pub struct CombatLogEntry {
+    pub time: Time,
+    pub attacker: usize,
+    pub attacker_name: String,
+    pub target: usize,
+    pub target_name: String,
+    pub hit_kind: HitKind,
+    pub hit_flags: HitFlags,
+
+    /// The cover the target had from props and walls between it and the
+    /// attacker
+    pub cover: Cover,
+
+    /// The attacker's elevation above the target, negative if the attacker
+    /// was below the target
+    pub elevation: i32,
+
+    /// The target's concealment, after subtracting the attacker's
+    /// concealment ignore
+    pub concealment: i32,
+
+    /// The accuracy roll against the target's defense.  This is `None`
+    /// if the attack missed due to concealment
+    pub roll: Option<AttackRoll>,
+
+    /// Each component of damage, before and after armor and resistance
+    pub damage: Vec<DamageRoll>,
+}
+
+impl CombatLogEntry {
+    pub(crate) fn new(time: Time, attacker: &EntityState, target: &EntityState) -> CombatLogEntry {
+        CombatLogEntry {
+            time,
+            attacker: attacker.index(),
+            attacker_name: attacker.actor.actor.name.to_string(),
+            target: target.index(),
+            target_name: target.actor.actor.name.to_string(),
+            hit_kind: HitKind::Miss,
+            hit_flags: HitFlags::default(),
+            cover: Cover::None,
+            elevation: 0,
+            concealment: 0,
+            roll: None,
+            damage: Vec::new(),
+        }
+    }
+
+    /// Returns true if the entity with the specified index was either the
+    /// attacker or the target of this attack
+    pub fn involves(&self, index: usize) -> bool {
+        self.attacker == index || self.target == index
+    }
+
+    /// The total damage dealt by this attack, after armor and resistance
+    pub fn total_damage(&self) -> u32 {
+        self.damage.iter().map(|damage| damage.amount).sum()
+    }
+}
+
+/// The log of recent attacks made in the current game.  The log is not
+/// saved, and only the most recent entries are kept.
+#[derive(Default)]
+pub struct CombatLog {
+    entries: VecDeque<CombatLogEntry>,
+    pub listeners: ChangeListenerList<CombatLog>,
+}
+
+impl CombatLog {
+    pub(crate) fn add(&mut self, entry: CombatLogEntry) {
+        if self.entries.len() == MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+        self.listeners.notify(self);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.listeners.notify(self);
+    }
+
+    /// Iterates over the entries in this log, from oldest to newest
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &CombatLogEntry> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,283 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use sulis_module::{Faction, Module};
+
+use crate::{distance_finder, EntityState, GameState, ScriptCallback};
+
+/// How aware an entity is of hidden hostiles around it
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Awareness {
+    #[default]
+    Unaware,
+
+    /// The entity has noticed something, either with a successful perception
+    /// roll or by hearing a noise, and is more likely to spot hidden entities
+    Suspicious,
+
+    /// The entity has spotted a hidden entity
+    Alerted,
+}
+
+impl fmt::Display for Awareness {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            Awareness::Unaware => "Unaware",
+            Awareness::Suspicious => "Suspicious",
+            Awareness::Alerted => "Alerted",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// The awareness of an entity, along with how long it will remain suspicious
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct AwarenessState {
+    #[serde(default)]
+    awareness: Awareness,
+
+    #[serde(default)]
+    rounds: u32,
+
+    // the last round this state was updated for
+    #[serde(default)]
+    round: u32,
+
+    // the hidden entities this entity has already rolled against this round
+    #[serde(skip)]
+    rolled: Vec<usize>,
+}
+
+impl AwarenessState {
+    pub fn awareness(&self) -> Awareness {
+        self.awareness
+    }
+
+    fn suspect(&mut self) {
+        self.rounds = Module::rules().detection_suspicious_rounds;
+        if self.awareness == Awareness::Unaware {
+            self.awareness = Awareness::Suspicious;
+        }
+    }
+
+    /// Raises the awareness after a successful perception roll, returning
+    /// true if the entity is now alerted
+    fn raise(&mut self, alert: bool) -> bool {
+        if alert || self.awareness != Awareness::Unaware {
+            self.awareness = Awareness::Alerted;
+            true
+        } else {
+            self.suspect();
+            false
+        }
+    }
+
+    fn update_round(&mut self, round: u32, combat_active: bool) {
+        if round == self.round {
+            return;
+        }
+
+        let rounds = round.saturating_sub(self.round);
+        self.round = round;
+        self.rolled.clear();
+
+        match self.awareness {
+            Awareness::Unaware => (),
+            Awareness::Suspicious => {
+                self.rounds = self.rounds.saturating_sub(rounds);
+                if self.rounds == 0 {
+                    self.awareness = Awareness::Unaware;
+                }
+            }
+            Awareness::Alerted => {
+                if !combat_active {
+                    self.awareness = Awareness::Unaware;
+                    self.suspect();
+                }
+            }
+        }
+    }
+}
+
+/// Rolls the perception of `observer` against the stealth of `hidden`,
+/// returning the margin the roll succeeded by.  Negative values are failures
+fn roll(observer: &EntityState, hidden: &EntityState, brightness: f32) -> i32 {
+    let rules = Module::rules();
+
+    let dist = distance_finder::dist(observer, hidden) - rules.detection_break_even_distance;
+    let darkness = (1.0 - brightness) * rules.detection_darkness_bonus as f32;
+    let stealth = hidden.actor.stats.stealth
+        + hidden.actor.stats.concealment
+        + (dist * rules.detection_distance_factor) as i32
+        + darkness as i32;
+
+    let roll = GameState::with_combat_rand(|rand| rand.gen(0, rules.detection_roll_max + 1));
+    let mut perception = observer.actor.stats.perception + roll;
+    if observer.awareness.awareness == Awareness::Suspicious {
+        perception += rules.detection_suspicious_bonus;
+    }
+
+    trace!(
+        "Detection roll for '{}' vs '{}': {} vs {}",
+        observer.unique_id(),
+        hidden.unique_id(),
+        perception,
+        stealth
+    );
+
+    perception - stealth
+}
+
+/// How bright the current area is at the current time of day, from 0 to 1
+fn brightness() -> f32 {
+    let time = GameState::turn_manager().borrow().current_time();
+    let area_state = GameState::area_state();
+    let kind = area_state.borrow().area.area.location_kind;
+    let color = Module::rules().get_area_color(kind, time);
+    ((color.r + color.g + color.b) / 3.0).clamp(0.0, 1.0)
+}
+
+fn is_observer(entity: &EntityState) -> bool {
+    !entity.actor.is_dead()
+        && !entity.is_marked_for_removal()
+        && (entity.is_party_member() || entity.actor.actor.ai.is_some())
+}
+
+/// Gives each entity in the current area a perception roll against each
+/// hostile hidden entity it can see, once per round.  Entities that become
+/// alerted spot the hidden entity, firing its `on_spotted` callbacks.
+pub(crate) fn update() {
+    let mgr = GameState::turn_manager();
+    let round = mgr.borrow().current_round();
+    let combat_active = mgr.borrow().is_combat_active();
+    for entity in mgr.borrow().entity_iter() {
+        entity.borrow_mut().awareness.update_round(round, combat_active);
+    }
+
+    let area_state = GameState::area_state();
+    let area_id = area_state.borrow().area.area.id.to_string();
+
+    let (hidden, observers): (Vec<_>, Vec<_>) = mgr
+        .borrow()
+        .entity_iter()
+        .filter(|entity| {
+            let entity = entity.borrow();
+            entity.location.is_in_area_id(&area_id) && is_observer(&entity)
+        })
+        .partition(|entity| entity.borrow().actor.stats.hidden);
+
+    if hidden.is_empty() {
+        return;
+    }
+
+    let brightness = brightness();
+    let mut spotted = Vec::new();
+    for target in hidden.iter() {
+        let target = target.borrow();
+        for observer in observers.iter() {
+            let mut observer = observer.borrow_mut();
+            if !observer.is_hostile(&target) || observer.awareness.rolled.contains(&target.index())
+            {
+                continue;
+            }
+            if !area_state.borrow().has_visibility(&observer, &target) {
+                continue;
+            }
+
+            observer.awareness.rolled.push(target.index());
+            let margin = roll(&observer, &target, brightness);
+            if margin <= 0 {
+                continue;
+            }
+
+            let alert = margin >= Module::rules().detection_alert_margin;
+            if observer.awareness.raise(alert) {
+                spotted.push((target.index(), observer.index()));
+            }
+        }
+    }
+
+    for (target, observer) in spotted {
+        let target = mgr.borrow().entity(target);
+        if !target.borrow().actor.stats.hidden {
+            continue;
+        }
+
+        info!(
+            "'{}' spotted '{}'",
+            mgr.borrow().entity(observer).borrow().actor.actor.name,
+            target.borrow().actor.actor.name
+        );
+
+        let cbs = target.borrow().callbacks(&mgr.borrow());
+        cbs.iter().for_each(|cb| cb.on_spotted(observer));
+    }
+}
+
+/// Makes a noise originating from `source`, which makes any entities hostile
+/// to it within `radius` suspicious.  Entities with active AI are already in
+/// combat, and do not react.
+pub fn make_noise(source: &Rc<RefCell<EntityState>>, radius: f32) {
+    let source = source.borrow();
+    hear_noise(&source.location.area_id, Some(source.index()), radius, |entity| {
+        entity.is_hostile(&source) && distance_finder::dist(entity, &*source) <= radius
+    });
+}
+
+/// Makes a noise at the specified point in the area, such as from a door
+/// opening, which makes any entities hostile to the party within `radius`
+/// suspicious.
+pub fn make_noise_at(area_id: &str, point: (f32, f32), radius: f32) {
+    hear_noise(area_id, None, radius, |entity| {
+        entity.actor.faction().is_hostile(Faction::Friendly)
+            && distance_finder::dist(entity, &point) <= radius
+    });
+}
+
+fn hear_noise<F>(area_id: &str, source: Option<usize>, radius: f32, hears: F)
+where
+    F: Fn(&EntityState) -> bool,
+{
+    if radius <= 0.0 {
+        return;
+    }
+
+    let mgr = GameState::turn_manager();
+    let round = mgr.borrow().current_round();
+    let combat_active = mgr.borrow().is_combat_active();
+    for entity in mgr.borrow().entity_iter() {
+        // the source is already borrowed by the caller
+        if Some(entity.borrow().index()) == source || entity.borrow().is_party_member() {
+            continue;
+        }
+
+        let mut entity = entity.borrow_mut();
+        if !is_observer(&entity) || entity.is_ai_active() || entity.location.area_id != area_id {
+            continue;
+        }
+        if !hears(&entity) {
+            continue;
+        }
+
+        entity.awareness.update_round(round, combat_active);
+        entity.awareness.suspect();
+    }
+}
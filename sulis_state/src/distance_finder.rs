@@ -17,6 +17,7 @@
 use crate::{EntityState, PropState};
 use sulis_core::util::Point;
 use sulis_module::area::Transition;
+use sulis_module::Module;
 
 pub trait Locatable {
     fn size(&self) -> (f32, f32);
@@ -110,9 +111,26 @@ pub fn is_within(parent: &impl Locatable, target: &impl Locatable, max_dist: f32
     dist(parent, target) <= max_dist
 }
 
-pub fn is_within_attack_dist<T: Locatable>(parent: &EntityState, target: &T) -> bool {
+/// The elevation of `parent` above `target`.  This is negative if `parent`
+/// is below `target`
+pub fn elevation_difference(parent: &EntityState, target: &EntityState) -> i32 {
+    parent.elevation() as i32 - target.elevation() as i32
+}
+
+/// The distance at which `parent` can attack `target`.  Ranged attacks
+/// reach further from higher ground and less far from lower ground
+pub fn attack_distance(parent: &EntityState, target: &EntityState) -> f32 {
     let dist = parent.actor.stats.attack_distance();
-    is_within(parent, target, dist)
+    if parent.actor.stats.attack_is_melee() {
+        return dist;
+    }
+
+    let elevation = elevation_difference(parent, target);
+    (dist + Module::rules().elevation_range_bonus(elevation)).max(0.0)
+}
+
+pub fn is_within_attack_dist(parent: &EntityState, target: &EntityState) -> bool {
+    is_within(parent, target, attack_distance(parent, target))
 }
 
 pub fn is_within_touch_dist<T: Locatable>(parent: &EntityState, target: &T) -> bool {
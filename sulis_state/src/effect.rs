@@ -15,14 +15,15 @@
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
 use std::cell::RefCell;
+use std::cmp;
 use std::collections::HashMap;
 use std::io::Error;
 use std::rc::Rc;
 
 use crate::script::{script_callback::FuncKind, CallbackData};
-use crate::{save_state::EffectSaveState, ChangeListenerList, EntityState};
+use crate::{save_state::EffectSaveState, ChangeListenerList, EntityState, TurnManager};
 use sulis_core::util::{invalid_data_error, ExtInt, Point};
-use sulis_module::{BonusList, ROUND_TIME_MILLIS};
+use sulis_module::{BonusList, ConditionStacking, ROUND_TIME_MILLIS};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
@@ -42,6 +43,78 @@ pub struct Icon {
     pub text: String,
 }
 
+/// Enforces the `stacking` policy of a new effect with the given `duration` against
+/// the effects already applied to `entity` for which `same_kind` returns true.
+/// Existing effects are refreshed or marked for removal as the policy requires.
+/// Returns true if the new effect should be added, false if it should be discarded.
+pub(crate) fn apply_stacking<F: Fn(&Effect) -> bool>(
+    mgr: &mut TurnManager,
+    entity: &EntityState,
+    stacking: ConditionStacking,
+    duration: ExtInt,
+    same_kind: F,
+) -> bool {
+    if stacking == ConditionStacking::Stack {
+        return true;
+    }
+
+    let mut existing: Vec<usize> = entity
+        .actor
+        .effects_iter()
+        .filter(|index| match mgr.effect_checked(**index) {
+            None => false,
+            Some(effect) => !effect.is_marked_for_removal() && same_kind(effect),
+        })
+        .copied()
+        .collect();
+
+    if existing.is_empty() {
+        return true;
+    }
+
+    // effect indices are assigned in increasing order, so this is oldest first
+    existing.sort_unstable();
+
+    match stacking {
+        ConditionStacking::Stack => true,
+        ConditionStacking::StackUpTo(max) => {
+            let max = cmp::max(1, max) as usize;
+            let num_to_remove = (existing.len() + 1).saturating_sub(max);
+            for index in existing.into_iter().take(num_to_remove) {
+                mgr.effect_mut(index).mark_for_removal();
+            }
+            true
+        }
+        ConditionStacking::Refresh => {
+            for index in existing {
+                mgr.effect_mut(index).refresh_duration(duration);
+            }
+            false
+        }
+        ConditionStacking::Replace => {
+            for index in existing {
+                mgr.effect_mut(index).mark_for_removal();
+            }
+            true
+        }
+        ConditionStacking::HighestWins(strength) => {
+            let stronger = existing.iter().any(|index| match mgr.effect(*index).stacking {
+                ConditionStacking::HighestWins(other) => other > strength,
+                _ => false,
+            });
+
+            if stronger {
+                return false;
+            }
+
+            for index in existing {
+                mgr.effect_mut(index).mark_for_removal();
+            }
+            true
+        }
+    }
+}
+
 pub struct Effect {
     pub name: String,
     pub tag: String,
@@ -56,6 +129,8 @@ pub struct Effect {
     pub(crate) entity: Option<usize>,
     pub(crate) callbacks: Vec<Rc<CallbackData>>,
     pub(crate) icon: Option<Icon>,
+    pub(crate) condition: Option<String>,
+    pub(crate) stacking: ConditionStacking,
 
     squares_moved: HashMap<usize, u32>,
 
@@ -108,6 +183,8 @@ impl Effect {
             surface,
             entity: data.entity,
             icon: data.icon,
+            condition: data.condition,
+            stacking: data.stacking,
 
             squares_moved: HashMap::new(),
             callbacks,
@@ -137,6 +214,8 @@ impl Effect {
             surface: None,
             entity: None,
             icon: None,
+            condition: None,
+            stacking: ConditionStacking::Stack,
             squares_moved: HashMap::new(),
         }
     }
@@ -183,6 +262,32 @@ impl Effect {
         self.icon = Some(Icon { icon, text });
     }
 
+    /// Returns the ID of the `Condition` this effect applies, if any
+    pub fn condition(&self) -> Option<&str> {
+        self.condition.as_deref()
+    }
+
+    pub fn set_condition(&mut self, id: String) {
+        self.condition = Some(id);
+    }
+
+    pub fn stacking(&self) -> ConditionStacking {
+        self.stacking
+    }
+
+    pub fn set_stacking(&mut self, stacking: ConditionStacking) {
+        self.stacking = stacking;
+    }
+
+    /// Extends this effect, if needed, so that at least `duration` millis of
+    /// it remain.  Effects are never shortened by this method.
+    pub fn refresh_duration(&mut self, duration: ExtInt) {
+        let remaining = self.total_duration - self.cur_duration;
+        if duration > remaining {
+            self.total_duration = duration + self.cur_duration;
+        }
+    }
+
     pub fn set_owning_entity(&mut self, entity: usize) {
         self.entity = Some(entity);
     }
@@ -254,6 +359,15 @@ impl Effect {
         Vec::new()
     }
 
+    /// Returns true if this effect has been marked for removal or has otherwise
+    /// reached the end of its duration, but has not yet been removed
+    pub fn is_marked_for_removal(&self) -> bool {
+        match self.total_duration {
+            ExtInt::Infinity => false,
+            ExtInt::Int(total_duration) => self.cur_duration >= total_duration,
+        }
+    }
+
     pub fn is_removal(&self) -> bool {
         match self.total_duration {
             ExtInt::Infinity => false,
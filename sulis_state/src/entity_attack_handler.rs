@@ -18,15 +18,92 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use sulis_core::io::Audio;
-use crate::{center, is_threat, ActorState, EntityState, GameState};
-use sulis_module::{AccuracyKind, Attack, AttackKind, DamageKind, HitFlags, HitKind, Module,
-    OnTrigger};
+use crate::{
+    center, elevation_difference, is_threat, ActorState, CombatLogEntry, EntityState, GameState,
+};
+use sulis_module::{AccuracyKind, Attack, AttackKind, Cover, DamageKind, HitChances, HitFlags,
+    HitKind, Module, OnTrigger, StatList};
+
+/// The predicted outcome of one weapon attack against a target, computed
+/// without rolling any random numbers.  Damage ranges are the minimum and
+/// maximum total damage for each kind of hit.
+#[derive(Debug, Clone, Copy)]
+pub struct AttackPrediction {
+    pub chances: HitChances,
+    pub flanking: bool,
+    pub sneak_attack: bool,
+    pub cover: Cover,
+    pub elevation: i32,
+    pub concealment: i32,
+    pub graze_damage: (u32, u32),
+    pub hit_damage: (u32, u32),
+    pub crit_damage: (u32, u32),
+}
+
+/// The circumstances of an attack that depend on the positions and states
+/// of the attacker and target, computed once for all of an attacker's attacks
+#[derive(Clone, Copy)]
+struct Situation {
+    flanking: bool,
+    sneak_attack: bool,
+    cover: Cover,
+    elevation: i32,
+}
+
+impl Situation {
+    /// The situation for `parent`'s weapon attacks, which are traced from
+    /// its center and so may be blocked by cover
+    fn weapon(parent: &EntityState, target: &EntityState) -> Situation {
+        Situation::new(parent, target, cover(parent, target))
+    }
+
+    /// The situation for a scripted attack.  Scripts such as area spells
+    /// do not originate from the attacker's position, so cover is not used.
+    fn scripted(parent: &EntityState, target: &EntityState) -> Situation {
+        Situation::new(parent, target, Cover::None)
+    }
+
+    fn new(parent: &EntityState, target: &EntityState, cover: Cover) -> Situation {
+        Situation {
+            flanking: is_flanking(parent, target),
+            sneak_attack: is_sneak_attack(parent, target),
+            cover,
+            elevation: elevation_difference(parent, target),
+        }
+    }
+
+    /// Adds `parent_stats` bonuses that are contingent on this situation to
+    /// the specified weapon attack
+    fn weapon_attack(&self, attack: &Attack, parent_stats: &StatList) -> Attack {
+        let mut attack = if self.flanking {
+            Attack::from(attack, &parent_stats.flanking_bonuses)
+        } else {
+            attack.clone()
+        };
+
+        if self.elevation > 0 {
+            attack = Attack::from(&attack, &parent_stats.higher_ground_bonuses);
+        }
+
+        attack
+    }
+}
 
 fn is_sneak_attack(parent: &EntityState, target: &EntityState) -> bool {
     parent.actor.stats.hidden && !target.actor.stats.sneak_attack_immunity
 }
 
 fn is_flanking(parent: &EntityState, target: &EntityState) -> bool {
+    is_flanking_from(parent, center(parent), target)
+}
+
+/// Whether `parent` would be flanking `target` if its center were at
+/// `p_parent`, along with some other threatening entity
+pub(crate) fn is_flanking_from(
+    parent: &EntityState,
+    p_parent: (f32, f32),
+    target: &EntityState,
+) -> bool {
     if target.actor.stats.flanked_immunity {
         return false;
     }
@@ -47,7 +124,6 @@ fn is_flanking(parent: &EntityState, target: &EntityState) -> bool {
         }
 
         let p_target = center(target);
-        let p_parent = center(parent);
         let p_other = center(&*entity);
 
         let p1 = (p_target.0 - p_parent.0, p_target.1 - p_parent.1);
@@ -76,6 +152,128 @@ fn is_flanking(parent: &EntityState, target: &EntityState) -> bool {
     false
 }
 
+fn cover(parent: &EntityState, target: &EntityState) -> Cover {
+    if parent.location.area_id != target.location.area_id {
+        return Cover::None;
+    }
+
+    match GameState::get_area_state(&parent.location.area_id) {
+        None => Cover::None,
+        Some(area) => area.borrow().cover(parent, target),
+    }
+}
+
+/// Whether cover grants its defense bonus and concealment against `kind`
+fn cover_applies(kind: &AttackKind) -> bool {
+    matches!(
+        kind,
+        AttackKind::Reflex { .. } | AttackKind::Melee { .. } | AttackKind::Ranged { .. }
+    )
+}
+
+fn concealment(parent: &EntityState, target: &EntityState, attack: &Attack, cover: Cover) -> i32 {
+    let mut concealment = target.actor.stats.concealment;
+    if cover_applies(&attack.kind) {
+        concealment += Module::rules().cover_concealment(cover);
+    }
+    std::cmp::max(0, concealment - parent.actor.stats.concealment_ignore)
+}
+
+fn accuracy_vs_defense(
+    attack: &Attack,
+    target_stats: &StatList,
+    cover: Cover,
+) -> Option<(AccuracyKind, i32)> {
+    let cover_bonus = if cover_applies(&attack.kind) {
+        Module::rules().cover_defense_bonus(cover)
+    } else {
+        0
+    };
+    match attack.kind {
+        AttackKind::Fortitude { accuracy } => Some((accuracy, target_stats.fortitude)),
+        AttackKind::Reflex { accuracy } => Some((accuracy, target_stats.reflex + cover_bonus)),
+        AttackKind::Will { accuracy } => Some((accuracy, target_stats.will)),
+        AttackKind::Melee { .. } => {
+            Some((AccuracyKind::Melee, target_stats.defense + cover_bonus))
+        }
+        AttackKind::Ranged { .. } => {
+            Some((AccuracyKind::Ranged, target_stats.defense + cover_bonus))
+        }
+        AttackKind::Dummy => None,
+    }
+}
+
+fn add_situational_bonuses(attack: &mut Attack, situation: &Situation) {
+    let rules = Module::rules();
+    let mut bonus = if situation.flanking {
+        rules.flanking_accuracy_bonus
+    } else if situation.sneak_attack {
+        rules.hidden_accuracy_bonus
+    } else {
+        0
+    };
+    bonus += rules.elevation_accuracy_bonus(situation.elevation);
+
+    attack.bonuses.melee_accuracy += bonus;
+    attack.bonuses.ranged_accuracy += bonus;
+    attack.bonuses.spell_accuracy += bonus;
+
+    if situation.elevation > 0 {
+        let multiplier = rules.higher_ground_damage_multiplier;
+        attack.bonuses.graze_multiplier += multiplier;
+        attack.bonuses.hit_multiplier += multiplier;
+        attack.bonuses.crit_multiplier += multiplier;
+    }
+}
+
+/// Predicts the outcome of each of `parent`'s weapon attacks against
+/// `target`, including flanking, sneak attack, cover, elevation, and
+/// concealment, as they would be rolled by `weapon_attack` from the current
+/// positions.  This has no side effects.
+pub fn predict_weapon_attack(parent: &EntityState, target: &EntityState) -> Vec<AttackPrediction> {
+    let rules = Module::rules();
+    let situation = Situation::weapon(parent, target);
+    let cover = situation.cover;
+    let parent_stats = &parent.actor.stats;
+    let target_stats = &target.actor.stats;
+
+    let mut result = Vec::new();
+    for attack in parent_stats.attacks.iter() {
+        let mut attack = situation.weapon_attack(attack, parent_stats);
+        let concealment = concealment(parent, target, &attack, cover);
+
+        let (accuracy_kind, defense) = match accuracy_vs_defense(&attack, target_stats, cover) {
+            None => continue,
+            Some(result) => result,
+        };
+        add_situational_bonuses(&mut attack, &situation);
+
+        let chances = parent_stats
+            .hit_chances(accuracy_kind, target_stats.crit_immunity, defense, &attack.bonuses)
+            .with_concealment(concealment);
+
+        let bonuses = &attack.bonuses;
+        let damage = |multiplier| {
+            let (armor, resistance) = (&target_stats.armor, &target_stats.resistance);
+            rules.damage_range(&attack.damage, armor, resistance, multiplier)
+        };
+
+        result.push(AttackPrediction {
+            chances,
+            flanking: situation.flanking,
+            sneak_attack: situation.sneak_attack,
+            cover,
+            elevation: situation.elevation,
+            concealment,
+            graze_damage: damage(parent_stats.graze_multiplier + bonuses.graze_multiplier),
+            hit_damage: damage(parent_stats.hit_multiplier + bonuses.hit_multiplier),
+            crit_damage: damage(parent_stats.crit_multiplier + bonuses.crit_multiplier),
+        });
+    }
+
+    result
+}
+
 type AttackResult = Vec<(HitKind, HitFlags, Vec<(DamageKind, u32)>)>;
 
 pub fn weapon_attack(
@@ -94,20 +292,15 @@ pub fn weapon_attack(
 
     let attacks = parent.borrow().actor.stats.attacks.clone();
 
-    let is_flanking = is_flanking(&parent.borrow(), &target.borrow());
-    let is_sneak_attack = is_sneak_attack(&parent.borrow(), &target.borrow());
+    let situation = Situation::weapon(&parent.borrow(), &target.borrow());
 
     let mut had_crit = false;
     let mut result = Vec::new();
     for attack in attacks {
-        let mut attack = if is_flanking {
-            Attack::from(&attack, &parent.borrow().actor.stats.flanking_bonuses)
-        } else {
-            attack
-        };
+        let mut attack = situation.weapon_attack(&attack, &parent.borrow().actor.stats);
 
         let (hit_kind, hit_flags, damage) =
-            attack_internal(parent, target, &mut attack, is_flanking, is_sneak_attack);
+            attack_internal(parent, target, &mut attack, &situation);
 
         if hit_kind == HitKind::Crit {
             had_crit = true;
@@ -144,11 +337,9 @@ pub fn attack(
         target.borrow().actor.actor.name
     );
 
-    let is_flanking = is_flanking(&parent.borrow(), &target.borrow());
-    let is_sneak_attack = is_sneak_attack(&parent.borrow(), &target.borrow());
+    let situation = Situation::scripted(&parent.borrow(), &target.borrow());
 
-    let (hit_kind, hit_flags, damage) =
-        attack_internal(parent, target, attack, is_flanking, is_sneak_attack);
+    let (hit_kind, hit_flags, damage) = attack_internal(parent, target, attack, &situation);
 
     ActorState::check_death(parent, target);
 
@@ -159,17 +350,41 @@ fn attack_internal(
     parent: &Rc<RefCell<EntityState>>,
     target: &Rc<RefCell<EntityState>>,
     attack: &mut Attack,
-    flanking: bool,
-    sneak_attack: bool,
+    situation: &Situation,
+) -> (HitKind, HitFlags, Vec<(DamageKind, u32)>) {
+    let time = GameState::turn_manager().borrow().current_time();
+    let mut entry = CombatLogEntry::new(time, &parent.borrow(), &target.borrow());
+
+    let (hit_kind, hit_flags, damage) =
+        roll_attack(parent, target, attack, situation, &mut entry);
+
+    if let AttackKind::Dummy = attack.kind {
+        return (hit_kind, hit_flags, damage);
+    }
+
+    entry.hit_kind = hit_kind;
+    entry.hit_flags = hit_flags;
+    GameState::combat_log().borrow_mut().add(entry);
+
+    (hit_kind, hit_flags, damage)
+}
+
+fn roll_attack(
+    parent: &Rc<RefCell<EntityState>>,
+    target: &Rc<RefCell<EntityState>>,
+    attack: &mut Attack,
+    situation: &Situation,
+    entry: &mut CombatLogEntry,
 ) -> (HitKind, HitFlags, Vec<(DamageKind, u32)>) {
     let rules = Module::rules();
 
-    let concealment = std::cmp::max(
-        0,
-        target.borrow().actor.stats.concealment - parent.borrow().actor.stats.concealment_ignore,
-    );
+    let cover = situation.cover;
+    let concealment = concealment(&parent.borrow(), &target.borrow(), attack, cover);
+    entry.cover = cover;
+    entry.elevation = situation.elevation;
+    entry.concealment = concealment;
 
-    if !rules.concealment_roll(concealment) {
+    if !GameState::with_combat_rand(|rand| rules.concealment_roll(concealment, rand)) {
         debug!("Concealment miss");
         return (
             HitKind::Miss,
@@ -181,41 +396,34 @@ fn attack_internal(
         );
     }
 
-    let (accuracy_kind, defense) = {
-        let target_stats = &target.borrow().actor.stats;
-        match attack.kind {
-            AttackKind::Fortitude { accuracy } => (accuracy, target_stats.fortitude),
-            AttackKind::Reflex { accuracy } => (accuracy, target_stats.reflex),
-            AttackKind::Will { accuracy } => (accuracy, target_stats.will),
-            AttackKind::Melee { .. } => (AccuracyKind::Melee, target_stats.defense),
-            AttackKind::Ranged { .. } => (AccuracyKind::Ranged, target_stats.defense),
-            AttackKind::Dummy => {
-                return (HitKind::Hit, HitFlags::default(), Vec::new());
-            }
-        }
+    let defense = accuracy_vs_defense(attack, &target.borrow().actor.stats, cover);
+    let (accuracy_kind, defense) = match defense {
+        None => return (HitKind::Hit, HitFlags::default(), Vec::new()),
+        Some(result) => result,
     };
     let crit_immunity = target.borrow().actor.stats.crit_immunity;
 
-    if flanking {
-        attack.bonuses.melee_accuracy += rules.flanking_accuracy_bonus;
-        attack.bonuses.ranged_accuracy += rules.flanking_accuracy_bonus;
-        attack.bonuses.spell_accuracy += rules.flanking_accuracy_bonus;
-    } else if sneak_attack {
-        attack.bonuses.melee_accuracy += rules.hidden_accuracy_bonus;
-        attack.bonuses.ranged_accuracy += rules.hidden_accuracy_bonus;
-        attack.bonuses.spell_accuracy += rules.hidden_accuracy_bonus;
-    }
+    add_situational_bonuses(attack, situation);
 
     let hit_flags = HitFlags {
-        flanking,
-        sneak_attack,
+        flanking: situation.flanking,
+        sneak_attack: situation.sneak_attack,
         concealment: false,
     };
 
     let (hit_kind, damage_multiplier) = {
         let parent_stats = &parent.borrow().actor.stats;
-        let hit_kind =
-            parent_stats.attack_roll(accuracy_kind, crit_immunity, defense, &attack.bonuses);
+        let roll = GameState::with_combat_rand(|rand| {
+            parent_stats.attack_roll_detail(
+                accuracy_kind,
+                crit_immunity,
+                defense,
+                &attack.bonuses,
+                rand,
+            )
+        });
+        entry.roll = Some(roll);
+        let hit_kind = roll.hit_kind;
         let damage_multiplier = match hit_kind {
             HitKind::Miss => {
                 debug!("Miss");
@@ -229,11 +437,20 @@ fn attack_internal(
         (hit_kind, damage_multiplier)
     };
 
-    let damage = {
+    entry.damage = {
         let target = &target.borrow().actor.stats;
         let damage = &attack.damage;
-        rules.roll_damage(damage, &target.armor, &target.resistance, damage_multiplier)
+        let (armor, resistance) = (&target.armor, &target.resistance);
+        GameState::with_combat_rand(|rand| {
+            rules.roll_damage_detail(damage, armor, resistance, damage_multiplier, rand)
+        })
     };
+    let damage: Vec<_> = entry
+        .damage
+        .iter()
+        .filter(|roll| roll.amount > 0)
+        .map(|roll| (roll.kind, roll.amount))
+        .collect();
 
     debug!("{:?}. {:?} damage", hit_kind, damage);
 
@@ -26,17 +26,19 @@ use sulis_core::config::Config;
 use crate::animation::{self, Anim};
 use crate::save_state::EntitySaveState;
 use crate::script::{self, CallbackData, ScriptEntitySet};
+use crate::detection::{Awareness, AwarenessState};
 use crate::{
     entity_attack_handler::weapon_attack, entity_texture_cache::Slot, is_within_attack_dist,
     ActorState, AreaState, ChangeListenerList, EntityTextureCache, EntityTextureSlot, GameState,
-    Location, ScriptCallback, TurnManager,
+    Location, ScheduleState, ScriptCallback, TurnManager,
 };
 use sulis_core::io::GraphicsRenderer;
 use sulis_core::ui::{color, Color};
 use sulis_core::util::{invalid_data_error, Offset, Scale, Size, Point};
 use sulis_module::area::MAX_AREA_SIZE;
 use sulis_module::{
-    actor::Faction, ai, Actor, DamageKind, HitKind, Module, ObjectSize, ObjectSizeIterator,
+    actor::Faction, ai, Actor, Condition, ConditionAIHint, DamageKind, HitKind, Module,
+    ObjectSize, ObjectSizeIterator,
 };
 
 enum AIState {
@@ -57,6 +59,12 @@ pub struct EntityState {
     ai_state: AIState,
     ai_callbacks: Option<Rc<CallbackData>>,
     pub(crate) marked_for_removal: bool,
+    // the elevation at the center of this entity, updated by the area
+    // whenever it is placed or moved
+    pub(crate) elevation: u8,
+    // the out of combat patrol and schedule for this entity, if it has one
+    pub(crate) schedule: Option<ScheduleState>,
+    pub(crate) awareness: AwarenessState,
     texture_cache_slot: Option<EntityTextureSlot>,
 
     custom_flags: HashMap<String, String>,
@@ -122,6 +130,9 @@ impl EntityState {
             listeners: ChangeListenerList::default(),
             ai_state,
             marked_for_removal: false,
+            elevation: 0,
+            schedule: save.schedule,
+            awareness: save.awareness,
             texture_cache_slot: None,
             custom_flags: save.custom_flags,
             collapsed_groups: save.collapsed_groups,
@@ -169,6 +180,9 @@ impl EntityState {
             unique_id,
             listeners: ChangeListenerList::default(),
             marked_for_removal: false,
+            elevation: 0,
+            schedule: None,
+            awareness: AwarenessState::default(),
             ai_state,
             texture_cache_slot: None,
             custom_flags: HashMap::new(),
@@ -196,6 +210,11 @@ impl EntityState {
         self.index
     }
 
+    /// Returns how aware this entity is of hidden hostiles around it
+    pub fn awareness(&self) -> Awareness {
+        self.awareness.awareness()
+    }
+
     pub fn set_index(&mut self, index: usize) {
         self.index = index;
         if let Some(ref ai) = self.actor.actor.ai {
@@ -249,6 +268,38 @@ impl EntityState {
         result
     }
 
+    /// Returns each distinct `Condition` currently applied to this entity by
+    /// its effects
+    pub fn conditions(&self, mgr: &TurnManager) -> Vec<Rc<Condition>> {
+        let mut result: Vec<Rc<Condition>> = Vec::new();
+        for index in self.actor.effects_iter() {
+            let id = match mgr.effect_checked(*index).and_then(|e| e.condition()) {
+                None => continue,
+                Some(id) => id,
+            };
+
+            if result.iter().any(|c| c.id == id) {
+                continue;
+            }
+
+            match Module::condition(id) {
+                None => warn!("Invalid condition '{}' on effect {}", id, index),
+                Some(condition) => result.push(condition),
+            }
+        }
+        result
+    }
+
+    /// Returns the most restrictive AI hint out of all conditions currently
+    /// applied to this entity
+    pub fn condition_ai_hint(&self, mgr: &TurnManager) -> ConditionAIHint {
+        self.conditions(mgr)
+            .iter()
+            .map(|c| c.ai_hint)
+            .max()
+            .unwrap_or_default()
+    }
+
     pub fn custom_flags(&self) -> impl Iterator<Item = (&String, &String)> {
         self.custom_flags.iter()
     }
@@ -548,6 +599,11 @@ impl EntityState {
         self.size.relative_points()
     }
 
+    /// The area elevation at the center of this entity
+    pub fn elevation(&self) -> u8 {
+        self.elevation
+    }
+
     pub fn location_points(&self) -> ObjectSizeIterator {
         self.size.points(self.location.x, self.location.y)
     }
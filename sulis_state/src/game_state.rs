@@ -21,7 +21,7 @@ use std::rc::Rc;
 
 use sulis_core::config::Config;
 use sulis_core::io::{GraphicsRenderer};
-use sulis_core::util::{invalid_data_error, ExtInt, Offset, Point, Scale};
+use sulis_core::util::{invalid_data_error, ExtInt, Offset, Point, ReproducibleRandom, Scale};
 use sulis_module::on_trigger::QuestEntryState;
 use sulis_module::{
     area::{Destination, PathFinder, Trigger, TriggerKind},
@@ -29,11 +29,13 @@ use sulis_module::{
 };
 
 use crate::animation::{particle_generator::Param, Anim, AnimSaveState, AnimState};
+use crate::reaction::ReadiedReaction;
 use crate::script::{script_cache, script_callback, Script, ScriptCallback, ScriptEntity};
 use crate::{
-    path_finder, transition_handler, AreaState, ChangeListener, ChangeListenerList, Effect,
-    EntityState, Formation, ItemList, Location, PartyStash, QuestStateSet, SaveState, TurnManager,
-    UICallback, WorldMapState, AI,
+    attack_distance, detection, hot_reload, path_finder, save_file, schedule_state,
+    transition_handler, AIDecisionTrace, AreaState, ChangeListener, ChangeListenerList, CombatLog,
+    Effect, EntityState, Formation, ItemList, Location, PartyStash, QuestStateSet, SaveState,
+    TurnManager, UICallback, WorldMapState, AI,
 };
 
 thread_local! {
@@ -42,9 +44,13 @@ thread_local! {
     static AI: RefCell<AI> = RefCell::new(AI::new());
     static CLEAR_ANIMS: Cell<bool> = Cell::new(false);
     static MODAL_LOCKED: Cell<bool> = Cell::new(false);
+    static PARTY_AI_ENABLED: Cell<bool> = Cell::new(false);
     static ANIMATIONS: RefCell<AnimState> = RefCell::new(AnimState::new());
     static ANIMS_TO_ADD: RefCell<Vec<Anim>> = RefCell::new(Vec::new());
     static COMBAT_INACTIVE_TIME: Cell<u32> = Cell::new(0);
+    static AUTOSAVE_ENABLED: Cell<bool> = const { Cell::new(true) };
+    static AUTOSAVE_REQUESTED: Cell<bool> = Cell::new(false);
+    static PENDING_AUTOSAVE: RefCell<Option<SaveState>> = const { RefCell::new(None) };
 }
 
 pub struct GameState {
@@ -58,12 +64,19 @@ pub struct GameState {
     party_formation: Rc<RefCell<Formation>>,
     party_coins: i32,
     party_stash: Rc<RefCell<PartyStash>>,
+    combat_log: Rc<RefCell<CombatLog>>,
+    ai_trace: Rc<RefCell<AIDecisionTrace>>,
 
     // listener returns the first selected party member
     party_listeners: ChangeListenerList<Option<Rc<RefCell<EntityState>>>>,
     party_death_listeners: ChangeListenerList<Vec<Rc<RefCell<EntityState>>>>,
     path_finder: PathFinder,
     ui_callbacks: Vec<UICallback>,
+
+    // random generator used for all combat rolls.  This is persisted in
+    // saves so that a reloaded game rolls the same results for the same
+    // actions, unless the campaign opts in to reseeding it on load
+    combat_rand: ReproducibleRandom,
 }
 
 const MAX_COMBAT_INACTIVE_TIME: u32 = 5000;
@@ -80,9 +93,12 @@ impl GameState {
         STATE.with(|state| *state.borrow_mut() = None);
         CLEAR_ANIMS.with(|c| c.set(false));
         MODAL_LOCKED.with(|c| c.set(false));
+        AUTOSAVE_REQUESTED.with(|c| c.set(false));
+        PENDING_AUTOSAVE.with(|s| *s.borrow_mut() = None);
         ANIMS_TO_ADD.with(|anims| anims.borrow_mut().clear());
         AI.with(|ai| *ai.borrow_mut() = AI::new());
         script_cache::setup().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        hot_reload::setup();
 
         let game_state: Result<GameState, Error> = {
             let mut areas = HashMap::new();
@@ -158,6 +174,23 @@ impl GameState {
                 mgr.borrow_mut().ai_groups.insert(index, value);
             }
 
+            for (key, value) in save_state.manager.ai_group_states {
+                let index = match key.parse::<usize>() {
+                    Ok(val) => val,
+                    Err(e) => {
+                        let err = Error::new(ErrorKind::InvalidInput, e);
+                        return Err(err);
+                    }
+                };
+
+                // the entity indices have changed with the load
+                let focus_target = value
+                    .focus_target
+                    .and_then(|target| entities.get(&target))
+                    .map(|target| target.borrow().index());
+                mgr.borrow_mut().load_ai_group_state(index, focus_target, value.retreating, &areas);
+            }
+
             for effect_save in save_state.manager.effects {
                 let old_index = effect_save.index;
                 let new_index = mgr.borrow().get_next_effect_index();
@@ -205,6 +238,38 @@ impl GameState {
                 }
             }
 
+            for reaction_save in save_state.manager.readied_reactions {
+                let entity = match entities.get(&reaction_save.index) {
+                    None => {
+                        return invalid_data_error(&format!(
+                            "Invalid readied reaction entity {}",
+                            reaction_save.index
+                        ));
+                    }
+                    Some(entity) => Rc::clone(entity),
+                };
+
+                let mut cb = reaction_save.cb;
+                cb.update_entity_refs_on_load(&entities)?;
+                if let Some(old_index) = cb.effect() {
+                    match effects.get(&old_index) {
+                        None => {
+                            warn!("Dropping readied reaction for removed effect {}", old_index);
+                            continue;
+                        }
+                        Some(new_index) => cb.update_effect_index_on_load(*new_index),
+                    }
+                }
+
+                let reaction = ReadiedReaction {
+                    trigger: reaction_save.trigger,
+                    ap: reaction_save.ap,
+                    cb: Rc::new(cb),
+                };
+                let index = entity.borrow().index();
+                mgr.borrow_mut().ready_reaction(index, reaction);
+            }
+
             let mut marked = HashMap::new();
             for anim in save_state.anims {
                 match anim.load(&entities, &effects, &mut marked) {
@@ -236,6 +301,9 @@ impl GameState {
             }
 
             let quests = QuestStateSet::load(save_state.quests);
+            let combat_rand = save_state
+                .combat_rand
+                .unwrap_or_else(|| ReproducibleRandom::new(None));
             let mut world_map = save_state.world_map;
             world_map.load();
 
@@ -253,11 +321,14 @@ impl GameState {
                 party_formation: Rc::new(RefCell::new(formation)),
                 party_coins,
                 party_stash: Rc::new(RefCell::new(PartyStash::new(stash))),
+                combat_log: Rc::new(RefCell::new(CombatLog::default())),
+                ai_trace: Rc::new(RefCell::new(AIDecisionTrace::default())),
                 party_listeners: ChangeListenerList::default(),
                 party_death_listeners: ChangeListenerList::default(),
                 ui_callbacks: Vec::new(),
                 world_map,
                 quests,
+                combat_rand,
             })
         };
 
@@ -293,6 +364,7 @@ impl GameState {
         });
 
         script_cache::setup().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        hot_reload::setup();
         let game_state = GameState::new(pc_actor, party_actors, flags)?;
         STATE.with(|state| {
             *state.borrow_mut() = Some(game_state);
@@ -423,11 +495,14 @@ impl GameState {
             party_formation: Rc::new(RefCell::new(Formation::default())),
             party_coins,
             party_stash: Rc::new(RefCell::new(PartyStash::new(party_stash))),
+            combat_log: Rc::new(RefCell::new(CombatLog::default())),
+            ai_trace: Rc::new(RefCell::new(AIDecisionTrace::default())),
             party_listeners: ChangeListenerList::default(),
             party_death_listeners: ChangeListenerList::default(),
             ui_callbacks: Vec::new(),
             world_map: WorldMapState::new(),
             quests: QuestStateSet::default(),
+            combat_rand: ReproducibleRandom::new(None),
         })
     }
 
@@ -903,6 +978,85 @@ impl GameState {
         MODAL_LOCKED.with(|c| c.set(locked))
     }
 
+    /// Returns true if party members take their combat turns using the AI
+    /// template of their actor, rather than waiting for player input
+    pub fn is_party_ai_enabled() -> bool {
+        PARTY_AI_ENABLED.with(|c| c.get())
+    }
+
+    /// Sets whether party members are driven by the AI during combat.  Party
+    /// members without an AI template will end their turn immediately.  This
+    /// is used when running the game without any player input.
+    pub fn set_party_ai_enabled(enabled: bool) {
+        PARTY_AI_ENABLED.with(|c| c.set(enabled))
+    }
+
+    /// Sets whether autosaves are written.  This is disabled when running the
+    /// game without player input, so that headless and replay runs do not
+    /// overwrite the player's own autosaves.
+    pub fn set_autosave_enabled(enabled: bool) {
+        AUTOSAVE_ENABLED.with(|c| c.set(enabled))
+    }
+
+    /// Requests that an autosave be written at the start of the next update,
+    /// once any changes to the game state in progress have completed
+    pub fn request_autosave() {
+        if !AUTOSAVE_ENABLED.with(|c| c.get()) {
+            return;
+        }
+
+        AUTOSAVE_REQUESTED.with(|c| c.set(true));
+    }
+
+    /// Captures an autosave of the game state immediately prior to combat
+    /// starting, before any AI is activated or initiative is rolled.  The
+    /// specified area must be the current area.  Animations may be mid
+    /// update at this point, so they are added and the save is written to
+    /// disk at the start of the next update.
+    pub(crate) fn autosave_before_combat(mgr: &TurnManager, area_state: &AreaState) {
+        if !GameState::needs_combat_autosave() {
+            return;
+        }
+
+        let state = SaveState::snapshot(mgr, area_state);
+        PENDING_AUTOSAVE.with(|s| *s.borrow_mut() = Some(state));
+    }
+
+    /// Captures an autosave as combat becomes active, if one was not already
+    /// captured before the AI was activated.  The current area must not be
+    /// mutably borrowed in that case.
+    pub(crate) fn autosave_on_combat_start(mgr: &TurnManager) {
+        if !GameState::needs_combat_autosave() {
+            return;
+        }
+
+        let area_state = GameState::area_state();
+        GameState::autosave_before_combat(mgr, &area_state.borrow());
+    }
+
+    fn needs_combat_autosave() -> bool {
+        AUTOSAVE_ENABLED.with(|c| c.get()) && PENDING_AUTOSAVE.with(|s| s.borrow().is_none())
+    }
+
+    fn check_autosave() {
+        let pending = PENDING_AUTOSAVE.with(|s| s.borrow_mut().take());
+        let requested = AUTOSAVE_REQUESTED.with(|c| c.replace(false));
+
+        let result = match pending {
+            Some(mut state) => {
+                state.anims = GameState::save_anims();
+                save_file::write_autosave(state)
+            }
+            None if requested => save_file::create_autosave(),
+            None => return,
+        };
+
+        if let Err(e) = result {
+            warn!("Error writing autosave");
+            warn!("{}", e);
+        }
+    }
+
     fn check_clear_anims() -> bool {
         CLEAR_ANIMS.with(|c| c.replace(false))
     }
@@ -933,6 +1087,8 @@ impl GameState {
 
     #[must_use]
     pub fn update(millis: u32) -> Option<UICallback> {
+        GameState::check_autosave();
+
         let ui_cb = STATE.with(|s| {
             let mut state = s.borrow_mut();
             let state = state.as_mut().unwrap();
@@ -960,12 +1116,18 @@ impl GameState {
         let cbs = mgr.borrow_mut().update_entity_move_callbacks();
         script_callback::fire_on_moved(cbs);
 
+        let reactions = mgr.borrow_mut().drain_reactions();
+        reactions.into_iter().for_each(|reaction| reaction.fire());
+
         {
             let area_state = GameState::area_state();
             let mut area_state = area_state.borrow_mut();
             area_state.update();
         }
 
+        schedule_state::update(millis);
+        detection::update();
+
         if GameState::check_clear_anims() {
             ANIMATIONS.with(|a| a.borrow_mut().clear_all_blocking_anims());
         }
@@ -1100,7 +1262,7 @@ impl GameState {
     }
 
     pub fn get_target_dest(entity: &EntityState, target: &EntityState) -> Destination {
-        let dist = entity.actor.stats.attack_distance();
+        let dist = attack_distance(entity, target);
         let x = target.location.x as f32;
         let y = target.location.y as f32;
         let w = target.size.width as f32;
@@ -1155,6 +1317,7 @@ impl GameState {
                 entities_to_ignore,
                 dest,
                 cb,
+                &state.ai_trace,
             )
         });
 
@@ -1211,6 +1374,36 @@ impl GameState {
         STATE.with(|s| Rc::clone(&s.borrow().as_ref().unwrap().party_stash))
     }
 
+    pub fn combat_log() -> Rc<RefCell<CombatLog>> {
+        STATE.with(|s| Rc::clone(&s.borrow().as_ref().unwrap().combat_log))
+    }
+
+    /// The decisions made during the most recent AI turn.  See `AIDecisionTrace`
+    pub fn ai_trace() -> Rc<RefCell<AIDecisionTrace>> {
+        STATE.with(|s| Rc::clone(&s.borrow().as_ref().unwrap().ai_trace))
+    }
+
+    /// Calls the specified function with the random generator used for
+    /// all combat rolls - attack, concealment, and damage.
+    pub fn with_combat_rand<T, F: FnOnce(&mut ReproducibleRandom) -> T>(f: F) -> T {
+        STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let state = state.as_mut().unwrap();
+            f(&mut state.combat_rand)
+        })
+    }
+
+    /// Returns a copy of the current combat random generator, including
+    /// its position in the sequence
+    pub fn combat_rand() -> ReproducibleRandom {
+        STATE.with(|s| s.borrow().as_ref().unwrap().combat_rand.clone())
+    }
+
+    /// Restarts the combat random generator with the specified seed
+    pub fn set_combat_seed(seed: u128) {
+        GameState::with_combat_rand(|rand| *rand = ReproducibleRandom::new(Some(seed)));
+    }
+
     pub fn party_coins() -> i32 {
         STATE.with(|s| s.borrow().as_ref().unwrap().party_coins)
     }
@@ -0,0 +1,167 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+//! Reloads the YAML resources while a game is in progress.  Only the items,
+//! abilities, and actors defined in changed files are rebuilt, and the ones
+//! referenced by the current game state are then replaced with their new
+//! versions, by ID.  Any resources that fail to build or cannot be found after
+//! the reload are left as they were.
+
+use std::cell::RefCell;
+use std::io::Error;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use sulis_core::config::Config;
+use sulis_core::resource::DirWatcher;
+use sulis_module::{Actor, ItemSaveState, ItemState, Module};
+
+use crate::{save_state, EntityState, GameState};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(1000);
+
+thread_local! {
+    static WATCHER: RefCell<Option<ResourceWatcher>> = RefCell::new(None);
+}
+
+struct ResourceWatcher {
+    dirs: DirWatcher,
+    last_poll: Instant,
+}
+
+/// Starts watching the resource directories for changes, if resource hot
+/// reloading is enabled in the config
+pub fn setup() {
+    WATCHER.with(|watcher| {
+        *watcher.borrow_mut() = if Config::debug().hot_reload_resources {
+            let dirs = Module::resource_dirs()
+                .into_iter()
+                .map(PathBuf::from)
+                .collect();
+            Some(ResourceWatcher {
+                dirs: DirWatcher::new(dirs, "yml"),
+                last_poll: Instant::now(),
+            })
+        } else {
+            None
+        };
+    });
+}
+
+/// If resource hot reloading is enabled, checks the resource directories for
+/// changed files and reloads the resources defined in them if there are any.
+/// Returns the number of reloaded resources along with the result of the
+/// reload, or `None` if nothing was changed.
+pub fn poll_for_changes() -> Option<Result<usize, Error>> {
+    let changed = WATCHER.with(|watcher| {
+        let mut watcher = watcher.borrow_mut();
+        let watcher = match watcher.as_mut() {
+            None => return Vec::new(),
+            Some(watcher) => watcher,
+        };
+
+        if watcher.last_poll.elapsed() < POLL_INTERVAL {
+            return Vec::new();
+        }
+        watcher.last_poll = Instant::now();
+
+        watcher.dirs.changed_files()
+    });
+
+    if changed.is_empty() {
+        return None;
+    }
+
+    info!("{} resource file(s) changed, reloading resources", changed.len());
+    Some(reload_resources(&changed).map(|reloaded| reloaded.len()))
+}
+
+/// Rebuilds the items, abilities, and actors defined in the specified `files`,
+/// and then updates the current game state to use the new versions.  Returns
+/// the IDs of the resources that were reloaded.
+pub fn reload_resources(files: &[PathBuf]) -> Result<Vec<String>, Error> {
+    let reloaded = Module::reload_resources(files)?;
+    if reloaded.is_empty() {
+        return Ok(reloaded);
+    }
+    info!("Reloaded resources {:?}", reloaded);
+
+    let entities: Vec<_> = GameState::turn_manager().borrow().entity_iter().collect();
+    for entity in entities {
+        let mut entity = entity.borrow_mut();
+        let actor = reload_actor(&entity);
+        entity.actor.reload_resources(actor);
+    }
+
+    GameState::party_stash().borrow_mut().reload_resources();
+
+    for id in GameState::area_state_ids() {
+        let area_state = match GameState::get_area_state(&id) {
+            None => continue,
+            Some(area_state) => area_state,
+        };
+        let mut area_state = area_state.borrow_mut();
+
+        for merchant in area_state.merchants.iter_mut() {
+            merchant.reload_resources();
+        }
+
+        let props = area_state.props_mut();
+        for index in 0..props.len() {
+            if props.index_valid(index) {
+                props.get_mut(index).reload_resources();
+            }
+        }
+    }
+
+    Ok(reloaded)
+}
+
+// Party members are saved with their full actor definition, so are rebuilt
+// from that rather than looked up in the module
+fn reload_actor(entity: &EntityState) -> Option<Rc<Actor>> {
+    let actor = &entity.actor.actor;
+    if !entity.is_party_member() {
+        let new_actor = Module::actor(&actor.id);
+        if new_actor.is_none() {
+            warn!("Unable to reload actor '{}'", actor.id);
+        }
+        return new_actor;
+    }
+
+    match Module::load_actor(save_state::actor_builder(actor)) {
+        Ok(actor) => Some(Rc::new(actor)),
+        Err(e) => {
+            warn!("Unable to reload party member '{}'", actor.id);
+            warn!("{}", e);
+            None
+        }
+    }
+}
+
+/// Returns the version of the specified item currently loaded in the module,
+/// or a copy of the item if it no longer exists
+pub(crate) fn reload_item(item: &ItemState) -> ItemState {
+    let save = ItemSaveState::new(item);
+    match Module::create_get_item(&save.id, &save.adjectives) {
+        Some(new_item) => ItemState::new(new_item, save.variant),
+        None => {
+            warn!("Unable to reload item '{}'", save.id);
+            item.clone()
+        }
+    }
+}
@@ -26,6 +26,8 @@ use sulis_module::{
     QuickSlot, Slot, StatList, WeaponStyle,
 };
 
+use crate::hot_reload;
+
 #[derive(Clone)]
 pub struct Inventory {
     pub equipped: HashMap<Slot, ItemState>,
@@ -40,6 +42,14 @@ impl Inventory {
         }
     }
 
+    /// Replaces each equipped and quick item with the version currently
+    /// loaded in the module
+    pub(crate) fn reload_resources(&mut self) {
+        for item in self.equipped.values_mut().chain(self.quick.values_mut()) {
+            *item = hot_reload::reload_item(item);
+        }
+    }
+
     pub fn load(
         &mut self,
         equipped: Vec<Option<ItemSaveState>>,
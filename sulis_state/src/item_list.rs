@@ -19,6 +19,8 @@ use std::slice::Iter;
 
 use sulis_module::ItemState;
 
+use crate::hot_reload;
+
 #[derive(Clone, Debug)]
 pub struct ItemList {
     items: Vec<(u32, ItemState)>,
@@ -46,6 +48,13 @@ impl ItemList {
         self.items.iter()
     }
 
+    /// Replaces each item with the version currently loaded in the module
+    pub(crate) fn reload_resources(&mut self) {
+        for (_, item) in self.items.iter_mut() {
+            *item = hot_reload::reload_item(item);
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.items.len() == 0
     }
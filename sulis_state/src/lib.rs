@@ -22,6 +22,12 @@ extern crate serde_derive;
 mod ai;
 pub use self::ai::AI;
 
+mod ai_group;
+pub use self::ai_group::AIGroupState;
+
+pub mod ai_trace;
+pub use self::ai_trace::{AIDecision, AIDecisionTrace};
+
 pub mod ability_state;
 pub use self::ability_state::AbilityState;
 
@@ -40,16 +46,23 @@ mod change_listener;
 pub use self::change_listener::ChangeListener;
 pub use self::change_listener::ChangeListenerList;
 
+pub mod combat_log;
+pub use self::combat_log::{CombatLog, CombatLogEntry};
+
+pub mod detection;
+pub use self::detection::Awareness;
+
 mod distance_finder;
 pub use self::distance_finder::{
-    can_attack, center, center_i32, dist, is_threat, is_within, is_within_attack_dist,
-    is_within_touch_dist, Locatable,
+    attack_distance, can_attack, center, center_i32, dist, elevation_difference, is_threat,
+    is_within, is_within_attack_dist, is_within_touch_dist, Locatable,
 };
 
 mod effect;
 pub use self::effect::Effect;
 
 mod entity_attack_handler;
+pub use self::entity_attack_handler::{predict_weapon_attack, AttackPrediction};
 
 mod entity_state;
 pub use self::entity_state::AreaDrawable;
@@ -68,6 +81,8 @@ pub use self::game_state::GameState;
 mod generated_area;
 pub use self::generated_area::{GeneratedArea, PregenOutput};
 
+pub mod hot_reload;
+
 pub mod inventory;
 pub use self::inventory::Inventory;
 
@@ -78,6 +93,7 @@ mod location;
 pub use self::location::Location;
 
 mod los_calculator;
+pub use self::los_calculator::calculate_cover;
 pub use self::los_calculator::calculate_los;
 pub use self::los_calculator::has_visibility;
 
@@ -104,13 +120,24 @@ pub use self::quest_state::QuestStateSet;
 mod range_indicator;
 pub use self::range_indicator::{RangeIndicator, RangeIndicatorHandler, RangeIndicatorImageSet};
 
+pub mod reaction;
+pub use self::reaction::{Reaction, ReactionTrigger};
+
+pub mod replay;
+pub use self::replay::Replay;
+
 pub mod save_file;
 pub use self::save_file::SaveFile;
 pub use self::save_file::SaveFileMetaData;
 
+pub mod save_migration;
+
 mod save_state;
 pub use self::save_state::SaveState;
 
+mod schedule_state;
+pub use self::schedule_state::ScheduleState;
+
 pub mod script;
 pub use self::script::{Script, ScriptCallback, ScriptState};
 
@@ -17,6 +17,8 @@
 use std::cmp;
 use std::collections::HashSet;
 
+use sulis_module::Cover;
+
 use crate::{EntityState, GeneratedArea};
 
 #[must_use]
@@ -106,85 +108,80 @@ fn check_vis(
     let dist_squared =
         (start_x - end_x) * (start_x - end_x) + (start_y - end_y) * (start_y - end_y);
 
-    if dist_squared < area.area.vis_dist_up_one_squared {
-        cast_ray(
-            area,
-            prop_vis_grid,
-            start_x,
-            start_y,
-            end_x,
-            end_y,
-            src_elev + 1,
-        )
+    let src_elev = if dist_squared < area.area.vis_dist_up_one_squared {
+        src_elev + 1
     } else if dist_squared < area.area.vis_dist_squared {
-        cast_ray(
-            area,
-            prop_vis_grid,
-            start_x,
-            start_y,
-            end_x,
-            end_y,
-            src_elev,
-        )
+        src_elev
     } else {
-        false
-    }
+        return false;
+    };
+
+    cast_ray(start_x, start_y, end_x, end_y, &|x, y| {
+        check(area, prop_vis_grid, x, y, src_elev)
+    })
 }
 
-#[allow(clippy::collapsible_if)]
-fn cast_ray(
+/// Computes the cover `target` has against attacks from `entity`.  A line
+/// is traced from the center of `entity` to each point of `target`.  Lines
+/// that are blocked by walls or props, or that cross an impassable
+/// prop, are obstructed.  If no lines are obstructed, there is no cover.
+/// If all lines are obstructed, the cover is full.  Otherwise, it is partial.
+pub fn calculate_cover(
     area: &GeneratedArea,
     prop_vis_grid: &[bool],
+    prop_pass_grid: &[bool],
+    entity: &EntityState,
+    target: &EntityState,
+) -> Cover {
+    let start_x = entity.location.x + entity.size.width / 2;
+    let start_y = entity.location.y + entity.size.height / 2;
+    let src_elev = area.layer_set.elevation(start_x, start_y);
+
+    let mut clear = 0;
+    let mut total = 0;
+    for p in target.location_points() {
+        total += 1;
+        if !check_vis(area, prop_vis_grid, start_x, start_y, p.x, p.y, src_elev) {
+            continue;
+        }
+
+        let width = area.width;
+        if cast_ray(start_x, start_y, p.x, p.y, &|x, y| {
+            prop_pass_grid[(x + y * width) as usize]
+        }) {
+            clear += 1;
+        }
+    }
+
+    if clear == total {
+        Cover::None
+    } else if clear == 0 {
+        Cover::Full
+    } else {
+        Cover::Partial
+    }
+}
+
+#[allow(clippy::collapsible_if)]
+fn cast_ray<F: Fn(i32, i32) -> bool>(
     start_x: i32,
     start_y: i32,
     end_x: i32,
     end_y: i32,
-    src_elev: u8,
+    check: &F,
 ) -> bool {
     #[allow(clippy::collapsible_else_if)] // this block is logically easier to read when not collapsed
     if (end_y - start_y).abs() < (end_x - start_x).abs() {
         if start_x > end_x {
-            cast_low(
-                area,
-                prop_vis_grid,
-                end_x,
-                end_y,
-                start_x,
-                start_y,
-                src_elev,
-            )
+            cast_low(end_x, end_y, start_x, start_y, check)
         } else {
-            cast_low(
-                area,
-                prop_vis_grid,
-                start_x,
-                start_y,
-                end_x,
-                end_y,
-                src_elev,
-            )
+            cast_low(start_x, start_y, end_x, end_y, check)
         }
     } else {
         if start_y > end_y {
-            cast_high(
-                area,
-                prop_vis_grid,
-                end_x,
-                end_y,
-                start_x,
-                start_y,
-                src_elev,
-            )
+            cast_high(end_x, end_y, start_x, start_y, check)
         } else {
-            cast_high(
-                area,
-                prop_vis_grid,
-                start_x,
-                start_y,
-                end_x,
-                end_y,
-                src_elev,
-            )
+            cast_high(start_x, start_y, end_x, end_y, check)
         }
     }
 }
@@ -197,14 +194,12 @@ fn check(area: &GeneratedArea, prop_vis_grid: &[bool], x: i32, y: i32, src_elev:
         && area.layer_set.elevation_index(index) <= src_elev
 }
 
-fn cast_high(
-    area: &GeneratedArea,
-    prop_vis_grid: &[bool],
+fn cast_high<F: Fn(i32, i32) -> bool>(
     start_x: i32,
     start_y: i32,
     end_x: i32,
     end_y: i32,
-    src_elev: u8,
+    check: &F,
 ) -> bool {
     let mut delta_x = end_x - start_x;
     let delta_y = end_y - start_y;
@@ -223,7 +218,7 @@ fn cast_high(
     for y in start_y..end_y {
         if first {
             first = false;
-        } else if !check(area, prop_vis_grid, x, y, src_elev) {
+        } else if !check(x, y) {
             return false;
         }
 
@@ -237,14 +232,12 @@ fn cast_high(
     true
 }
 
-fn cast_low(
-    area: &GeneratedArea,
-    prop_vis_grid: &[bool],
+fn cast_low<F: Fn(i32, i32) -> bool>(
     start_x: i32,
     start_y: i32,
     end_x: i32,
     end_y: i32,
-    src_elev: u8,
+    check: &F,
 ) -> bool {
     let delta_x = end_x - start_x;
     let mut delta_y = end_y - start_y;
@@ -263,7 +256,7 @@ fn cast_low(
     for x in start_x..end_x {
         if first {
             first = false;
-        } else if !check(area, prop_vis_grid, x, y, src_elev) {
+        } else if !check(x, y) {
             return false;
         }
 
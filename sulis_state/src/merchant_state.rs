@@ -154,6 +154,11 @@ impl MerchantState {
         result
     }
 
+    pub(crate) fn reload_resources(&mut self) {
+        self.items.reload_resources();
+        self.listeners.notify(self);
+    }
+
     pub fn items(&self) -> &ItemList {
         &self.items
     }
@@ -40,6 +40,11 @@ impl PartyStash {
             .collect()
     }
 
+    pub(crate) fn reload_resources(&mut self) {
+        self.items.reload_resources();
+        self.listeners.notify(self);
+    }
+
     pub fn items(&self) -> &ItemList {
         &self.items
     }
@@ -17,7 +17,8 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use crate::{animation, animation::Anim, script::ScriptCallback, AreaState, EntityState};
+use crate::{animation, animation::Anim, script::ScriptCallback};
+use crate::{AIDecisionTrace, AreaState, EntityState};
 use sulis_core::{
     config::Config,
     util::{self, Point},
@@ -123,6 +124,7 @@ pub fn move_towards_point(
     entities_to_ignore: &[usize],
     dest: Destination,
     cb: Option<Box<dyn ScriptCallback>>,
+    ai_trace: &RefCell<AIDecisionTrace>,
 ) -> Option<Anim> {
     let path = match find_path(
         finder,
@@ -136,6 +138,8 @@ pub fn move_towards_point(
         Some(path) => path,
     };
 
+    ai_trace.borrow_mut().add_path(&entity.borrow(), &path);
+
     let mut anim =
         animation::move_animation::new(entity, path, Config::animation_base_time_millis());
     if let Some(cb) = cb {
@@ -355,6 +355,13 @@ impl PropState {
         self.listeners.notify(self);
     }
 
+    pub(crate) fn reload_resources(&mut self) {
+        if let Interactive::Container { ref mut items, .. } = self.interactive {
+            items.reload_resources();
+            self.listeners.notify(self);
+        }
+    }
+
     pub fn items(&self) -> Option<&ItemList> {
         match self.interactive {
             Interactive::Container { ref items, .. } => Some(items),
@@ -0,0 +1,265 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::script::{CallbackData, ScriptCallback};
+use crate::{area_feedback_text::ColorKind, is_threat, AreaFeedbackText, EntityState, GameState};
+use sulis_module::{on_trigger::Kind, Module};
+
+/// The kinds of action by a hostile entity that a reaction may respond to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReactionTrigger {
+    /// The hostile moves out of a square threatened by the reacting entity.
+    /// When no reaction is readied, this provokes an attack of opportunity.
+    LeaveThreat,
+
+    /// The hostile moves into a square threatened by the reacting entity
+    EnterThreat,
+
+    /// The hostile moves a square while visible to the reacting entity
+    Move,
+
+    /// The hostile activates an ability while visible to the reacting entity
+    Ability,
+}
+
+impl ReactionTrigger {
+    pub fn option_from_str(val: &str) -> Option<ReactionTrigger> {
+        match val {
+            "LeaveThreat" => Some(ReactionTrigger::LeaveThreat),
+            "EnterThreat" => Some(ReactionTrigger::EnterThreat),
+            "Move" => Some(ReactionTrigger::Move),
+            "Ability" => Some(ReactionTrigger::Ability),
+            _ => None,
+        }
+    }
+}
+
+/// A reaction readied by an entity, usually from an ability script.  The
+/// reserved AP is removed from the entity when the reaction is readied.  If
+/// the reaction has not fired by the start of the entity's next turn, the
+/// AP is returned to it as overflow AP.
+#[derive(Clone)]
+pub struct ReadiedReaction {
+    pub trigger: ReactionTrigger,
+    pub ap: u32,
+    pub cb: Rc<CallbackData>,
+}
+
+#[derive(Clone)]
+pub enum ReactionKind {
+    /// A standard weapon attack against the triggering entity, paid for with
+    /// `reaction_ap` of the reacting entity's overflow AP
+    AttackOfOpportunity,
+
+    /// A readied reaction, which fires the `on_reaction` function of its callback
+    Readied(ReadiedReaction),
+}
+
+/// A reaction that has been triggered, and is waiting to be fired.  Party
+/// members controlled by the player are prompted before their reactions fire.
+#[derive(Clone)]
+pub struct Reaction {
+    pub reactor: Rc<RefCell<EntityState>>,
+    pub target: Rc<RefCell<EntityState>>,
+    pub trigger: ReactionTrigger,
+    pub kind: ReactionKind,
+}
+
+impl Reaction {
+    /// The name of this reaction, for display to the player
+    pub fn name(&self) -> String {
+        match &self.kind {
+            ReactionKind::AttackOfOpportunity => "Attack of Opportunity".to_string(),
+            ReactionKind::Readied(readied) => match readied.cb.kind() {
+                Kind::Ability(id) => match Module::ability(&id) {
+                    None => "Readied Action".to_string(),
+                    Some(ability) => ability.name.to_string(),
+                },
+                _ => "Readied Action".to_string(),
+            },
+        }
+    }
+
+    /// Whether the player should be asked before this reaction fires
+    pub fn needs_prompt(&self) -> bool {
+        self.reactor.borrow().is_party_member() && !GameState::is_party_ai_enabled()
+    }
+
+    /// Fires this reaction once the player has accepted it.  A reaction to a
+    /// move interrupts the rest of the move, which waited for the player.
+    pub fn accept(self) {
+        let moving = GameState::has_blocking_animations(&self.target);
+        if self.trigger != ReactionTrigger::Ability && moving {
+            let index = self.target.borrow().index();
+            GameState::turn_manager().borrow_mut().interrupt_move(index);
+        }
+
+        self.fire();
+    }
+
+    /// Fires this reaction, if both the reacting and triggering entities are
+    /// still valid.  `on_reaction` is called on each of the reacting entity's
+    /// callbacks, in addition to the callback of any readied reaction.
+    pub fn fire(self) {
+        {
+            let reactor = self.reactor.borrow();
+            let target = self.target.borrow();
+            if reactor.actor.is_dead() || reactor.is_marked_for_removal() {
+                return;
+            }
+            if target.actor.is_dead() || target.is_marked_for_removal() {
+                return;
+            }
+        }
+
+        let area = GameState::area_state();
+        let mut text = AreaFeedbackText::with_target(&self.reactor.borrow(), &area.borrow());
+        text.add_entry(self.name(), ColorKind::Info);
+        area.borrow_mut().add_feedback_text(text);
+
+        info!(
+            "'{}' reacts to '{}' with {}",
+            self.reactor.borrow().actor.actor.name,
+            self.target.borrow().actor.actor.name,
+            self.name()
+        );
+
+        let target = self.target.borrow().index();
+        match self.kind {
+            ReactionKind::AttackOfOpportunity => {
+                let ap = Module::rules().reaction_ap as i32;
+                self.reactor.borrow_mut().actor.change_overflow_ap(-ap);
+                EntityState::attack(&self.reactor, &self.target, None, false);
+            }
+            ReactionKind::Readied(readied) => readied.cb.on_reaction(target),
+        }
+
+        let mgr = GameState::turn_manager();
+        let cbs = self.reactor.borrow().callbacks(&mgr.borrow());
+        cbs.iter().for_each(|cb| cb.on_reaction(target));
+    }
+}
+
+/// Checks each of the `candidates` for a reaction to an action by `target`.
+/// Reactions of AI controlled entities fire on the next update, while the
+/// player is prompted for party members.  Returns true if any reaction will
+/// fire without a prompt, in which case the target's action should be
+/// interrupted.  Prompted reactions interrupt only once they are accepted.
+pub(crate) fn trigger(
+    target: &Rc<RefCell<EntityState>>,
+    candidates: Vec<(usize, ReactionTrigger)>,
+) -> bool {
+    if candidates.is_empty() {
+        return false;
+    }
+
+    let mgr = GameState::turn_manager();
+    let reactions = mgr.borrow_mut().find_reactions(target, candidates);
+
+    let mut interrupt = false;
+    for reaction in reactions {
+        if reaction.needs_prompt() {
+            mgr.borrow_mut().add_reaction_prompt(reaction);
+        } else {
+            mgr.borrow_mut().fire_reaction_next_update(reaction);
+            interrupt = true;
+        }
+    }
+
+    interrupt
+}
+
+fn readied_with_visibility(
+    target: &Rc<RefCell<EntityState>>,
+    trigger: ReactionTrigger,
+    candidates: &mut Vec<(usize, ReactionTrigger)>,
+) {
+    let mgr = GameState::turn_manager();
+    let area = GameState::area_state();
+    let target_index = target.borrow().index();
+
+    for index in mgr.borrow().readied_reactors(trigger) {
+        if index == target_index {
+            continue;
+        }
+
+        let reactor = match mgr.borrow().entity_checked(index) {
+            None => continue,
+            Some(reactor) => reactor,
+        };
+
+        if reactor.borrow().location.area_id != target.borrow().location.area_id {
+            continue;
+        }
+
+        if area.borrow().has_visibility(&reactor.borrow(), &target.borrow()) {
+            candidates.push((index, trigger));
+        }
+    }
+}
+
+/// Returns the indices of all entities in the same area currently
+/// threatening the specified entity
+pub(crate) fn threatened_by(entity: &Rc<RefCell<EntityState>>) -> Vec<usize> {
+    let mgr = GameState::turn_manager();
+    let mgr = mgr.borrow();
+    let area = match GameState::get_area_state(&entity.borrow().location.area_id) {
+        None => return Vec::new(),
+        Some(area) => area,
+    };
+    let area = area.borrow();
+
+    let entity = entity.borrow();
+    area.entity_iter()
+        .filter(|index| **index != entity.index())
+        .filter(|index| is_threat(&mgr.entity(**index).borrow(), &entity))
+        .copied()
+        .collect()
+}
+
+/// Checks for reactions to `mover` having moved a square.  `threatened_by`
+/// is the list of entities threatening the mover prior to the move.
+pub(crate) fn check_move(mover: &Rc<RefCell<EntityState>>, threatened_by: &[usize]) -> bool {
+    let now_threatened_by = self::threatened_by(mover);
+
+    let mut candidates = Vec::new();
+    for index in threatened_by {
+        if !now_threatened_by.contains(index) {
+            candidates.push((*index, ReactionTrigger::LeaveThreat));
+        }
+    }
+
+    for index in now_threatened_by.iter() {
+        if !threatened_by.contains(index) {
+            candidates.push((*index, ReactionTrigger::EnterThreat));
+        }
+    }
+
+    readied_with_visibility(mover, ReactionTrigger::Move, &mut candidates);
+
+    trigger(mover, candidates)
+}
+
+/// Checks for reactions to `parent` activating an ability
+pub(crate) fn check_ability(parent: &Rc<RefCell<EntityState>>) -> bool {
+    let mut candidates = Vec::new();
+    readied_with_visibility(parent, ReactionTrigger::Ability, &mut candidates);
+
+    trigger(parent, candidates)
+}
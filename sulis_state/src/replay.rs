@@ -0,0 +1,148 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::collections::VecDeque;
+use std::fs;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+
+use chrono::prelude::*;
+
+use crate::SaveState;
+use sulis_core::config::{self, Config};
+use sulis_core::io::InputAction;
+use sulis_core::resource::{read_single_resource_path, write_json_to_file};
+use sulis_core::util::ActiveResources;
+
+/// All of the input actions received during a single frame, along with the
+/// elapsed time that frame was updated with.  Only frames with input are
+/// recorded.  The frames without input before this one are stored in
+/// `skipped` as runs of `(count, millis)`, with `count` consecutive frames
+/// each updated with the same elapsed `millis`.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ReplayFrame {
+    pub skipped: Vec<(u32, u32)>,
+    pub millis: u32,
+    pub actions: Vec<InputAction>,
+}
+
+impl ReplayFrame {
+    /// Returns the elapsed millis for the next of the skipped frames before
+    /// this one, or None if there are none left
+    pub fn pop_skipped(&mut self) -> Option<u32> {
+        let (count, millis) = self.skipped.first_mut()?;
+        let millis = *millis;
+        *count -= 1;
+        if *count == 0 {
+            self.skipped.remove(0);
+        }
+        Some(millis)
+    }
+
+    /// The total number of frames played back by this frame, including the
+    /// skipped frames
+    pub fn frame_count(&self) -> u32 {
+        self.skipped.iter().map(|(count, _)| count).sum::<u32>() + 1
+    }
+}
+
+/// A recording of all player input from a starting game state.  Playing
+/// back the frames on top of the save state, with the same resources,
+/// recreates the recorded game, including combat rolls.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Replay {
+    pub resources: ActiveResources,
+    pub ui_size: (i32, i32),
+    pub save_state: SaveState,
+    pub frames: VecDeque<ReplayFrame>,
+
+    #[serde(skip)]
+    skipped: Vec<(u32, u32)>,
+}
+
+impl Replay {
+    /// Creates a new, empty replay starting from the current game state
+    pub fn create() -> Replay {
+        Replay {
+            resources: ActiveResources::read(),
+            ui_size: Config::ui_size(),
+            save_state: SaveState::create(),
+            frames: VecDeque::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Replay, Error> {
+        let replay: Replay = read_single_resource_path(path)?;
+
+        if replay.ui_size != Config::ui_size() {
+            warn!(
+                "Replay was recorded with UI size {:?} but the current size is {:?}.",
+                replay.ui_size,
+                Config::ui_size()
+            );
+            warn!("Mouse input will not be played back correctly.");
+        }
+
+        Ok(replay)
+    }
+
+    pub fn push_frame(&mut self, millis: u32, actions: Vec<InputAction>) {
+        if actions.is_empty() {
+            match self.skipped.last_mut() {
+                Some((count, last)) if *last == millis => *count += 1,
+                _ => self.skipped.push((1, millis)),
+            }
+            return;
+        }
+
+        self.frames.push_back(ReplayFrame {
+            skipped: std::mem::take(&mut self.skipped),
+            millis,
+            actions,
+        });
+    }
+
+    /// Writes this replay to a new file in the replays directory, returning
+    /// the path of the file
+    pub fn write(&mut self) -> Result<PathBuf, Error> {
+        // keep the frames without input recorded after the last input
+        if let Some((count, millis)) = self.skipped.pop() {
+            if count > 1 {
+                self.skipped.push((count - 1, millis));
+            }
+            self.frames.push_back(ReplayFrame {
+                skipped: std::mem::take(&mut self.skipped),
+                millis,
+                actions: Vec::new(),
+            });
+        }
+
+        let mut path = config::USER_DIR.clone();
+        path.push("replays");
+        if !path.is_dir() {
+            fs::create_dir_all(path.clone())?;
+        }
+
+        let utc = Utc::now();
+        path.push(format!("replay_{}.json", utc.format("%Y%m%d-%H%M%S%.3f")));
+
+        write_json_to_file(path.as_path(), self)?;
+        Ok(path)
+    }
+}
@@ -15,16 +15,20 @@
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
 use std::fs::{self, File};
-use std::io::{Error, Read};
+use std::io::{Error, Read, Write};
 use std::path::{Path, PathBuf};
 use std::time;
 
 use chrono::prelude::*;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 
+use crate::save_migration::{self, SAVE_FORMAT_VERSION};
 use crate::{GameState, SaveState};
-use sulis_core::resource::{read_single_resource_path, write_json_to_file};
+use sulis_core::config::{self, Config, SaveFormat};
+use sulis_core::extern_image::{ImageBuffer, Rgba};
+use sulis_core::resource::write_json_to_file;
 use sulis_core::util::invalid_data_error;
-use sulis_core::{config, serde_json, util};
+use sulis_core::{serde_json, util};
 use sulis_module::Module;
 
 #[derive(Serialize, Deserialize)]
@@ -36,7 +40,14 @@ pub struct SaveFile {
 
 impl SaveFile {
     fn from_json(data: &str) -> Result<Self, Error> {
-        let resource: Result<SaveFile, serde_json::Error> = serde_json::from_str(data);
+        let mut value: serde_json::Value = match serde_json::from_str(data) {
+            Ok(value) => value,
+            Err(error) => return invalid_data_error(&format!("{}", error)),
+        };
+
+        save_migration::migrate(&mut value)?;
+
+        let resource: Result<SaveFile, serde_json::Error> = serde_json::from_value(value);
 
         match resource {
             Ok(resource) => Ok(resource),
@@ -45,9 +56,19 @@ impl SaveFile {
     }
 }
 
+// Used to read just the meta data of a save file that could not be
+// read in full
+#[derive(Deserialize)]
+struct SaveFileMetaOnly {
+    meta: SaveFileMetaData,
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct SaveFileMetaData {
+    #[serde(default)]
+    pub name: Option<String>,
+
     pub player_name: String,
 
     pub level: Option<u32>,
@@ -56,6 +77,22 @@ pub struct SaveFileMetaData {
     pub datetime: String,
     pub current_area_name: String,
 
+    #[serde(default)]
+    pub format_version: u32,
+
+    #[serde(default)]
+    pub campaign_version: Option<String>,
+
+    #[serde(default)]
+    pub autosave: bool,
+
+    #[serde(default)]
+    pub play_time_millis: usize,
+
+    /// The image IDs of the portraits of each party member
+    #[serde(default)]
+    pub party_portraits: Vec<String>,
+
     #[serde(skip)]
     path: PathBuf,
 
@@ -63,6 +100,31 @@ pub struct SaveFileMetaData {
     pub error: Option<String>,
 }
 
+impl SaveFileMetaData {
+    /// Returns the path of the thumbnail image for this save, if one
+    /// was written
+    pub fn thumbnail_path(&self) -> Option<PathBuf> {
+        let path = thumbnail_path(&self.path);
+        if path.is_file() {
+            Some(path)
+        } else {
+            None
+        }
+    }
+}
+
+// The thumbnail is stored alongside the save file, with the same name
+// but a png extension in place of the save format extension
+fn thumbnail_path(save_path: &Path) -> PathBuf {
+    let file_name = save_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let stem = file_name.trim_end_matches(".gz").trim_end_matches(".json");
+
+    save_path.with_file_name(format!("{}.png", stem))
+}
+
 fn get_save_dir() -> PathBuf {
     let mut path = config::USER_DIR.clone();
     path.push("save");
@@ -72,22 +134,137 @@ fn get_save_dir() -> PathBuf {
 
 pub fn delete_save(save_file: &SaveFileMetaData) -> Result<(), Error> {
     let path = save_file.path.as_path();
-    fs::remove_file(path)
+    fs::remove_file(path)?;
+
+    if let Some(thumbnail) = save_file.thumbnail_path() {
+        fs::remove_file(thumbnail)?;
+    }
+
+    Ok(())
 }
 
 pub fn load_state(save_file: &SaveFileMetaData) -> Result<SaveState, Error> {
-    let path = save_file.path.as_path();
-    let save_file: SaveFile = read_single_resource_path(path)?;
-
-    Ok(save_file.state)
+    load_state_path(save_file.path.as_path())
 }
 
-pub fn create_save() -> Result<(), Error> {
-    let start_time = time::Instant::now();
-    info!("Start save");
+/// Reads the save state from the save file at the given path, which need
+/// not be in the current campaign's save directory
+pub fn load_state_path(path: &Path) -> Result<SaveState, Error> {
+    let mut state = read_save_file(path)?.state;
+
+    // the combat random generator is reseeded if the campaign opts in
+    if Module::campaign().reseed_combat_rand_on_load {
+        state.combat_rand = None;
+    }
+
+    Ok(state)
+}
 
+/// Writes a new save file with the specified user supplied name, if any.  If
+/// a thumbnail is specified, it is written as a png alongside the save.
+pub fn create_save(
+    name: Option<String>,
+    thumbnail: Option<ImageBuffer<Rgba<u8>, Vec<u8>>>,
+) -> Result<(), Error> {
     let utc = Utc::now();
-    let filename = format!("save_{}.json", utc.format("%Y%m%d-%H%M%S%.3f"));
+    let filename = format!("save_{}", utc.format("%Y%m%d-%H%M%S%.3f"));
+
+    write_save(&save_path(&filename)?, utc, name, false, SaveState::create())?;
+
+    if let Some(thumbnail) = thumbnail {
+        let mut path = get_save_dir();
+        path.push(format!("{}.png", filename));
+        // the save itself is still usable without its thumbnail
+        if let Err(e) = thumbnail.save(&path) {
+            warn!("Unable to write save thumbnail to {:?}", path);
+            warn!("{}", e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes an autosave into the next of the configured number of autosave
+/// slots.  Once all slots are filled, the oldest autosave is replaced.
+pub fn create_autosave() -> Result<(), Error> {
+    write_autosave(SaveState::create())
+}
+
+/// Writes the specified, previously created, save state as an autosave
+pub(crate) fn write_autosave(state: SaveState) -> Result<(), Error> {
+    let count = Config::autosave_count();
+    if count == 0 {
+        return Ok(());
+    }
+
+    let slot = next_autosave_slot(count);
+    let path = save_path(&autosave_slot_name(slot))?;
+
+    // the new autosave is written to a temporary file first, so that the old
+    // autosave in the slot is kept if the write fails
+    let mut tmp_path = path.clone().into_os_string();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+    if let Err(e) = write_save(&tmp_path, Utc::now(), None, true, state) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+
+    for old_path in autosave_slot_files(slot) {
+        fs::remove_file(old_path)?;
+    }
+
+    fs::rename(&tmp_path, &path)
+}
+
+fn autosave_slot_name(slot: usize) -> String {
+    format!("autosave_{}", slot)
+}
+
+// returns all files currently in the specified slot, in any save format
+fn autosave_slot_files(slot: usize) -> Vec<PathBuf> {
+    let name = autosave_slot_name(slot);
+
+    ["json", "json.gz", "png"]
+        .iter()
+        .map(|ext| {
+            let mut path = get_save_dir();
+            path.push(format!("{}.{}", name, ext));
+            path
+        })
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+// the first empty slot, or the slot with the oldest autosave if all are filled
+fn next_autosave_slot(count: usize) -> usize {
+    let mut oldest = (0, time::SystemTime::now());
+    for slot in 0..count {
+        let files = autosave_slot_files(slot);
+        if files.is_empty() {
+            return slot;
+        }
+
+        for path in files {
+            let modified = fs::metadata(&path).and_then(|meta| meta.modified());
+            let modified = modified.unwrap_or(time::UNIX_EPOCH);
+            if modified < oldest.1 {
+                oldest = (slot, modified);
+            }
+        }
+    }
+
+    oldest.0
+}
+
+// The path in the save directory for a save with the specified filename,
+// with the extension of the configured save format.  The save directory is
+// created if needed.
+fn save_path(filename: &str) -> Result<PathBuf, Error> {
+    let extension = match Config::save_format() {
+        SaveFormat::Json => "json",
+        SaveFormat::CompressedJson => "json.gz",
+    };
 
     let mut path = get_save_dir();
     if !path.is_dir() {
@@ -95,17 +272,29 @@ pub fn create_save() -> Result<(), Error> {
         fs::create_dir_all(path.clone())?;
     }
 
-    path.push(filename);
+    path.push(format!("{}.{}", filename, extension));
+    Ok(path)
+}
+
+fn write_save(
+    path: &Path,
+    utc: DateTime<Utc>,
+    name: Option<String>,
+    autosave: bool,
+    state: SaveState,
+) -> Result<(), Error> {
+    let start_time = time::Instant::now();
+    info!("Start save");
 
-    let meta = create_meta_data(utc.format("%c").to_string());
+    let mut meta = create_meta_data(utc.format("%c").to_string());
+    meta.name = name;
+    meta.autosave = autosave;
 
     info!(
         "  Filename and meta data creation complete in {} secs",
         util::format_elapsed_secs(start_time.elapsed())
     );
 
-    let state = SaveState::create();
-
     let save = SaveFile { meta, state };
 
     info!(
@@ -113,7 +302,10 @@ pub fn create_save() -> Result<(), Error> {
         util::format_elapsed_secs(start_time.elapsed())
     );
 
-    let result = write_json_to_file(path.as_path(), &save);
+    let result = match Config::save_format() {
+        SaveFormat::Json => write_json_to_file(path, &save),
+        SaveFormat::CompressedJson => write_compressed_json_to_file(path, &save),
+    };
 
     info!(
         "  Save to disk complete in {} secs",
@@ -123,18 +315,46 @@ pub fn create_save() -> Result<(), Error> {
     result
 }
 
+fn write_compressed_json_to_file(path: &Path, save: &SaveFile) -> Result<(), Error> {
+    let file = File::create(path)?;
+    let mut encoder = GzEncoder::new(file, Compression::default());
+
+    if let Err(e) = serde_json::to_writer(&mut encoder, save) {
+        return invalid_data_error(&format!("{}", e));
+    }
+
+    encoder.finish()?.flush()
+}
+
 fn create_meta_data(datetime: String) -> SaveFileMetaData {
     let cur_area = GameState::area_state();
     let cur_area = cur_area.borrow();
     let player = GameState::player();
     let player = player.borrow();
 
+    let party_portraits = GameState::party()
+        .iter()
+        .filter_map(|member| {
+            let member = member.borrow();
+            member.actor.actor.portrait.as_ref().map(|image| image.id())
+        })
+        .collect();
+
+    let mgr = GameState::turn_manager();
+    let play_time_millis = mgr.borrow().total_elapsed_millis();
+
     SaveFileMetaData {
+        name: None,
         player_name: player.actor.actor.name.to_string(),
         level: Some(player.actor.actor.total_level),
         class: Some(player.actor.actor.base_class().name.to_string()),
         datetime,
         current_area_name: cur_area.area.area.name.to_string(),
+        format_version: SAVE_FORMAT_VERSION,
+        campaign_version: Module::campaign().version.clone(),
+        autosave: false,
+        play_time_millis,
+        party_portraits,
         path: Default::default(),
         error: None,
     }
@@ -162,12 +382,7 @@ pub fn has_available_save_files() -> bool {
             continue;
         }
 
-        let extension = match path.extension() {
-            None => continue,
-            Some(ext) => ext.to_string_lossy(),
-        };
-
-        if extension != "json" {
+        if !is_save_file(&path) {
             continue;
         }
 
@@ -177,16 +392,69 @@ pub fn has_available_save_files() -> bool {
     false
 }
 
-fn read_save_file(path: &Path) -> Result<SaveFile, Error> {
-    let mut file = File::open(path)?;
+fn is_save_file(path: &Path) -> bool {
+    match path.extension() {
+        None => false,
+        Some(ext) => ext == "json" || ext == "gz",
+    }
+}
 
-    let mut file_data = String::new();
-    file.read_to_string(&mut file_data)?;
+// Reads the save file data, decompressing it first if it has the gzip
+// header.  Uncompressed saves are plain JSON.
+fn read_file(path: &Path) -> Result<String, Error> {
+    let mut file_data = Vec::new();
+    File::open(path)?.read_to_end(&mut file_data)?;
+
+    let mut data = String::new();
+    if file_data.starts_with(&[0x1f, 0x8b]) {
+        GzDecoder::new(file_data.as_slice()).read_to_string(&mut data)?;
+    } else {
+        match String::from_utf8(file_data) {
+            Ok(file_data) => data = file_data,
+            Err(e) => return invalid_data_error(&format!("{}", e)),
+        }
+    }
+
+    Ok(data)
+}
+
+fn read_save_file(path: &Path) -> Result<SaveFile, Error> {
+    let file_data = read_file(path)?;
 
     SaveFile::from_json(&file_data)
 }
 
-fn create_error_meta(path: PathBuf, error: Error) -> SaveFileMetaData {
+/// Attempts to read only the meta data from the save file at the given path,
+/// which may still be possible when the save state itself cannot be read.
+/// The error is set to a description of why the save could not be loaded,
+/// or to `error` if the save's versions match the current ones.
+fn read_incompatible_meta(path: &Path, error: &Error) -> Option<SaveFileMetaData> {
+    let file_data = read_file(path).ok()?;
+    let save: SaveFileMetaOnly = serde_json::from_str(&file_data).ok()?;
+    let mut meta = save.meta;
+
+    let error = if meta.format_version > SAVE_FORMAT_VERSION {
+        "This save was made with a newer version of Sulis".to_string()
+    } else {
+        let current = Module::campaign().version.clone();
+        match (&meta.campaign_version, current) {
+            (Some(version), Some(current)) if *version != current => format!(
+                "This save was made with campaign version {} (current is {})",
+                version, current
+            ),
+            (None, Some(_)) => {
+                "This save was made with an older version of the campaign".to_string()
+            }
+            _ => error.to_string(),
+        }
+    };
+
+    meta.path = path.to_path_buf();
+    meta.error = Some(error);
+    Some(meta)
+}
+
+fn create_error_meta(path: PathBuf) -> SaveFileMetaData {
     let time = match fs::metadata(&path) {
         Err(e) => {
             warn!("Unable to get metadata for invalid save file at {:?}", path);
@@ -209,13 +477,19 @@ fn create_error_meta(path: PathBuf, error: Error) -> SaveFileMetaData {
     let datetime = time.format("%c").to_string();
 
     SaveFileMetaData {
+        name: None,
         player_name: "Unknown Player".to_string(),
         level: None,
         class: None,
         datetime,
         current_area_name: "Unknown Area".to_string(),
+        format_version: 0,
+        campaign_version: None,
+        autosave: false,
+        play_time_millis: 0,
+        party_portraits: Vec::new(),
         path,
-        error: Some(error.to_string()),
+        error: Some("Invalid or corrupt save file".to_string()),
     }
 }
 
@@ -240,12 +514,7 @@ pub fn get_available_save_files() -> Result<Vec<SaveFileMetaData>, Error> {
             continue;
         }
 
-        let extension = match path.extension() {
-            None => continue,
-            Some(ext) => ext.to_string_lossy(),
-        };
-
-        if extension != "json" {
+        if !is_save_file(&path) {
             continue;
         }
 
@@ -256,7 +525,11 @@ pub fn get_available_save_files() -> Result<Vec<SaveFileMetaData>, Error> {
             Err(e) => {
                 warn!("Unable to read save file: {}", path_buf.to_string_lossy());
                 warn!("{}", e);
-                results.push(create_error_meta(path_buf, e));
+                let meta = match read_incompatible_meta(&path_buf, &e) {
+                    Some(meta) => meta,
+                    None => create_error_meta(path_buf),
+                };
+                results.push(meta);
                 continue;
             }
         };
@@ -0,0 +1,87 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+//! Upgrades save files written with older versions of the save format.
+//!
+//! Whenever a change is made to `SaveState` or any of the structs it contains
+//! that would prevent older saves from being read, `SAVE_FORMAT_VERSION`
+//! should be incremented and a function converting the previous version to
+//! the new one added to the end of `MIGRATIONS`.
+
+use std::io::Error;
+
+use sulis_core::serde_json::{Map, Value};
+use sulis_core::util::invalid_data_error;
+
+/// The save format version written by this version of the game
+pub const SAVE_FORMAT_VERSION: u32 = 2;
+
+type Migration = fn(&mut Map<String, Value>) -> Result<(), Error>;
+
+// the migration at index i converts a save from format version i to i + 1
+const MIGRATIONS: [Migration; SAVE_FORMAT_VERSION as usize] = [v0_to_v1, v1_to_v2];
+
+/// Returns the format version recorded in the specified save file data.
+/// Saves written before versioning was added are version 0.
+pub fn format_version(save: &Value) -> u32 {
+    save.get("meta")
+        .and_then(|meta| meta.get("format_version"))
+        .and_then(|version| version.as_u64())
+        .unwrap_or(0) as u32
+}
+
+/// Runs each migration needed to bring the save file data up to the
+/// current `SAVE_FORMAT_VERSION`, in order.
+pub fn migrate(save: &mut Value) -> Result<(), Error> {
+    let version = format_version(save);
+    if version > SAVE_FORMAT_VERSION {
+        return invalid_data_error(&format!(
+            "Save format version {} is newer than the supported version {}",
+            version, SAVE_FORMAT_VERSION
+        ));
+    }
+
+    let save = match save.as_object_mut() {
+        None => return invalid_data_error("Save file is not a JSON object"),
+        Some(save) => save,
+    };
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        debug!("Migrating save from format version {} to {}", index, index + 1);
+        migration(save)?;
+
+        if let Some(Value::Object(meta)) = save.get_mut("meta") {
+            meta.insert("format_version".to_string(), Value::from(index + 1));
+        }
+    }
+
+    Ok(())
+}
+
+// Saves prior to version 1 have no version information in the meta data, and
+// no combat random state, which is created fresh on load.  Otherwise, the
+// format is identical.
+fn v0_to_v1(_save: &mut Map<String, Value>) -> Result<(), Error> {
+    Ok(())
+}
+
+// Version 2 adds readied reactions and AI group states to the turn manager,
+// conditions and stacking to effects, and awareness and schedules to
+// entities.  Each of these has a default when missing, so older saves read
+// as is, but older versions of the game cannot read the new fields.
+fn v1_to_v2(_save: &mut Map<String, Value>) -> Result<(), Error> {
+    Ok(())
+}
@@ -20,18 +20,22 @@ use std::io::Error;
 use std::rc::Rc;
 use std::u64;
 
-use sulis_core::util::{ExtInt, Point};
+use sulis_core::util::{ExtInt, Point, ReproducibleRandom};
 use sulis_module::{
     actor::{ActorBuilder, RewardBuilder},
-    BonusList, ItemListEntrySaveState, ItemSaveState, QuickSlot, Slot,
+    Actor, BonusList, ConditionStacking, ItemListEntrySaveState, ItemSaveState, QuickSlot,
+    Slot,
 };
 
 use crate::animation::AnimSaveState;
 use crate::area_state::TriggerState;
+use crate::detection::AwarenessState;
+use crate::reaction::{ReactionTrigger, ReadiedReaction};
 use crate::script::CallbackData;
 use crate::{
-    effect, prop_state::Interactive, turn_manager::EncounterRef, ActorState, Effect, EntityState,
-    Formation, GameState, Location, MerchantState, PStats, PropState, QuestState, WorldMapState,
+    effect, prop_state::Interactive, turn_manager::EncounterRef, AIGroupState, ActorState,
+    AreaState, Effect, EntityState, Formation, GameState, Location, MerchantState, PStats,
+    PropState, QuestState, ScheduleState, TurnManager, WorldMapState,
 };
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -54,6 +58,9 @@ pub struct SaveState {
 
     #[serde(default)]
     pub(crate) total_elapsed_millis: usize,
+
+    #[serde(default)]
+    pub(crate) combat_rand: Option<ReproducibleRandom>,
 }
 
 fn default_zoom() -> f32 {
@@ -62,15 +69,30 @@ fn default_zoom() -> f32 {
 
 impl SaveState {
     pub fn create() -> SaveState {
-        let mut areas = HashMap::new();
+        let mgr = GameState::turn_manager();
+        let area_state = GameState::area_state();
+        let mut state = SaveState::snapshot(&mgr.borrow(), &area_state.borrow());
+        state.anims = GameState::save_anims();
+        state
+    }
+
+    /// Creates the save state using the turn manager and current area, which
+    /// may already be borrowed by the caller.  Animations are not saved, as
+    /// they may be in the middle of being updated, and must be added to the
+    /// returned state separately.
+    pub(crate) fn snapshot(mgr: &TurnManager, area_state: &AreaState) -> SaveState {
+        let current_area = area_state.area.area.id.to_string();
 
+        let mut areas = HashMap::new();
         for id in GameState::area_state_ids() {
-            areas.insert(id.to_string(), AreaSaveState::new(id));
+            let area_save = if id == current_area {
+                AreaSaveState::from_area(area_state)
+            } else {
+                AreaSaveState::new(id.to_string())
+            };
+            areas.insert(id, area_save);
         }
 
-        let area_state = GameState::area_state();
-        let current_area = area_state.borrow().area.area.id.to_string();
-
         let mut party = Vec::new();
         for entity in GameState::party().iter() {
             party.push(entity.borrow().index());
@@ -99,8 +121,7 @@ impl SaveState {
             current_quest,
         };
 
-        let mgr = GameState::turn_manager();
-        let total_elapsed_millis = mgr.borrow().total_elapsed_millis();
+        let total_elapsed_millis = mgr.total_elapsed_millis();
 
         SaveState {
             areas,
@@ -111,11 +132,12 @@ impl SaveState {
             formation,
             coins: GameState::party_coins(),
             stash,
-            manager: ManagerSaveState::new(),
-            anims: GameState::save_anims(),
+            manager: ManagerSaveState::new(mgr),
+            anims: Vec::new(),
             world_map: GameState::world_map(),
             quests: quest_state,
             total_elapsed_millis,
+            combat_rand: Some(GameState::combat_rand()),
         }
     }
 
@@ -138,12 +160,16 @@ pub struct ManagerSaveState {
     pub(crate) effects: Vec<EffectSaveState>,
     pub(crate) cur_ai_group_index: usize,
     pub(crate) ai_groups: HashMap<String, EncounterRef>,
+
+    #[serde(default)]
+    pub(crate) ai_group_states: HashMap<String, AIGroupSaveState>,
+
+    #[serde(default)]
+    pub(crate) readied_reactions: Vec<ReadiedReactionSaveState>,
 }
 
 impl ManagerSaveState {
-    pub fn new() -> ManagerSaveState {
-        let mgr = GameState::turn_manager();
-        let mgr = mgr.borrow();
+    pub fn new(mgr: &TurnManager) -> ManagerSaveState {
         let mut entities = Vec::new();
         for entity in mgr.entity_iter() {
             entities.push(EntitySaveState::new(entity));
@@ -165,11 +191,59 @@ impl ManagerSaveState {
             ai_groups.insert(key.to_string(), value.clone());
         }
 
+        let mut ai_group_states = HashMap::new();
+        for group in mgr.ai_group_states() {
+            ai_group_states.insert(group.index().to_string(), AIGroupSaveState::new(group));
+        }
+
+        let mut readied_reactions = Vec::new();
+        for (index, reaction) in mgr.readied_reactions() {
+            readied_reactions.push(ReadiedReactionSaveState::new(index, reaction));
+        }
+
         ManagerSaveState {
             entities,
             effects,
             cur_ai_group_index,
             ai_groups,
+            ai_group_states,
+            readied_reactions,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AIGroupSaveState {
+    pub(crate) focus_target: Option<usize>,
+    pub(crate) retreating: bool,
+}
+
+impl AIGroupSaveState {
+    pub fn new(group: &AIGroupState) -> AIGroupSaveState {
+        AIGroupSaveState {
+            focus_target: group.focus_target(),
+            retreating: group.is_retreating(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ReadiedReactionSaveState {
+    pub(crate) index: usize,
+    pub(crate) trigger: ReactionTrigger,
+    pub(crate) ap: u32,
+    pub(crate) cb: CallbackData,
+}
+
+impl ReadiedReactionSaveState {
+    pub fn new(index: usize, reaction: &ReadiedReaction) -> ReadiedReactionSaveState {
+        ReadiedReactionSaveState {
+            index,
+            trigger: reaction.trigger,
+            ap: reaction.ap,
+            cb: CallbackData::clone(&reaction.cb),
         }
     }
 }
@@ -191,6 +265,12 @@ pub struct EffectSaveState {
     #[serde(default)]
     pub(crate) icon: Option<effect::Icon>,
 
+    #[serde(default)]
+    pub(crate) condition: Option<String>,
+
+    #[serde(default = "default_stacking")]
+    pub(crate) stacking: ConditionStacking,
+
     #[serde(default = "default_true")]
     pub(crate) ui_visible: bool,
 }
@@ -199,6 +279,10 @@ fn default_true() -> bool {
     true
 }
 
+fn default_stacking() -> ConditionStacking {
+    ConditionStacking::Stack
+}
+
 impl EffectSaveState {
     pub fn new(effect: &Effect, index: usize) -> EffectSaveState {
         let mut callbacks: Vec<CallbackData> = Vec::new();
@@ -219,6 +303,8 @@ impl EffectSaveState {
             bonuses: effect.bonuses.clone(),
             callbacks,
             icon: effect.icon.clone(),
+            condition: effect.condition.clone(),
+            stacking: effect.stacking,
             ui_visible: effect.ui_visible,
         }
     }
@@ -241,7 +327,10 @@ impl AreaSaveState {
     pub fn new(id: String) -> AreaSaveState {
         let area_state = GameState::get_area_state(&id).unwrap();
         let area_state = area_state.borrow();
+        AreaSaveState::from_area(&area_state)
+    }
 
+    pub fn from_area(area_state: &AreaState) -> AreaSaveState {
         let mut pc_explored: Vec<u64> = Vec::new();
         let mut mask: u64 = 1;
         let mut cur_buf: u64 = 0;
@@ -433,6 +522,12 @@ pub struct EntitySaveState {
 
     #[serde(default)]
     pub(crate) collapsed_groups: Vec<String>,
+
+    #[serde(default)]
+    pub(crate) schedule: Option<ScheduleState>,
+
+    #[serde(default)]
+    pub(crate) awareness: AwarenessState,
 }
 
 impl EntitySaveState {
@@ -440,51 +535,7 @@ impl EntitySaveState {
         let entity = entity.borrow();
 
         let actor_base = if entity.is_party_member() {
-            let actor = &entity.actor.actor;
-
-            let mut levels = HashMap::new();
-            for (ref class, level) in actor.levels.iter() {
-                levels.insert(class.id.to_string(), *level);
-            }
-
-            let reward = actor.reward.as_ref().map(|reward| {
-                RewardBuilder {
-                    xp: reward.xp,
-                    loot: reward.loot.as_ref().map(|l| l.id.to_string()),
-                    loot_chance: Some(reward.loot_chance),
-                }
-            });
-
-            let mut abilities: Vec<String> = Vec::new();
-            for owned_ability in actor.abilities.iter() {
-                for _ in 0..=owned_ability.level {
-                    abilities.push(owned_ability.ability.id.to_string());
-                }
-            }
-
-            let ai = actor.ai.as_ref().map(|ai| ai.id.to_string());
-
-            Some(ActorBuilder {
-                id: actor.id.to_string(),
-                name: actor.name.to_string(),
-                race: Some(actor.race.id.to_string()),
-                inline_race: None,
-                sex: Some(actor.sex),
-                portrait: actor.portrait.as_ref().map(|p| p.id()),
-                attributes: actor.attributes,
-                conversation: actor.conversation.as_ref().map(|c| c.id.to_string()),
-                faction: Some(actor.faction()),
-                images: actor.builder_images.clone(),
-                hue: actor.hue,
-                hair_color: actor.hair_color,
-                skin_color: actor.skin_color,
-                inventory: actor.inventory.clone(),
-                levels,
-                xp: Some(actor.xp),
-                reward,
-                abilities,
-                ai,
-            })
+            Some(actor_builder(&entity.actor.actor))
         } else {
             None
         };
@@ -506,7 +557,58 @@ impl EntitySaveState {
             show_portrait: entity.show_portrait(),
             actor_base,
             collapsed_groups: entity.collapsed_groups(),
+            schedule: entity.schedule.clone(),
+            awareness: entity.awareness.clone(),
+        }
+    }
+}
+
+/// Creates a builder that can be used to recreate the specified actor.  This
+/// is used for party members, which may differ from any actor defined in
+/// the module.
+pub(crate) fn actor_builder(actor: &Actor) -> ActorBuilder {
+    let mut levels = HashMap::new();
+    for (ref class, level) in actor.levels.iter() {
+        levels.insert(class.id.to_string(), *level);
+    }
+
+    let reward = actor.reward.as_ref().map(|reward| {
+        RewardBuilder {
+            xp: reward.xp,
+            loot: reward.loot.as_ref().map(|l| l.id.to_string()),
+            loot_chance: Some(reward.loot_chance),
         }
+    });
+
+    let mut abilities: Vec<String> = Vec::new();
+    for owned_ability in actor.abilities.iter() {
+        for _ in 0..=owned_ability.level {
+            abilities.push(owned_ability.ability.id.to_string());
+        }
+    }
+
+    let ai = actor.ai.as_ref().map(|ai| ai.id.to_string());
+
+    ActorBuilder {
+        id: actor.id.to_string(),
+        name: actor.name.to_string(),
+        race: Some(actor.race.id.to_string()),
+        inline_race: None,
+        sex: Some(actor.sex),
+        portrait: actor.portrait.as_ref().map(|p| p.id()),
+        attributes: actor.attributes.clone(),
+        conversation: actor.conversation.as_ref().map(|c| c.id.to_string()),
+        faction: Some(actor.faction()),
+        images: actor.builder_images.clone(),
+        hue: actor.hue,
+        hair_color: actor.hair_color,
+        skin_color: actor.skin_color,
+        inventory: actor.inventory.clone(),
+        levels,
+        xp: Some(actor.xp),
+        reward,
+        abilities,
+        ai,
     }
 }
 
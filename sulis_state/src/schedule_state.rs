@@ -0,0 +1,236 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sulis_core::util::Point;
+use sulis_module::area::{Destination, Patrol, PatrolKind, ScheduleEntry};
+
+use crate::{EntityState, GameState};
+
+/// The time to wait before retrying after failing to find a path
+const RETRY_MILLIS: u32 = 2000;
+
+/// The maximum path length used when moving to the next destination
+const MAX_PATH_LEN: u32 = 200;
+
+/// The out of combat patrol and schedule of an entity, along with its
+/// progress along them.  Created from the entity's `ActorData` in the area
+/// definition, and saved with the entity
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduleState {
+    #[serde(default)]
+    patrol: Option<Patrol>,
+
+    #[serde(default)]
+    schedule: Vec<ScheduleEntry>,
+
+    #[serde(default)]
+    entry: Option<usize>,
+
+    #[serde(default)]
+    waypoint: usize,
+
+    #[serde(default)]
+    reverse: bool,
+
+    #[serde(default)]
+    wait_millis: u32,
+}
+
+impl ScheduleState {
+    /// Creates a new schedule state, or `None` if there is neither a patrol
+    /// nor any schedule entries
+    pub fn new(patrol: Option<Patrol>, schedule: Vec<ScheduleEntry>) -> Option<ScheduleState> {
+        if patrol.is_none() && schedule.is_empty() {
+            return None;
+        }
+
+        Some(ScheduleState {
+            patrol,
+            schedule,
+            entry: None,
+            waypoint: 0,
+            reverse: false,
+            wait_millis: 0,
+        })
+    }
+
+    /// Returns the schedule entry active at `hour`.  Before the first entry
+    /// of the day starts, the last entry from the previous day is still active
+    fn entry_for(&self, hour: u32) -> Option<usize> {
+        if self.schedule.is_empty() {
+            return None;
+        }
+
+        let index = self.schedule.iter().rposition(|entry| entry.start_hour <= hour);
+        Some(index.unwrap_or(self.schedule.len() - 1))
+    }
+
+    fn activity(&self) -> (Option<Point>, Option<&Patrol>) {
+        match self.entry {
+            None => (None, self.patrol.as_ref()),
+            Some(index) => {
+                let entry = &self.schedule[index];
+                (entry.location, entry.patrol.as_ref())
+            }
+        }
+    }
+
+    /// Advances this schedule by `millis` at the specified `hour` of the day.
+    /// `arrived` returns whether the entity has reached a given point.  Returns
+    /// the point the entity should move towards, if any
+    fn next_dest<F>(&mut self, arrived: F, hour: u32, millis: u32) -> Option<Point>
+    where
+        F: Fn(Point) -> bool,
+    {
+        let entry = self.entry_for(hour);
+        if entry != self.entry {
+            self.entry = entry;
+            self.waypoint = 0;
+            self.reverse = false;
+            self.wait_millis = 0;
+        }
+
+        if self.wait_millis > 0 {
+            self.wait_millis = self.wait_millis.saturating_sub(millis);
+            return None;
+        }
+
+        let (location, patrol) = self.activity();
+        if let Some(location) = location {
+            return if arrived(location) { None } else { Some(location) };
+        }
+
+        let (target, len, kind, wait) = match patrol {
+            None => return None,
+            Some(patrol) => {
+                let target = patrol.points[self.waypoint.min(patrol.points.len() - 1)];
+                (target, patrol.points.len(), patrol.kind, patrol.wait_millis)
+            }
+        };
+
+        if !arrived(target) {
+            return Some(target);
+        }
+
+        self.advance(len, kind);
+        self.wait_millis = wait;
+        None
+    }
+
+    fn advance(&mut self, len: usize, kind: PatrolKind) {
+        if len < 2 {
+            self.waypoint = 0;
+            return;
+        }
+
+        match kind {
+            PatrolKind::Loop => self.waypoint = (self.waypoint + 1) % len,
+            PatrolKind::PingPong => {
+                if self.reverse && self.waypoint == 0 {
+                    self.reverse = false;
+                } else if !self.reverse && self.waypoint == len - 1 {
+                    self.reverse = true;
+                }
+
+                if self.reverse {
+                    self.waypoint -= 1;
+                } else {
+                    self.waypoint += 1;
+                }
+            }
+        }
+    }
+
+    /// Called when no path could be found to the current destination.  A
+    /// blocked patrol point is skipped, and the move is retried after a delay
+    fn move_failed(&mut self) {
+        let (location, patrol) = self.activity();
+        let patrol = if location.is_some() { None } else { patrol };
+        if let Some((len, kind)) = patrol.map(|patrol| (patrol.points.len(), patrol.kind)) {
+            self.advance(len, kind);
+        }
+
+        self.wait_millis = RETRY_MILLIS;
+    }
+}
+
+/// Moves all entities in the current area with a patrol or schedule along
+/// it.  Nothing is done while combat is active
+pub(crate) fn update(millis: u32) {
+    let mgr = GameState::turn_manager();
+    if mgr.borrow().is_combat_active() {
+        return;
+    }
+
+    let hour = mgr.borrow().current_time().hour;
+    let area_state = GameState::area_state();
+    let area_id = area_state.borrow().area.area.id.to_string();
+
+    let entities: Vec<Rc<RefCell<EntityState>>> = mgr
+        .borrow()
+        .entity_iter()
+        .filter(|entity| {
+            let entity = entity.borrow();
+            entity.schedule.is_some()
+                && entity.location.is_in_area_id(&area_id)
+                && !entity.is_party_member()
+                && !entity.is_ai_active()
+                && !entity.actor.is_dead()
+        })
+        .collect();
+
+    for entity in entities {
+        if GameState::has_blocking_animations(&entity) {
+            continue;
+        }
+
+        let dest = {
+            let entity = &mut *entity.borrow_mut();
+            let (x, y) = (entity.location.x, entity.location.y);
+            // check arrival against the same destination the path finder is given,
+            // as it will not find a path to a destination that is already reached
+            let base = GameState::get_point_dest(entity, 0.0, 0.0);
+            let arrived = |point: Point| {
+                let dest = Destination { x: point.x as f32, y: point.y as f32, ..base };
+                dest.is_reached(x, y)
+            };
+
+            match entity.schedule.as_mut() {
+                None => continue,
+                Some(schedule) => schedule.next_dest(arrived, hour, millis),
+            }
+        };
+
+        let dest = match dest {
+            None => continue,
+            Some(dest) => dest,
+        };
+
+        let index = entity.borrow().index();
+        let mut dest = GameState::get_point_dest(&entity.borrow(), dest.x as f32, dest.y as f32);
+        dest.max_path_len = Some(MAX_PATH_LEN);
+        if !GameState::move_towards_dest(&entity, &[index], dest, None) {
+            debug!("Unable to move '{}' along its schedule", entity.borrow().unique_id());
+            if let Some(schedule) = entity.borrow_mut().schedule.as_mut() {
+                schedule.move_failed();
+            }
+        }
+    }
+}
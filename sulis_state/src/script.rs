@@ -49,6 +49,9 @@ pub use self::module_export::ModuleExport;
 mod script_ability;
 pub use self::script_ability::{ScriptAbility, ScriptAbilitySet};
 
+mod script_ai_group;
+pub use self::script_ai_group::ScriptAIGroup;
+
 pub mod script_cache;
 
 pub mod script_callback;
@@ -22,7 +22,7 @@ use sulis_core::image::Image;
 use sulis_core::io::{DrawList, GraphicsRenderer};
 use sulis_core::ui::{animation_state, color, Cursor, LineRenderer};
 use sulis_core::util::{Offset, Point, Rect, Scale};
-use sulis_module::{Ability, Module, ObjectSize};
+use sulis_module::{Ability, Cover, Module, ObjectSize};
 
 use crate::script::{targeter, ScriptItemKind, TargeterData};
 use crate::{
@@ -678,6 +678,7 @@ pub struct AreaTargeter {
     script_source: ScriptSource,
     parent: Rc<RefCell<EntityState>>,
     selectable: Vec<Rc<RefCell<EntityState>>>,
+    selectable_cover: Vec<Cover>,
     effectable: Vec<Rc<RefCell<EntityState>>>,
     max_effectable: Option<usize>,
     shape: Shape,
@@ -719,6 +720,29 @@ fn create_entity_state_vec(
     out
 }
 
+// Cover is only shown for hostile targets, and doesn't change while
+// targeting as neither the parent nor the targets can move
+fn selectable_cover(
+    parent: &Rc<RefCell<EntityState>>,
+    selectable: &[Rc<RefCell<EntityState>>],
+) -> Vec<Cover> {
+    let area = GameState::area_state();
+    let area = area.borrow();
+    let parent = parent.borrow();
+
+    selectable
+        .iter()
+        .map(|target| {
+            let target = target.borrow();
+            if !parent.is_hostile(&target) {
+                Cover::None
+            } else {
+                area.cover(&parent, &target)
+            }
+        })
+        .collect()
+}
+
 impl AreaTargeter {
     pub fn from(data: &TargeterData) -> AreaTargeter {
         let mgr = GameState::turn_manager();
@@ -774,6 +798,9 @@ impl AreaTargeter {
             }
         };
 
+        let selectable = create_entity_state_vec(&mgr, &data.selectable);
+        let selectable_cover = selectable_cover(&parent, &selectable);
+
         AreaTargeter {
             on_target_select_func: data.on_target_select_func.to_string(),
             on_target_select_custom_target: match data.on_target_select_custom_target {
@@ -782,7 +809,8 @@ impl AreaTargeter {
             },
             script_source,
             parent,
-            selectable: create_entity_state_vec(&mgr, &data.selectable),
+            selectable,
+            selectable_cover,
             effectable: create_entity_state_vec(&mgr, &data.effectable),
             max_effectable: data.max_effectable,
             cancel: false,
@@ -951,6 +979,8 @@ impl AreaTargeter {
             renderer.draw(draw_list);
         }
 
+        self.draw_cover(renderer, params, offset, scale, millis);
+
         let mut draw_list = DrawList::empty_sprite();
         for target in self.cur_effected.iter() {
             draw_list.append(&mut self.draw_target(target, offset));
@@ -979,6 +1009,39 @@ impl AreaTargeter {
         }
     }
 
+    fn draw_cover(
+        &self,
+        renderer: &mut dyn GraphicsRenderer,
+        params: &Params,
+        offset: Offset,
+        scale: Scale,
+        millis: u32,
+    ) {
+        let mut draw_list = DrawList::empty_sprite();
+        for (target, cover) in self.selectable.iter().zip(self.selectable_cover.iter()) {
+            let icon = match cover {
+                Cover::None => continue,
+                Cover::Partial => &params.partial_cover_icon,
+                Cover::Full => &params.full_cover_icon,
+            };
+
+            let target = target.borrow();
+            let size = params.scale / 1.5;
+            let rect = Rect {
+                x: target.location.x as f32 + target.size.width as f32 - size - offset.x,
+                y: target.location.y as f32 - offset.y,
+                w: size,
+                h: size,
+            };
+            icon.append_to_draw_list(&mut draw_list, &animation_state::NORMAL, rect, millis);
+        }
+
+        if !draw_list.is_empty() {
+            draw_list.set_scale(scale);
+            renderer.draw(draw_list);
+        }
+    }
+
     fn draw_ap_usage(
         &self,
         renderer: &mut dyn GraphicsRenderer,
@@ -21,7 +21,7 @@ use std::rc::Rc;
 use rlua::{self, Context, UserData, UserDataMethods};
 
 use crate::script::{CallbackData, ScriptEntity};
-use crate::{area_feedback_text::ColorKind, AreaFeedbackText, EntityState, GameState};
+use crate::{area_feedback_text::ColorKind, reaction, AreaFeedbackText, EntityState, GameState};
 use sulis_module::{
     ability::{self, AIData, Range},
     Ability, Module,
@@ -475,5 +475,7 @@ fn activate(
         .borrow_mut()
         .actor
         .activate_ability_state(&ability.id);
+
+    reaction::check_ability(&entity);
     Ok(())
 }
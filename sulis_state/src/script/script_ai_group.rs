@@ -0,0 +1,207 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use rlua::{Context, Table, UserData, UserDataMethods};
+
+use crate::script::{Result, ScriptEntity};
+use crate::{AIGroupState, GameState};
+use sulis_core::util::Point;
+
+/// The shared AI state of the members of a spawned encounter, used to
+/// coordinate group tactics.  The tactics themselves are configured in the
+/// `tactics` section of the encounter.  Normally created by
+/// `ScriptEntity:ai_group()`
+///
+/// # `id() -> Int`
+/// Returns the index of this group.  Entities with the same AI group id are
+/// members of the same group.
+///
+/// # `members() -> Table`
+/// Returns an array-like table containing a `ScriptEntity` for each living
+/// member of this group.
+///
+/// # `focus_fire() -> Bool`
+/// Returns whether members of this group should attack the same target.
+///
+/// # `ranged_behind_melee() -> Bool`
+/// Returns whether ranged members of this group should stay behind the
+/// melee members.
+///
+/// # `flank() -> Bool`
+/// Returns whether melee members of this group should approach targets from
+/// flanking positions.
+///
+/// # `retreat_threshold() -> Float`
+/// Returns the fraction of its combined maximum hit points at which this group
+/// retreats, or zero if this group never retreats.
+///
+/// # `focus_target() -> ScriptEntity`
+/// Returns the target that members of this group should prefer to attack, or
+/// nil if focus fire is disabled or no target has been chosen yet.  The focus
+/// target is cleared automatically when it dies.
+///
+/// # `set_focus_target(target: ScriptEntity)`
+/// Sets the target that members of this group should prefer to attack.
+///
+/// # `clear_focus_target()`
+/// Clears the focus target, allowing a new one to be chosen.
+///
+/// # `is_retreating() -> Bool`
+/// Returns true if this group is retreating.  This is set automatically once
+/// the group has taken enough damage, based on the `retreat_threshold`.
+///
+/// # `set_retreating(retreating: Bool)`
+/// Sets whether all members of this group should retreat.
+///
+/// # `flank_point(parent: ScriptEntity, target: ScriptEntity) -> Table`
+/// Returns a table with `x` and `y` coordinates of the closest position to
+/// `parent` from which it can attack `target` while flanking it with another
+/// attacker.  Returns nil if flanking is disabled for this group, `parent` does
+/// not have a melee attack, or no such position exists.
+/// ## Examples
+/// ```lua
+///   local point = parent:ai_group():flank_point(parent, target)
+///   if point ~= nil then
+///     parent:move_towards_point(point.x, point.y)
+///   end
+/// ```
+///
+/// # `ranged_point(parent: ScriptEntity, target: ScriptEntity) -> Table`
+/// Returns a table with `x` and `y` coordinates of a position where `parent`
+/// is behind the melee members of this group but can still attack `target`.
+/// Returns nil if ranged members do not keep behind melee in this group,
+/// `parent` does not have a ranged attack, or `parent` is already behind the
+/// melee members.
+#[derive(Clone)]
+pub struct ScriptAIGroup {
+    index: usize,
+}
+
+impl ScriptAIGroup {
+    pub fn new(index: usize) -> ScriptAIGroup {
+        ScriptAIGroup { index }
+    }
+
+    fn try_unwrap(&self) -> Result<AIGroupState> {
+        let mgr = GameState::turn_manager();
+        let group = mgr.borrow_mut().ai_group(self.index);
+        match group {
+            None => Err(rlua::Error::FromLuaConversionError {
+                from: "ScriptAIGroup",
+                to: "AIGroupState",
+                message: Some(format!("AI group {} does not exist", self.index)),
+            }),
+            Some(group) => Ok(group),
+        }
+    }
+}
+
+impl UserData for ScriptAIGroup {
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("id", |_, group, ()| Ok(group.index));
+
+        methods.add_method("members", |_, group, ()| {
+            let mgr = GameState::turn_manager();
+            let members: Vec<ScriptEntity> = mgr
+                .borrow()
+                .ai_group_members(group.index)
+                .iter()
+                .filter(|e| !e.borrow().actor.is_dead())
+                .map(ScriptEntity::from)
+                .collect();
+            Ok(members)
+        });
+
+        methods.add_method("focus_fire", |_, group, ()| {
+            Ok(group.try_unwrap()?.tactics().focus_fire)
+        });
+
+        methods.add_method("ranged_behind_melee", |_, group, ()| {
+            Ok(group.try_unwrap()?.tactics().ranged_behind_melee)
+        });
+
+        methods.add_method("flank", |_, group, ()| Ok(group.try_unwrap()?.tactics().flank));
+
+        methods.add_method("retreat_threshold", |_, group, ()| {
+            Ok(group.try_unwrap()?.tactics().retreat_threshold)
+        });
+
+        methods.add_method("focus_target", |_, group, ()| {
+            Ok(group.try_unwrap()?.focus_target().map(ScriptEntity::new))
+        });
+
+        methods.add_method("set_focus_target", |_, group, target: ScriptEntity| {
+            let target = target.try_unwrap_index()?;
+            group.try_unwrap()?;
+            let mgr = GameState::turn_manager();
+            mgr.borrow_mut().set_ai_group_focus_target(group.index, Some(target));
+            Ok(())
+        });
+
+        methods.add_method("clear_focus_target", |_, group, ()| {
+            group.try_unwrap()?;
+            let mgr = GameState::turn_manager();
+            mgr.borrow_mut().set_ai_group_focus_target(group.index, None);
+            Ok(())
+        });
+
+        methods.add_method("is_retreating", |_, group, ()| {
+            Ok(group.try_unwrap()?.is_retreating())
+        });
+
+        methods.add_method("set_retreating", |_, group, retreating: bool| {
+            group.try_unwrap()?;
+            let mgr = GameState::turn_manager();
+            mgr.borrow_mut().set_ai_group_retreating(group.index, retreating);
+            Ok(())
+        });
+
+        methods.add_method(
+            "flank_point",
+            |lua, group, (parent, target): (ScriptEntity, ScriptEntity)| {
+                let ai_group = group.try_unwrap()?;
+                let parent = parent.try_unwrap()?;
+                let target = target.try_unwrap()?;
+                let point = ai_group.flank_point(&parent.borrow(), &target.borrow());
+                point_table(lua, point)
+            },
+        );
+
+        methods.add_method(
+            "ranged_point",
+            |lua, group, (parent, target): (ScriptEntity, ScriptEntity)| {
+                let ai_group = group.try_unwrap()?;
+                let parent = parent.try_unwrap()?;
+                let target = target.try_unwrap()?;
+                let members = GameState::turn_manager().borrow().ai_group_members(group.index);
+                let point = ai_group.ranged_point(&parent.borrow(), &target.borrow(), &members);
+                point_table(lua, point)
+            },
+        );
+    }
+}
+
+fn point_table(lua: Context, point: Option<Point>) -> Result<Option<Table>> {
+    let point = match point {
+        None => return Ok(None),
+        Some(point) => point,
+    };
+
+    let table = lua.create_table()?;
+    table.set("x", point.x)?;
+    table.set("y", point.y)?;
+    Ok(Some(table))
+}
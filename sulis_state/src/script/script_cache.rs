@@ -25,12 +25,32 @@ use crate::script::{
     Result, ScriptAbility, ScriptEntity, ScriptEntitySet, ScriptItem, ScriptItemKind, ScriptState,
 };
 use crate::{ai, EntityState};
+use sulis_core::config::Config;
+use sulis_core::resource::DirWatcher;
 use sulis_core::util::Point;
 use sulis_module::{ai::AITemplate, Ability, Item, Module};
 
+const RELOAD_POLL_INTERVAL: Duration = Duration::from_millis(1000);
+
 thread_local! {
     static SCRIPT_CACHE: RefCell<HashMap<String, Rc<ScriptState>>> = RefCell::new(HashMap::new());
     static REPORTING: Cell<bool> = Cell::new(true);
+    static WATCHER: RefCell<Option<ScriptWatcher>> = RefCell::new(None);
+}
+
+struct ScriptWatcher {
+    dirs: DirWatcher,
+    last_poll: Instant,
+}
+
+/// The outcome of reloading scripts that were changed on disk
+pub struct ReloadReport {
+    /// The IDs of all scripts that were successfully reloaded
+    pub reloaded: Vec<String>,
+
+    /// Errors for each script that could not be loaded.  The previous
+    /// version of each of these scripts remains in use.
+    pub errors: Vec<String>,
 }
 
 pub fn setup() -> Result<()> {
@@ -48,6 +68,17 @@ pub fn setup() -> Result<()> {
         Ok(())
     })?;
 
+    WATCHER.with(|watcher| {
+        *watcher.borrow_mut() = if Config::debug().hot_reload_scripts {
+            Some(ScriptWatcher {
+                dirs: DirWatcher::new(Module::script_dirs(), "lua"),
+                last_poll: Instant::now(),
+            })
+        } else {
+            None
+        };
+    });
+
     info!(
         "Setup scripts in {:.3} millis",
         get_elapsed_millis(start.elapsed())
@@ -55,6 +86,65 @@ pub fn setup() -> Result<()> {
     Ok(())
 }
 
+/// If script hot reloading is enabled, checks the script directories for
+/// any changed files.  All scripts affected by the changes, including those
+/// that include a changed script, are then loaded again and replace the
+/// cached versions.  Returns `None` if no scripts were changed.
+pub fn poll_for_changes() -> Option<ReloadReport> {
+    let changed = WATCHER.with(|watcher| {
+        let mut watcher = watcher.borrow_mut();
+        let watcher = match watcher.as_mut() {
+            None => return false,
+            Some(watcher) => watcher,
+        };
+
+        if watcher.last_poll.elapsed() < RELOAD_POLL_INTERVAL {
+            return false;
+        }
+        watcher.last_poll = Instant::now();
+
+        !watcher.dirs.changed_files().is_empty()
+    });
+
+    if !changed {
+        return None;
+    }
+
+    let ids = Module::reload_scripts();
+    if ids.is_empty() {
+        return None;
+    }
+
+    let mut report = ReloadReport {
+        reloaded: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    SCRIPT_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+
+        for id in ids {
+            let mut state = ScriptState::default();
+            let result = get_script_from_id(&id).and_then(|script| state.load(&id, &script));
+
+            match result {
+                Ok(()) => {
+                    info!("Reloaded script '{}'", id);
+                    cache.insert(id.to_string(), Rc::new(state));
+                    report.reloaded.push(id);
+                }
+                Err(e) => {
+                    warn!("Unable to reload script '{}'", id);
+                    warn!("{}", e);
+                    report.errors.push(format!("{}: {}", id, e));
+                }
+            }
+        }
+    });
+
+    Some(report)
+}
+
 pub fn set_report_enabled(enabled: bool) {
     REPORTING.with(|r| r.set(enabled));
 }
@@ -196,6 +196,15 @@ pub enum FuncKind {
 
     /// Called whena an ability mode is deactivated
     OnDeactivated,
+
+    /// Called when the parent entity reacts to an action by a hostile entity,
+    /// such as with an attack of opportunity or a readied reaction.  The
+    /// hostile entity is provided as the target.
+    OnReaction,
+
+    /// Called when a hidden parent entity is spotted by a hostile entity's
+    /// perception roll.  The spotting entity is provided as the target.
+    OnSpotted,
 }
 
 /// A trait representing a callback that will fire a script when called.  In lua scripts,
@@ -252,6 +261,10 @@ pub trait ScriptCallback {
     fn on_entered_surface(&self, _target: usize) {}
 
     fn on_exited_surface(&self, _target: usize) {}
+
+    fn on_reaction(&self, _target: usize) {}
+
+    fn on_spotted(&self, _target: usize) {}
 }
 
 /// A callback that can be passed to various functions to be executed later.
@@ -290,6 +303,8 @@ pub trait ScriptCallback {
 /// # `set_on_moved_in_surface_fn(func: String)`
 /// # `set_on_entered_surface_fn(func: String)`
 /// # `set_on_exited_surface_fn(func: String)`
+/// # `set_on_reaction_fn(func: String)`
+/// # `set_on_spotted_fn(func: String)`
 /// Each of these methods causes a specified lua `func` to be called when the condition is met,
 /// as described in `FuncKind`.  Multiple of these methods may be added to one
 /// Callback.
@@ -316,6 +331,10 @@ impl CallbackData {
         self.parent
     }
 
+    pub fn effect(&self) -> Option<usize> {
+        self.effect
+    }
+
     pub fn get_func(&self, func: FuncKind) -> Option<String> {
         self.funcs.get(&func).cloned()
     }
@@ -620,6 +639,28 @@ impl ScriptCallback for CallbackData {
         self.exec_standard_script(targets, FuncKind::OnExitedSurface);
     }
 
+    fn on_reaction(&self, target: usize) {
+        if !self.funcs.contains_key(&FuncKind::OnReaction) {
+            return;
+        }
+
+        let mut targets = ScriptEntitySet::with_parent(self.parent);
+        targets.indices.push(Some(target));
+
+        self.exec_standard_script(targets, FuncKind::OnReaction);
+    }
+
+    fn on_spotted(&self, target: usize) {
+        if !self.funcs.contains_key(&FuncKind::OnSpotted) {
+            return;
+        }
+
+        let mut targets = ScriptEntitySet::with_parent(self.parent);
+        targets.indices.push(Some(target));
+
+        self.exec_standard_script(targets, FuncKind::OnSpotted);
+    }
+
     fn after_defense(
         &self,
         targets: &ScriptEntitySet,
@@ -836,6 +877,14 @@ impl UserData for CallbackData {
             cb.add_func(FuncKind::OnExitedSurface, func);
             Ok(())
         });
+        methods.add_method_mut("set_on_reaction_fn", |_, cb, func: String| {
+            cb.add_func(FuncKind::OnReaction, func);
+            Ok(())
+        });
+        methods.add_method_mut("set_on_spotted_fn", |_, cb, func: String| {
+            cb.add_func(FuncKind::OnSpotted, func);
+            Ok(())
+        });
     }
 }
 
@@ -883,12 +932,12 @@ pub struct ScriptHitKind {
 
 #[derive(Clone)]
 struct DamageEntry {
-    kind: &'static str,
+    kind: DamageKind,
     amount: u32,
 }
 impl UserData for DamageEntry {
     fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
-        methods.add_method("kind", |_, entry, ()| Ok(entry.kind));
+        methods.add_method("kind", |_, entry, ()| Ok(entry.kind.id()));
         methods.add_method("amount", |_, entry, ()| Ok(entry.amount));
     }
 }
@@ -900,7 +949,7 @@ impl ScriptHitKind {
         for (kind, amount) in damage {
             total_damage += amount;
             entries.push(DamageEntry {
-                kind: kind.to_str(),
+                kind,
                 amount,
             });
         }
@@ -927,7 +976,7 @@ impl UserData for ScriptHitKind {
         methods.add_method("damage_of_type", |_, hit, kind: String| {
             let mut total = 0;
             for entry in hit.entries.iter() {
-                if entry.kind != kind {
+                if entry.kind.id() != kind {
                     continue;
                 }
                 total += entry.amount;
@@ -14,7 +14,9 @@
 //  You should have received a copy of the GNU General Public License
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 use std::str::FromStr;
 
 use rlua::{Context, UserData, UserDataMethods};
@@ -22,8 +24,8 @@ use rlua::{Context, UserData, UserDataMethods};
 use sulis_core::util::{ExtInt, Point};
 use sulis_module::{
     bonus::{self, Contingent},
-    ArmorKind, Attribute, Bonus, BonusKind, BonusList, Damage, DamageKind, Slot, WeaponKind,
-    WeaponStyle, ROUND_TIME_MILLIS,
+    ArmorKind, Attribute, Bonus, BonusKind, BonusList, Condition, ConditionStacking, Damage,
+    DamageKind, Module, Slot, WeaponKind, WeaponStyle, ROUND_TIME_MILLIS,
 };
 
 use crate::script::{
@@ -32,7 +34,9 @@ use crate::script::{
     ScriptCallback, ScriptColorAnimation, ScriptEntity, ScriptImageLayerAnimation,
     ScriptParticleGenerator, ScriptScaleAnimation, ScriptSubposAnimation,
 };
-use crate::{effect, Effect, GameState};
+use crate::{
+    area_feedback_text::ColorKind, effect, AreaFeedbackText, Effect, EntityState, GameState,
+};
 
 /// Represents a surface that already exists, and is being passed into
 /// a Lua script.  Not used during effect creation
@@ -116,8 +120,8 @@ impl UserData for ScriptMenuSelection {
 /// Checks whether this effect has one of more bonuses of the given kind.  The kind
 /// Bonus kinds include `armor`, `ap`, `reach`, `range`, `initiative`, `hit_points`,
 /// `melee_accuracy`, `ranged_accuracy`, `spell_accuracy`, `defense`, `fortitude`,
-/// `reflex`, `will`, `concealment`, `concealment_ignore`, `crit_chance`,
-/// `hit_threshold`, `graze_threshold`, `graze_multiplier`, `hit_multiplier`,
+/// `reflex`, `will`, `concealment`, `concealment_ignore`, `stealth`, `perception`,
+/// `crit_chance`, `hit_threshold`, `graze_threshold`, `graze_multiplier`, `hit_multiplier`,
 /// `crit_multiplier`, `movement_rate`, `move_anim_rate`, `attack_cost`, `ability_ap_cost`,
 /// `hidden`, `free_ability_group_use`, abilities_disabled`, `move_disabled`,
 /// `attack_disabled`, `flanked_immunity`, `sneak_attack_immunity`, `crit_immunity`
@@ -175,6 +179,8 @@ fn check_for_bonus(effect: &ScriptAppliedEffect, kind: String) -> bool {
         "will" => Will(0),
         "concealment" => Concealment(0),
         "concealment_ignore" => ConcealmentIgnore(0),
+        "stealth" => Stealth(0),
+        "perception" => Perception(0),
         "crit_chance" => CritChance(0),
         "hit_threshold" => HitThreshold(0),
         "graze_threshold" => GrazeThreshold(0),
@@ -295,6 +301,25 @@ impl UserData for ScriptAppliedEffect {
 /// Sets the specified icon and text as the icon data for this effect.  This icon
 /// is displayed in various places in the UI.
 ///
+/// # `set_condition(id: String)`
+/// Sets this effect to apply the `Condition` with the specified `id`.  The condition's
+/// bonuses are added to this effect, and its icon and name are used as the icon
+/// for this effect.  If the effect does not have a tag set, the condition ID is used
+/// as the tag.  When applied to an entity that is immune to the condition, the effect
+/// is not applied.  If the entity already has the condition, the condition's stacking
+/// rule is used unless `set_stacking` is called.
+///
+/// # `set_stacking(policy: String, amount: Float (Optional))`
+/// Sets how this effect interacts with effects with the same tag (or the same
+/// condition, if one is set) already applied to the target.  `stack` adds this effect
+/// alongside any others and is the default.  `stack_up_to` keeps at most `amount`
+/// effects, removing the oldest.  `refresh` extends the duration of the existing
+/// effect instead of adding this one.  `replace` removes existing effects.
+/// `highest_wins` uses `amount` as this effect's strength; it is only added if no
+/// existing effect is stronger, and it replaces any that are not.  When not set,
+/// effects with a condition use the condition's stacking rule.  Has no effect on
+/// surfaces.
+///
 /// # `set_squares_to_fire_on_moved(squares: Int)`
 /// Only has an effect on surfaces.  Sets the number of squares that an entity
 /// must move within a surface in order to trigger an `OnMovedInSurface` script
@@ -349,14 +374,14 @@ impl UserData for ScriptAppliedEffect {
 /// Positive values are bonuses, while negative values are penalties.  `when` is optional
 /// and specifies a condition that must be met for the bonus to be active.  By default,
 /// the bonus is always applied.  Valid values are `always`, `attack_when_hidden`,
-/// `attack_when_flanking`, `weapon_equipped <WEAPON_KIND>`,
+/// `attack_when_flanking`, `attack_from_higher_ground`, `weapon_equipped <WEAPON_KIND>`,
 /// `armor_equipped <ARMOR_KIND> <INVENTORY_SLOT>`, `weapon_style <WEAPON_STYLE>`,
 /// `attack_with_weapon <WEAPON_KIND>`, `attack_with_damage_kind <DAMAGE_KIND>`
 ///
 /// Bonus kinds include `armor`, `ap`, `reach`, `range`, `initiative`, `hit_points`,
 /// `melee_accuracy`, `ranged_accuracy`, `spell_accuracy`, `defense`, `fortitude`,
-/// `reflex`, `will`, `concealment`, `concealment_ignore`, `crit_chance`,
-/// `hit_threshold`, `graze_threshold`, `graze_multiplier`, `hit_multiplier`,
+/// `reflex`, `will`, `concealment`, `concealment_ignore`, `stealth`, `perception`,
+/// `crit_chance`, `hit_threshold`, `graze_threshold`, `graze_multiplier`, `hit_multiplier`,
 /// `crit_multiplier`, `movement_rate`, `move_anim_rate`, `attack_cost`, `ability_ap_cost`,
 /// `caster_level`, `flanking_angle`
 ///
@@ -388,6 +413,10 @@ impl UserData for ScriptAppliedEffect {
 /// # `add_crit_immunity(when: String (Optional))`
 /// Adds immunity to crits to this effect (all crits become hits).  See `add_num_bonus`
 ///
+/// # `add_condition_immunity(id: String, when: String (Optional))`
+/// Adds immunity to the `Condition` with the specified `id` to this effect.  See
+/// `add_num_bonus`
+///
 /// # `add_damage_of_kind(min: Float, max: Float, kind: String, ap: String (Optional),
 /// when: String (Optional))`
 /// Adds the specified amount (from `min` to `max` randomly, with `ap` armor piercing)
@@ -404,7 +433,8 @@ impl UserData for ScriptAppliedEffect {
 ///
 /// # `add_attribute_bonus(attr: String, amount: Float, when: String (Optional))`
 /// Adds an attribute bonus for `attr` of `amount` to this effect.  Valid attributes
-/// are `Strength`, `Dexterity`, `Endurance`, `Perception`, `Intellect`, and `Wisdom`
+/// are those declared in the rules, by default `Strength`, `Dexterity`, `Endurance`,
+/// `Perception`, `Intellect`, and `Wisdom`
 #[derive(Clone)]
 pub struct ScriptEffect {
     kind: Kind,
@@ -415,6 +445,8 @@ pub struct ScriptEffect {
     deactivate_with_ability: Option<String>,
     pub bonuses: BonusList,
     icon: Option<effect::Icon>,
+    condition: Option<String>,
+    stacking: Option<ConditionStacking>,
     callbacks: Vec<CallbackData>,
     pgens: Vec<ScriptParticleGenerator>,
     image_layer_anims: Vec<ScriptImageLayerAnimation>,
@@ -437,6 +469,8 @@ impl ScriptEffect {
             deactivate_with_ability: None,
             duration,
             icon: None,
+            condition: None,
+            stacking: None,
             bonuses: BonusList::default(),
             callbacks: Vec::new(),
             pgens: Vec::new(),
@@ -456,6 +490,8 @@ impl ScriptEffect {
             deactivate_with_ability: None,
             duration,
             icon: None,
+            condition: None,
+            stacking: None,
             bonuses: BonusList::default(),
             callbacks: Vec::new(),
             pgens: Vec::new(),
@@ -474,6 +510,24 @@ impl UserData for ScriptEffect {
             effect.icon = Some(effect::Icon { icon, text });
             Ok(())
         });
+        methods.add_method_mut("set_condition", |_, effect, id: String| {
+            if Module::condition(&id).is_none() {
+                warn!("Attempted to set invalid condition '{}' on effect", id);
+                return Ok(());
+            }
+            effect.condition = Some(id);
+            Ok(())
+        });
+        methods.add_method_mut(
+            "set_stacking",
+            |_, effect, (kind, amount): (String, Option<f32>)| {
+                match ConditionStacking::from_str(&kind, amount) {
+                    None => warn!("Invalid stacking policy '{}' for effect", kind),
+                    Some(stacking) => effect.stacking = Some(stacking),
+                }
+                Ok(())
+            },
+        );
         methods.add_method_mut("set_squares_to_fire_on_moved", |_, effect, squares: u32| {
             match effect.kind {
                 Kind::Entity(_) => {
@@ -605,6 +659,17 @@ impl UserData for ScriptEffect {
             add_bonus_to_effect(effect, kind, when);
             Ok(())
         });
+        methods.add_method_mut(
+            "add_condition_immunity",
+            |_, effect, (id, when): (String, Option<String>)| {
+                if Module::condition(&id).is_none() {
+                    warn!("Attempted to add immunity to invalid condition '{}'", id);
+                    return Ok(());
+                }
+                add_bonus_to_effect(effect, BonusKind::ConditionImmunity(id), when);
+                Ok(())
+            },
+        );
         methods.add_method_mut("add_damage_of_kind", |_, effect, (min, max, kind, ap, when):
                                (f32, f32, String, Option<f32>, Option<String>)| {
             let min = min as u32;
@@ -675,6 +740,7 @@ fn add_bonus_to_effect(effect: &mut ScriptEffect, bonus_kind: BonusKind, when: O
                 "always" => Contingent::Always,
                 "attack_when_hidden" => Contingent::AttackWhenHidden,
                 "attack_when_flanking" => Contingent::AttackWhenFlanking,
+                "attack_from_higher_ground" => Contingent::AttackFromHigherGround,
                 "threatened" => Contingent::Threatened,
                 _ => {
                     warn!(
@@ -799,6 +865,8 @@ fn add_num_bonus(
         "will" => Will(amount_int),
         "concealment" => Concealment(amount_int),
         "concealment_ignore" => ConcealmentIgnore(amount_int),
+        "stealth" => Stealth(amount_int),
+        "perception" => Perception(amount_int),
         "crit_chance" => CritChance(amount_int),
         "hit_threshold" => HitThreshold(amount_int),
         "graze_threshold" => GrazeThreshold(amount_int),
@@ -820,6 +888,25 @@ fn add_num_bonus(
     Ok(())
 }
 
+/// Returns true if `entity` is immune to the specified condition, showing
+/// feedback text if so
+fn is_immune(entity: &Rc<RefCell<EntityState>>, condition: &Condition) -> bool {
+    if !entity.borrow().actor.stats.is_immune_to_condition(&condition.id) {
+        return false;
+    }
+
+    info!(
+        "'{}' is immune to condition '{}'",
+        entity.borrow().actor.actor.name,
+        condition.id
+    );
+    let area = GameState::area_state();
+    let mut text = AreaFeedbackText::with_target(&entity.borrow(), &area.borrow());
+    text.add_entry(format!("Immune to {}", condition.name), ColorKind::Miss);
+    area.borrow_mut().add_feedback_text(text);
+    true
+}
+
 fn apply(effect_data: &ScriptEffect) -> Result<()> {
     let mgr = GameState::turn_manager();
     let duration = effect_data.duration * ROUND_TIME_MILLIS;
@@ -828,15 +915,70 @@ fn apply(effect_data: &ScriptEffect) -> Result<()> {
         "Apply effect with {}, {}, {}",
         effect_data.name, effect_data.tag, duration
     );
+    let condition = effect_data.condition.as_ref().and_then(|id| Module::condition(id));
+    let stacking = match (effect_data.stacking, &condition) {
+        (Some(stacking), _) => stacking,
+        (None, Some(condition)) => condition.stacking,
+        (None, None) => ConditionStacking::Stack,
+    };
+
+    if let Kind::Entity(parent) = &effect_data.kind {
+        let entity = mgr.borrow().entity(*parent);
+        if let Some(condition) = &condition {
+            if is_immune(&entity, condition) {
+                return Ok(());
+            }
+        }
+
+        let mut mgr = mgr.borrow_mut();
+        let add = match &condition {
+            Some(condition) => {
+                let id = condition.id.as_str();
+                effect::apply_stacking(&mut mgr, &entity.borrow(), stacking, duration, |e| {
+                    e.condition() == Some(id)
+                })
+            }
+            None => {
+                let tag = effect_data.tag.as_str();
+                effect::apply_stacking(&mut mgr, &entity.borrow(), stacking, duration, |e| {
+                    e.tag == tag
+                })
+            }
+        };
+
+        if !add {
+            debug!("Effect '{}' not added due to stacking", effect_data.name);
+            return Ok(());
+        }
+    }
+
+    let mut bonuses = effect_data.bonuses.clone();
+    let mut tag = effect_data.tag.as_str();
+    if let Some(condition) = &condition {
+        for bonus in condition.bonuses.iter() {
+            bonuses.add(bonus.clone());
+        }
+        for id in condition.immunities.iter() {
+            bonuses.add_kind(BonusKind::ConditionImmunity(id.to_string()));
+        }
+        if tag == "default" {
+            tag = &condition.id;
+        }
+    }
+
     let mut effect = Effect::new(
         &effect_data.name,
-        &effect_data.tag,
+        tag,
         duration,
-        effect_data.bonuses.clone(),
+        bonuses,
         effect_data.deactivate_with_ability.clone(),
     );
     effect.ui_visible = effect_data.ui_visible;
-    if let Some(icon) = &effect_data.icon {
+    effect.set_stacking(stacking);
+    if let Some(condition) = &condition {
+        effect.set_condition(condition.id.to_string());
+        effect.set_icon(condition.icon.to_string(), condition.name.to_string());
+    } else if let Some(icon) = &effect_data.icon {
         effect.set_icon(icon.icon.clone(), icon.text.clone());
     }
     let cbs = effect_data.callbacks.clone();
@@ -23,8 +23,9 @@ use std::{self, f32, u32};
 use rlua::{self, Context, UserData, UserDataMethods};
 
 use crate::{ability_state::DisabledReason, dist, is_within_attack_dist, is_within_touch_dist};
-use crate::{ai, animation, entity_attack_handler, script::*, AreaFeedbackText};
+use crate::{ai, animation, detection, entity_attack_handler, script::*, AreaFeedbackText};
 use crate::{area_feedback_text::ColorKind, EntityState, GameState, Location};
+use crate::reaction::{ReactionTrigger, ReadiedReaction};
 use sulis_core::config::Config;
 use sulis_core::resource::ResourceSet;
 use sulis_core::util::ExtInt;
@@ -174,6 +175,31 @@ use sulis_module::{
 /// Returns true if this entity has one or more active effects with the specified tag,
 /// false otherwise.
 ///
+/// # `has_condition(id: String) -> Bool`
+/// Returns true if this entity has one or more active effects applying the `Condition`
+/// with the specified `id`, false otherwise.
+///
+/// # `conditions() -> Table`
+/// Returns an array-like table of the IDs of all conditions currently applied to this
+/// entity.
+///
+/// # `condition_ai_hint() -> String`
+/// Returns the most restrictive AI hint of all conditions currently applied to this
+/// entity.  One of `None`, `Flee`, or `SkipTurn`.
+///
+/// # `ai_group() -> ScriptAIGroup`
+/// Returns the AI group of the encounter this entity was spawned as part of,
+/// which holds the group's shared tactics state.  Returns nil if this entity
+/// is not part of an encounter.
+///
+/// # `awareness() -> String`
+/// Returns how aware this entity is of hidden hostiles around it.  One of
+/// `Unaware`, `Suspicious`, or `Alerted`.
+///
+/// # `make_noise(radius: Float)`
+/// Makes a noise originating from this entity.  Any entities hostile to this
+/// entity within the `radius` that are not already in combat become suspicious.
+///
 /// # `get_effects_with_tag(tag: String) -> Table of ScriptAppliedEffect`
 /// Returns an array-like table containing all of the effects currently applied to this
 /// entity with the specified tag.
@@ -364,6 +390,25 @@ use sulis_module::{
 /// Removes the specified `amount` of AP from this entity.  Keep in mind the `display_ap`
 /// factor that this amount is divided by for display purposes.
 ///
+/// # `ready_reaction(callback: CallbackData, trigger: String, ap: Int (Optional)) -> Bool`
+/// Readies a reaction for this entity until the start of its next turn, replacing any
+/// reaction already readied.  `trigger` is one of `LeaveThreat`, `EnterThreat`, `Move`, or
+/// `Ability`, describing the action by a hostile entity the reaction responds to.  When
+/// triggered, the `on_reaction` function of the `callback` is called, with the hostile
+/// entity as the target.  The specified `ap`, or `reaction_ap` from the rules if not
+/// specified, is reserved from this entity's current AP, and returned as overflow AP if the
+/// reaction does not fire.  Returns false if the reaction could not be readied, because
+/// combat is not active or this entity does not have enough AP.
+/// ## Examples
+/// ```lua
+///   local cb = ability:create_callback(parent)
+///   cb:set_on_reaction_fn("on_reaction")
+///   parent:ready_reaction(cb, "EnterThreat")
+/// ```
+///
+/// # `has_readied_reaction() -> Bool`
+/// Returns true if this entity currently has a reaction readied, false otherwise.
+///
 /// # `base_class() -> String`
 /// Returns the ID of the base class of this entity, or the class that this entity took at
 /// level 1.
@@ -404,7 +449,8 @@ use sulis_module::{
 ///
 /// # `stats() -> Table`
 /// Creates and returns a stats table for this entity.  This includes all stats shown on the
-/// character sheet.
+/// character sheet.  Each attribute is included by its lowercase name, so `perception` is the
+/// Perception attribute.  The stealth detection stats are `stealth` and `perception_check`.
 ///
 /// # `inventory() -> ScriptInventory`
 /// Returns a `ScriptInventory` object representing this entity's inventory.
@@ -878,6 +924,52 @@ impl UserData for ScriptEntity {
             Ok(false)
         });
 
+        methods.add_method("has_condition", |_, entity, id: String| {
+            let entity = entity.try_unwrap()?;
+            let mgr = GameState::turn_manager();
+            let conditions = entity.borrow().conditions(&mgr.borrow());
+            Ok(conditions.iter().any(|c| c.id == id))
+        });
+
+        methods.add_method("conditions", |_, entity, ()| {
+            let entity = entity.try_unwrap()?;
+            let mgr = GameState::turn_manager();
+            let conditions = entity.borrow().conditions(&mgr.borrow());
+            let ids: Vec<String> = conditions.iter().map(|c| c.id.to_string()).collect();
+            Ok(ids)
+        });
+
+        methods.add_method("condition_ai_hint", |_, entity, ()| {
+            let entity = entity.try_unwrap()?;
+            let mgr = GameState::turn_manager();
+            let hint = entity.borrow().condition_ai_hint(&mgr.borrow());
+            Ok(format!("{:?}", hint))
+        });
+
+        methods.add_method("ai_group", |_, entity, ()| {
+            let entity = entity.try_unwrap()?;
+            let group = match entity.borrow().ai_group() {
+                None => return Ok(None),
+                Some(group) => group,
+            };
+
+            let mgr = GameState::turn_manager();
+            let group = mgr.borrow_mut().ai_group(group);
+            Ok(group.map(|group| ScriptAIGroup::new(group.index())))
+        });
+
+        methods.add_method("awareness", |_, entity, ()| {
+            let entity = entity.try_unwrap()?;
+            let awareness = entity.borrow().awareness();
+            Ok(awareness.to_string())
+        });
+
+        methods.add_method("make_noise", |_, entity, radius: f32| {
+            let entity = entity.try_unwrap()?;
+            detection::make_noise(&entity, radius);
+            Ok(())
+        });
+
         methods.add_method("remove_effects_with_tag", |_, entity, tag: String| {
             let entity = entity.try_unwrap()?;
             let entity = entity.borrow();
@@ -1063,7 +1155,7 @@ impl UserData for ScriptEntity {
             |_, entity, target: ScriptEntity| {
                 let parent = entity.try_unwrap()?;
                 let target = target.try_unwrap()?;
-                let result = is_within_attack_dist(&*parent.borrow(), &*target.borrow());
+                let result = is_within_attack_dist(&parent.borrow(), &target.borrow());
                 Ok(result)
             },
         );
@@ -1222,7 +1314,7 @@ impl UserData for ScriptEntity {
                 let parent = entity.try_unwrap()?;
 
                 let damage_kind = match damage_kind {
-                    None => DamageKind::Raw,
+                    None => DamageKind::raw(),
                     Some(ref kind) => DamageKind::unwrap_from_str(kind),
                 };
                 let attack_kind = AttackKind::from_str(&attack_kind, &accuracy_kind);
@@ -1293,7 +1385,9 @@ impl UserData for ScriptEntity {
                         AttackKind::Dummy,
                     );
                     let damage = &attack.damage;
-                    rules.roll_damage(damage, &parent.armor, &parent.resistance, 1.0)
+                    GameState::with_combat_rand(|rand| {
+                        rules.roll_damage(damage, &parent.armor, &parent.resistance, 1.0, rand)
+                    })
                 };
 
                 if !damage.is_empty() {
@@ -1388,6 +1482,47 @@ impl UserData for ScriptEntity {
             Ok(())
         });
 
+        methods.add_method(
+            "ready_reaction",
+            |_, entity, (cb, trigger, ap): (CallbackData, String, Option<u32>)| {
+                let entity = entity.try_unwrap()?;
+                let trigger = match ReactionTrigger::option_from_str(&trigger) {
+                    None => {
+                        warn!("Invalid reaction trigger '{}' in script", trigger);
+                        return Ok(false);
+                    }
+                    Some(trigger) => trigger,
+                };
+
+                let mgr = GameState::turn_manager();
+                if !mgr.borrow().is_combat_active() {
+                    return Ok(false);
+                }
+
+                let ap = ap.unwrap_or(Module::rules().reaction_ap);
+                if entity.borrow().actor.ap() < ap {
+                    return Ok(false);
+                }
+                entity.borrow_mut().actor.remove_ap(ap);
+
+                let index = entity.borrow().index();
+                let cb = Rc::new(cb);
+                let reaction = ReadiedReaction { trigger, ap, cb };
+                let replaced = mgr.borrow_mut().ready_reaction(index, reaction);
+                if let Some(replaced) = replaced {
+                    entity.borrow_mut().actor.add_ap(replaced.ap);
+                }
+                Ok(true)
+            },
+        );
+
+        methods.add_method("has_readied_reaction", |_, entity, ()| {
+            let index = entity.try_unwrap_index()?;
+            let mgr = GameState::turn_manager();
+            let readied = mgr.borrow().readied_reaction(index).is_some();
+            Ok(readied)
+        });
+
         methods.add_method("base_class", |_, entity, ()| {
             let entity = entity.try_unwrap()?;
             let entity = entity.borrow();
@@ -1690,51 +1825,25 @@ fn create_stats_table<'a>(
     stats.set("current_ap", parent.actor.ap())?;
     stats.set("current_xp", parent.actor.xp())?;
 
-    stats.set("strength", src.attributes.strength)?;
-    stats.set("dexterity", src.attributes.dexterity)?;
-    stats.set("endurance", src.attributes.endurance)?;
-    stats.set("perception", src.attributes.perception)?;
-    stats.set("intellect", src.attributes.intellect)?;
-    stats.set("wisdom", src.attributes.wisdom)?;
-
-    {
-        use self::Attribute::*;
-        stats.set(
-            "strength_bonus",
-            src.attributes.bonus(Strength, rules.base_attribute),
-        )?;
-        stats.set(
-            "dexterity_bonus",
-            src.attributes.bonus(Dexterity, rules.base_attribute),
-        )?;
-        stats.set(
-            "endurance_bonus",
-            src.attributes.bonus(Endurance, rules.base_attribute),
-        )?;
-        stats.set(
-            "perception_bonus",
-            src.attributes.bonus(Perception, rules.base_attribute),
-        )?;
-        stats.set(
-            "intellect_bonus",
-            src.attributes.bonus(Intellect, rules.base_attribute),
-        )?;
+    for attr in Attribute::iter() {
+        let id = attr.id().to_lowercase();
+        stats.set(id.as_str(), src.attributes.get(attr))?;
         stats.set(
-            "wisdom_bonus",
-            src.attributes.bonus(Wisdom, rules.base_attribute),
+            format!("{}_bonus", id),
+            src.attributes.bonus(attr, rules.base_attribute),
         )?;
     }
 
     stats.set("base_armor", src.armor.base())?;
     let armor = lua.create_table()?;
     for kind in DamageKind::iter() {
-        armor.set(kind.to_str(), src.armor.amount(*kind))?;
+        armor.set(kind.id(), src.armor.amount(kind))?;
     }
     stats.set("armor", armor)?;
 
     let resistance = lua.create_table()?;
     for kind in DamageKind::iter() {
-        resistance.set(kind.to_str(), src.resistance.amount(*kind))?;
+        resistance.set(kind.id(), src.resistance.amount(kind))?;
     }
     stats.set("resistance", resistance)?;
 
@@ -1759,6 +1868,8 @@ fn create_stats_table<'a>(
 
     stats.set("concealment", src.concealment)?;
     stats.set("concealment_ignore", src.concealment_ignore)?;
+    stats.set("stealth", src.stealth)?;
+    stats.set("perception_check", src.perception)?;
     stats.set("crit_chance", src.crit_chance)?;
     stats.set("graze_threshold", src.graze_threshold)?;
     stats.set("hit_threshold", src.hit_threshold)?;
@@ -21,6 +21,7 @@ use rlua::{self, UserData, UserDataMethods};
 
 use crate::script::*;
 use crate::{animation::Anim, AreaState, EntityState, GameState, Location};
+use crate::{AIDecision, AIDecisionTrace};
 use sulis_core::{config::Config};
 use sulis_module::on_trigger::{self, QuestEntryState};
 use sulis_module::{Faction, ItemState, Module, OnTrigger, Time};
@@ -139,7 +140,7 @@ use sulis_module::{Faction, ItemState, Module, OnTrigger, Time};
 /// # `init_party_day()`
 /// Starts a new day for the player character and party.  This resets all skill
 /// uses and sets maximum hit points.  This is normally used in a script when the
-/// party rests.
+/// party rests, and so also writes an autosave once the script completes.
 ///
 /// # `create_menu_selection(value: String)`
 /// Creates a ScriptMenuSelection object with the specified value.  Useful to
@@ -168,6 +169,14 @@ use sulis_module::{Faction, ItemState, Module, OnTrigger, Time};
 /// # `trace(message: String)`
 /// Logs the specified string to game output at trace level.
 ///
+/// # `record_ai_decision(action: String, target: ScriptEntity (Optional),
+/// score: Float (Optional), priority: Int (Optional), chosen: Bool (Optional))`
+/// Records an action considered by the AI script currently running, for display
+/// in the AI decision debugger.  `score` is typically the `compute_weight` of the
+/// target, and `priority` the ai priority of an ability.  `chosen` should be true
+/// if the action was actually taken.  Does nothing unless the `show_ai_decisions`
+/// debug option is enabled.
+///
 /// # `ap_display_factor() -> Int`
 /// Gets the ap display factor, which is the factor that the internal AP representation is
 /// divided by when displayed.  Any AP values that are displayed to the user must be
@@ -497,6 +506,7 @@ impl UserData for ScriptInterface {
             for member in GameState::party() {
                 member.borrow_mut().actor.init_day();
             }
+            GameState::request_autosave();
             Ok(())
         });
 
@@ -539,6 +549,24 @@ impl UserData for ScriptInterface {
             Ok(())
         });
 
+        methods.add_method("record_ai_decision", |_, _, (action, target, score, priority, chosen):
+            (String, Option<ScriptEntity>, Option<f32>, Option<u32>, Option<bool>)| {
+            if !AIDecisionTrace::is_enabled() {
+                return Ok(());
+            }
+
+            let target = match target {
+                None => None,
+                Some(target) => Some(target.try_unwrap()?),
+            };
+            let target = target.as_ref().map(|t| t.borrow());
+
+            let chosen = chosen.unwrap_or(false);
+            let decision = AIDecision::new(&action, target.as_deref(), score, priority, chosen);
+            GameState::ai_trace().borrow_mut().add(decision);
+            Ok(())
+        });
+
         methods.add_method("debug", |_, _, val: String| {
             debug!("[LUA]: {}", val);
             Ok(())
@@ -77,6 +77,8 @@ pub(crate) fn transition_to(area_id: Option<&str>, p: Option<Point>, offset: Poi
     let pc = GameState::player();
     area.borrow_mut().push_scroll_to_callback(Rc::clone(&pc));
 
+    GameState::request_autosave();
+
     let mut area = area.borrow_mut();
 
     area.update_view_visibility();
@@ -18,10 +18,13 @@ use std::cell::{Cell, RefCell};
 use std::collections::{vec_deque::Iter, HashMap, HashSet, VecDeque};
 use std::rc::Rc;
 
+use crate::reaction::{Reaction, ReactionKind, ReactionTrigger, ReadiedReaction};
 use crate::script::{CallbackData, FuncKind, TriggeredCallback};
-use crate::{AreaState, ChangeListener, ChangeListenerList, Effect, EntityState, GameState};
-use sulis_core::{config::Config, util::{gen_rand, Point}};
-use sulis_module::{Faction, Module, Time, ROUND_TIME_MILLIS, OnTrigger};
+use crate::{
+    AIGroupState, AreaState, ChangeListener, ChangeListenerList, Effect, EntityState, GameState,
+};
+use sulis_core::{config::Config, util::Point};
+use sulis_module::{Faction, GroupTactics, Module, Time, ROUND_TIME_MILLIS, OnTrigger};
 
 fn add_campaign_elapsed_callback(cbs: &mut Vec<Rc<CallbackData>>) {
     let script_data = match Module::campaign().on_round_elapsed_script {
@@ -61,12 +64,20 @@ pub struct TurnManager {
     triggered_cbs_next_update: Vec<TriggeredCallback>,
     combat_active: bool,
 
+    readied_reactions: HashMap<usize, ReadiedReaction>,
+    reactions_used: HashMap<usize, u32>,
+    reaction_prompts: VecDeque<Reaction>,
+    reaction_prompt_shown: bool,
+    reactions_next_update: Vec<Reaction>,
+    interrupted_moves: HashSet<usize>,
+
     pub time_listeners: ChangeListenerList<Time>,
     pub listeners: ChangeListenerList<TurnManager>,
     order: VecDeque<Entry>,
 
     pub(crate) ai_groups: HashMap<usize, EncounterRef>,
     pub(crate) cur_ai_group_index: usize,
+    ai_group_states: HashMap<usize, AIGroupState>,
 
     total_elapsed_millis: usize,
 }
@@ -85,8 +96,15 @@ impl Default for TurnManager {
             time_listeners: ChangeListenerList::default(),
             order: VecDeque::new(),
             combat_active: false,
+            readied_reactions: HashMap::new(),
+            reactions_used: HashMap::new(),
+            reaction_prompts: VecDeque::new(),
+            reaction_prompt_shown: false,
+            reactions_next_update: Vec::new(),
+            interrupted_moves: HashSet::new(),
             ai_groups: HashMap::new(),
             cur_ai_group_index: 0,
+            ai_group_states: HashMap::new(),
             total_elapsed_millis: 0,
         }
     }
@@ -157,6 +175,7 @@ impl TurnManager {
         self.order.clear();
         self.cur_ai_group_index = 0;
         self.ai_groups.clear();
+        self.ai_group_states.clear();
         self.total_elapsed_millis = total_elapsed_millis;
     }
 
@@ -242,6 +261,95 @@ impl TurnManager {
         value
     }
 
+    /// Returns the current shared state of the specified AI group, or
+    /// `None` if there is no such group.  Any focus target that has died is
+    /// cleared, and the group will begin retreating if it has taken enough
+    /// damage
+    pub fn ai_group(&mut self, index: usize) -> Option<AIGroupState> {
+        if !self.ai_group_states.contains_key(&index) {
+            let tactics = self.ai_group_tactics(index)?;
+            self.ai_group_states.insert(index, AIGroupState::new(index, tactics));
+        }
+        let members = self.ai_group_members(index);
+
+        let focus = self.ai_group_states.get(&index).and_then(|g| g.focus_target());
+        let focus_dead = match focus {
+            None => false,
+            Some(target) => match self.entity_checked(target) {
+                None => true,
+                Some(target) => target.borrow().actor.is_dead(),
+            },
+        };
+
+        let group = self.ai_group_states.get_mut(&index)?;
+        if focus_dead {
+            group.set_focus_target(None);
+        }
+        group.check_retreat(&members);
+        Some(group.clone())
+    }
+
+    pub(crate) fn ai_group_states(&self) -> impl Iterator<Item = &AIGroupState> {
+        self.ai_group_states.values()
+    }
+
+    /// Restores the saved state of the specified AI group.  The group's
+    /// tactics are read from its encounter in `areas`, as the areas are not
+    /// yet part of the game state while loading
+    pub(crate) fn load_ai_group_state(
+        &mut self,
+        index: usize,
+        focus_target: Option<usize>,
+        retreating: bool,
+        areas: &HashMap<String, Rc<RefCell<AreaState>>>,
+    ) {
+        let tactics = self.ai_groups.get(&index).and_then(|enc_ref| {
+            let area_state = areas.get(&enc_ref.area_id)?.borrow();
+            let enc_data = area_state.area.encounters.get(enc_ref.encounter_index)?;
+            Some(enc_data.encounter.tactics)
+        });
+
+        let tactics = match tactics {
+            None => {
+                warn!("Unable to load state for invalid AI group {}", index);
+                return;
+            }
+            Some(tactics) => tactics,
+        };
+
+        let mut group = AIGroupState::new(index, tactics);
+        group.set_focus_target(focus_target);
+        group.set_retreating(retreating);
+        self.ai_group_states.insert(index, group);
+    }
+
+    pub fn set_ai_group_focus_target(&mut self, index: usize, target: Option<usize>) {
+        if let Some(group) = self.ai_group_states.get_mut(&index) {
+            group.set_focus_target(target);
+        }
+    }
+
+    pub fn set_ai_group_retreating(&mut self, index: usize, retreating: bool) {
+        if let Some(group) = self.ai_group_states.get_mut(&index) {
+            group.set_retreating(retreating);
+        }
+    }
+
+    /// Returns all entities in the specified AI group, including dead ones
+    pub fn ai_group_members(&self, index: usize) -> Vec<Rc<RefCell<EntityState>>> {
+        self.entity_iter()
+            .filter(|e| e.borrow().ai_group() == Some(index))
+            .collect()
+    }
+
+    fn ai_group_tactics(&self, index: usize) -> Option<GroupTactics> {
+        let enc_ref = self.ai_groups.get(&index)?;
+        let area_state = GameState::get_area_state(&enc_ref.area_id)?;
+        let area_state = area_state.try_borrow().ok()?;
+        let enc_data = area_state.area.encounters.get(enc_ref.encounter_index)?;
+        Some(enc_data.encounter.tactics)
+    }
+
     pub fn entity_checked(&self, index: usize) -> Option<Rc<RefCell<EntityState>>> {
         if index >= self.entities.len() {
             return None;
@@ -428,6 +536,11 @@ impl TurnManager {
         }
 
         let mut current = current.borrow_mut();
+        let index = current.index();
+        self.reactions_used.remove(&index);
+        if let Some(readied) = self.readied_reactions.remove(&index) {
+            current.actor.change_overflow_ap(readied.ap as i32);
+        }
         current.actor.init_turn();
         current.actor.elapse_time(ROUND_TIME_MILLIS, &self.effects);
 
@@ -522,19 +635,19 @@ impl TurnManager {
         }
 
         let mut groups_to_activate: HashSet<usize> = HashSet::new();
-        let mut state_changed = false;
+        let mut to_activate = Vec::new();
 
-        for entity in self.entities.iter() {
-            let entity = match entity {
+        for entity_ref in self.entities.iter() {
+            let entity_ref = match entity_ref {
                 None => continue,
                 Some(ref entity) => entity,
             };
 
-            if Rc::ptr_eq(mover, entity) {
+            if Rc::ptr_eq(mover, entity_ref) {
                 continue;
             }
 
-            let mut entity = entity.borrow_mut();
+            let entity = entity_ref.borrow();
             if entity.actor.is_dead() {
                 continue;
             }
@@ -555,14 +668,20 @@ impl TurnManager {
                 continue;
             }
 
-            self.activate_entity_ai(&mut entity, &mut groups_to_activate);
-            state_changed = true;
+            to_activate.push(Rc::clone(entity_ref));
         }
 
-        if !state_changed {
+        if to_activate.is_empty() {
             return;
         }
 
+        if !self.combat_active {
+            GameState::autosave_before_combat(self, area_state);
+        }
+
+        for entity in to_activate {
+            self.activate_entity_ai(&mut entity.borrow_mut(), &mut groups_to_activate);
+        }
         self.activate_entity_ai(&mut mover.borrow_mut(), &mut groups_to_activate);
 
         for entity in self.entities.iter() {
@@ -654,6 +773,7 @@ impl TurnManager {
         if !active {
             self.end_combat();
         } else {
+            GameState::autosave_on_combat_start(self);
             self.initiate_combat();
         }
     }
@@ -720,6 +840,12 @@ impl TurnManager {
             }
         }
 
+        self.readied_reactions.clear();
+        self.reactions_used.clear();
+        self.reaction_prompts.clear();
+        self.reactions_next_update.clear();
+        self.interrupted_moves.clear();
+
         self.add_millis(ROUND_TIME_MILLIS);
 
         if GameState::selected().is_empty() {
@@ -748,7 +874,8 @@ impl TurnManager {
                         .actor
                         .stats
                         .initiative;
-                    last_initiative = base + gen_rand(0, initiative_roll_max);
+                    let roll = GameState::with_combat_rand(|rand| rand.gen(0, initiative_roll_max));
+                    last_initiative = base + roll;
                     initiative[index] = 2 * last_initiative;
                 }
                 Entry::Effect(_) => {
@@ -782,6 +909,161 @@ impl TurnManager {
         GameState::set_clear_anims();
     }
 
+    /// Readies the reaction for the entity with the specified index, until
+    /// the start of its next turn.  Any reaction the entity already had
+    /// readied is replaced and returned.
+    pub fn ready_reaction(
+        &mut self,
+        index: usize,
+        reaction: ReadiedReaction,
+    ) -> Option<ReadiedReaction> {
+        self.readied_reactions.insert(index, reaction)
+    }
+
+    pub fn readied_reaction(&self, index: usize) -> Option<&ReadiedReaction> {
+        self.readied_reactions.get(&index)
+    }
+
+    pub(crate) fn readied_reactions(&self) -> impl Iterator<Item = (usize, &ReadiedReaction)> {
+        self.readied_reactions.iter().map(|(index, reaction)| (*index, reaction))
+    }
+
+    /// Returns the indices of all entities with a readied reaction to the
+    /// specified trigger
+    pub fn readied_reactors(&self, trigger: ReactionTrigger) -> Vec<usize> {
+        self.readied_reactions
+            .iter()
+            .filter(|(_, reaction)| reaction.trigger == trigger)
+            .map(|(index, _)| *index)
+            .collect()
+    }
+
+    /// Returns true if a reaction is waiting for the player to accept or
+    /// decline it.  The AI waits while this is the case.
+    pub fn has_reaction_prompt(&self) -> bool {
+        self.reaction_prompt_shown || !self.reaction_prompts.is_empty()
+    }
+
+    /// Returns the next reaction to prompt the player with, if any.  The
+    /// prompt is considered shown until `close_reaction_prompt` is called.
+    pub fn pop_reaction_prompt(&mut self) -> Option<Reaction> {
+        let reaction = self.reaction_prompts.pop_front();
+        self.reaction_prompt_shown = reaction.is_some();
+        reaction
+    }
+
+    /// Called once the player has accepted or declined the shown reaction
+    pub fn close_reaction_prompt(&mut self) {
+        self.reaction_prompt_shown = false;
+    }
+
+    /// Interrupts the current move of the entity with the specified index,
+    /// the next time its move animation is updated
+    pub(crate) fn interrupt_move(&mut self, index: usize) {
+        self.interrupted_moves.insert(index);
+    }
+
+    pub(crate) fn take_move_interrupt(&mut self, index: usize) -> bool {
+        self.interrupted_moves.remove(&index)
+    }
+
+    pub(crate) fn add_reaction_prompt(&mut self, reaction: Reaction) {
+        self.reaction_prompts.push_back(reaction);
+    }
+
+    pub(crate) fn fire_reaction_next_update(&mut self, reaction: Reaction) {
+        self.reactions_next_update.push(reaction);
+    }
+
+    pub(crate) fn drain_reactions(&mut self) -> Vec<Reaction> {
+        self.reactions_next_update.drain(..).collect()
+    }
+
+    /// Finds the reactions of each of the `candidates` to an action by the
+    /// `target`.  Each entity may react a limited number of times between
+    /// its turns, and only once to any one action.  The reactions found are
+    /// counted against this limit, whether or not they are accepted.
+    #[must_use]
+    pub(crate) fn find_reactions(
+        &mut self,
+        target: &Rc<RefCell<EntityState>>,
+        candidates: Vec<(usize, ReactionTrigger)>,
+    ) -> Vec<Reaction> {
+        let mut reactions: Vec<Reaction> = Vec::new();
+        if !self.combat_active {
+            return reactions;
+        }
+
+        let rules = Module::rules();
+        let current = self.current();
+        for (index, trigger) in candidates {
+            if reactions.iter().any(|r| r.reactor.borrow().index() == index) {
+                continue;
+            }
+
+            let reactor = match self.entity_checked(index) {
+                None => continue,
+                Some(reactor) => reactor,
+            };
+
+            if let Some(current) = current.as_ref() {
+                if Rc::ptr_eq(current, &reactor) {
+                    continue;
+                }
+            }
+
+            {
+                let reactor = reactor.borrow();
+                if reactor.actor.is_dead() || reactor.actor.is_disabled() {
+                    continue;
+                }
+
+                if !reactor.is_hostile(&target.borrow()) {
+                    continue;
+                }
+            }
+
+            let used = self.reactions_used.get(&index).copied().unwrap_or(0);
+            if used >= rules.reactions_per_round {
+                continue;
+            }
+
+            let readied = match self.readied_reactions.get(&index) {
+                None => false,
+                Some(readied) => readied.trigger == trigger,
+            };
+
+            let kind = if readied {
+                ReactionKind::Readied(self.readied_reactions.remove(&index).unwrap())
+            } else {
+                if trigger != ReactionTrigger::LeaveThreat || !rules.attacks_of_opportunity {
+                    continue;
+                }
+
+                let reactor = reactor.borrow();
+                if reactor.actor.overflow_ap() < rules.reaction_ap as i32 {
+                    continue;
+                }
+
+                if reactor.actor.stats.attack_disabled || target.borrow().actor.stats.hidden {
+                    continue;
+                }
+
+                ReactionKind::AttackOfOpportunity
+            };
+
+            self.reactions_used.insert(index, used + 1);
+            reactions.push(Reaction {
+                reactor,
+                target: Rc::clone(target),
+                trigger,
+                kind,
+            });
+        }
+
+        reactions
+    }
+
     pub(crate) fn fire_on_moved_next_update(&mut self, entity_index: usize) {
         self.entities_move_callback_next_update.insert(entity_index);
     }
@@ -1046,6 +1328,8 @@ impl TurnManager {
             self.remove_from_surface(index, *surface);
         }
 
+        self.readied_reactions.remove(&index);
+
         let cur_hp = entity.borrow().actor.hp();
         if cur_hp > 0 {
             // don't want all the entity checks, just to set the value
@@ -25,7 +25,7 @@ use sulis_module::{
     area::{Destination, ToKind},
     Faction, Module, ObjectSize, OnTrigger, Time, MOVE_TO_THRESHOLD,
 };
-use sulis_state::{can_attack, is_within};
+use sulis_state::{attack_distance, can_attack, is_within};
 use sulis_state::{AreaState, EntityState, GameState, PropState, ScriptCallback};
 
 pub fn get_action(x_f32: f32, y_f32: f32) -> Box<dyn ActionKind> {
@@ -583,7 +583,7 @@ impl AttackAction {
                 &pc,
                 target.borrow().location.to_point(),
                 &target.borrow().size,
-                pc.borrow().actor.stats.attack_distance(),
+                attack_distance(&pc.borrow(), &target.borrow()),
                 cb_action,
                 animation_state::Kind::MouseAttack,
             )
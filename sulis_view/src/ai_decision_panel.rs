@@ -0,0 +1,85 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sulis_core::ui::{Widget, WidgetKind};
+use sulis_core::widgets::{Label, ScrollDirection, ScrollPane, TextArea};
+use sulis_state::{AIDecision, ChangeListener, GameState};
+
+pub const NAME: &str = "ai_decision_panel";
+
+/// A side panel listing each action considered during the most recent AI
+/// turn.  Only shown when the `show_ai_decisions` debug option is enabled
+pub struct AIDecisionPanel {}
+
+impl AIDecisionPanel {
+    pub fn new() -> Rc<RefCell<AIDecisionPanel>> {
+        Rc::new(RefCell::new(AIDecisionPanel {}))
+    }
+}
+
+impl WidgetKind for AIDecisionPanel {
+    widget_kind!(NAME);
+
+    fn on_add(&mut self, widget: &Rc<RefCell<Widget>>) -> Vec<Rc<RefCell<Widget>>> {
+        let trace = GameState::ai_trace();
+        trace
+            .borrow_mut()
+            .listeners
+            .add(ChangeListener::invalidate(NAME, widget));
+
+        let trace = trace.borrow();
+        let title = Widget::with_theme(Label::empty(), "title");
+        title
+            .borrow_mut()
+            .state
+            .add_text_arg("name", trace.entity_name());
+
+        let entries_pane = ScrollPane::new(ScrollDirection::Vertical);
+        let entries = Widget::with_theme(entries_pane.clone(), "entries");
+
+        for decision in trace.decisions().rev() {
+            let widget = Widget::with_theme(TextArea::empty(), "entry");
+            add_decision_text_args(&mut widget.borrow_mut(), decision);
+            entries_pane.borrow().add_to_content(widget);
+        }
+
+        vec![title, entries]
+    }
+}
+
+fn add_decision_text_args(widget: &mut Widget, decision: &AIDecision) {
+    let state = &mut widget.state;
+
+    state.add_text_arg("step", &decision.step.to_string());
+    state.add_text_arg("action", &decision.action);
+
+    if let Some(target) = &decision.target_name {
+        state.add_text_arg("target", target);
+    }
+    if let Some(score) = decision.score {
+        state.add_text_arg("score", &format!("{:.2}", score));
+    }
+    if let Some(priority) = decision.priority {
+        state.add_text_arg("priority", &priority.to_string());
+    }
+    if decision.chosen {
+        state.add_text_arg("chosen", "true");
+    }
+}
@@ -22,7 +22,8 @@ use sulis_core::io::{event::ClickKind, GraphicsRenderer};
 use sulis_core::ui::{Widget, WidgetKind, WidgetState};
 use sulis_core::util::Point;
 use sulis_core::widgets::TextArea;
-use sulis_state::{ChangeListener, EntityState, GameState};
+use sulis_module::Cover;
+use sulis_state::{predict_weapon_attack, Awareness, ChangeListener, EntityState, GameState};
 
 const NAME: &str = "area_mouseover";
 
@@ -81,10 +82,24 @@ impl AreaMouseover {
 
         match self.kind {
             Kind::Entity(ref entity) => {
-                let actor = &entity.borrow().actor;
+                let entity = entity.borrow();
+                let actor = &entity.actor;
                 state.add_text_arg("name", &actor.actor.name);
                 state.add_text_arg("cur_hp", &actor.hp().to_string());
                 state.add_text_arg("max_hp", &actor.stats.max_hp.to_string());
+
+                let mgr = GameState::turn_manager();
+                let conditions = entity.conditions(&mgr.borrow());
+                if !conditions.is_empty() {
+                    let names: Vec<&str> = conditions.iter().map(|c| c.name.as_str()).collect();
+                    state.add_text_arg("conditions", &names.join(", "));
+                }
+
+                let awareness = entity.awareness();
+                if !entity.is_party_member() && awareness != Awareness::Unaware {
+                    state.add_text_arg("awareness", &awareness.to_string());
+                }
+                add_attack_prediction_text_args(state, &entity);
             }
             Kind::Prop(index) => {
                 let area_state = GameState::area_state();
@@ -114,6 +129,62 @@ impl AreaMouseover {
     }
 }
 
+/// Adds the odds for the selected party member's primary weapon attack
+/// against `target`, if it is a valid attack target
+fn add_attack_prediction_text_args(state: &mut WidgetState, target: &EntityState) {
+    let pc = match GameState::selected().into_iter().next() {
+        None => return,
+        Some(pc) => pc,
+    };
+    let pc = pc.borrow();
+
+    if !pc.is_hostile(target) || target.actor.hp() <= 0 || pc.actor.stats.attack_disabled {
+        return;
+    }
+
+    let predictions = predict_weapon_attack(&pc, target);
+    let prediction = match predictions.first() {
+        None => return,
+        Some(prediction) => prediction,
+    };
+
+    let percent = |chance: f32| format!("{:.0}", chance * 100.0);
+    let chances = &prediction.chances;
+    state.add_text_arg("miss_chance", &percent(chances.miss));
+    state.add_text_arg("graze_chance", &percent(chances.graze));
+    state.add_text_arg("hit_chance", &percent(chances.hit));
+    state.add_text_arg("crit_chance", &percent(chances.crit));
+    state.add_text_arg("graze_min", &prediction.graze_damage.0.to_string());
+    state.add_text_arg("graze_max", &prediction.graze_damage.1.to_string());
+    state.add_text_arg("hit_min", &prediction.hit_damage.0.to_string());
+    state.add_text_arg("hit_max", &prediction.hit_damage.1.to_string());
+    state.add_text_arg("crit_min", &prediction.crit_damage.0.to_string());
+    state.add_text_arg("crit_max", &prediction.crit_damage.1.to_string());
+
+    if predictions.len() > 1 {
+        state.add_text_arg("num_attacks", &predictions.len().to_string());
+    }
+    if prediction.flanking {
+        state.add_text_arg("flanking", "true");
+    }
+    if prediction.sneak_attack {
+        state.add_text_arg("sneak_attack", "true");
+    }
+    match prediction.cover {
+        Cover::None => (),
+        Cover::Partial => state.add_text_arg("partial_cover", "true"),
+        Cover::Full => state.add_text_arg("full_cover", "true"),
+    }
+    if prediction.elevation > 0 {
+        state.add_text_arg("higher_ground", "true");
+    } else if prediction.elevation < 0 {
+        state.add_text_arg("lower_ground", "true");
+    }
+    if prediction.concealment > 0 {
+        state.add_text_arg("concealment", &prediction.concealment.to_string());
+    }
+}
+
 impl WidgetKind for AreaMouseover {
     widget_kind!(NAME);
 
@@ -15,16 +15,18 @@
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
 use crate::{action_kind, AreaMouseover};
 use sulis_core::image::Image;
 use sulis_core::io::{DrawList, GraphicsRenderer};
 use sulis_core::resource::{ResourceSet, Sprite};
-use sulis_core::ui::{animation_state, Cursor, LineRenderer, Theme, Widget};
+use sulis_core::ui::{animation_state, color, Cursor, LineRenderer, Theme, Widget};
 use sulis_core::util::{Offset, Rect, Scale};
 use sulis_module::Module;
-use sulis_state::{area_feedback_text::Params, AreaState, EntityState, GameState};
+use sulis_state::{area_feedback_text::Params, AIDecisionTrace, AreaState, EntityState, GameState};
+use sulis_state::Awareness;
 
 pub struct HoverSprite {
     pub sprite: Rc<Sprite>,
@@ -363,6 +365,147 @@ impl AreaOverlayHandler {
         Some(draw_list)
     }
 
+    /// Returns the paths moved along during the most recent AI turn, if
+    /// the `show_ai_decisions` debug option is enabled
+    pub fn get_ai_path_draw_list(&self, offset: Offset, millis: u32) -> Option<DrawList> {
+        if !AIDecisionTrace::is_enabled() {
+            return None;
+        }
+
+        let image = match self.path_point_image {
+            None => return None,
+            Some(ref image) => image,
+        };
+
+        let trace = GameState::ai_trace();
+        let trace = trace.borrow();
+        let entity = GameState::turn_manager().borrow().entity_checked(trace.entity()?)?;
+
+        let (w, h) = {
+            let size = &entity.borrow().size;
+            (size.width as f32, size.height as f32)
+        };
+
+        let mut draw_list = DrawList::empty_sprite();
+        for path in trace.paths() {
+            for p in path.iter().skip(1) {
+                let rect = Rect {
+                    x: p.x as f32 + (w - 1.0) / 2.0 - offset.x,
+                    y: p.y as f32 + (h - 1.0) / 2.0 - offset.y,
+                    w: 1.0,
+                    h: 1.0,
+                };
+                image.append_to_draw_list(&mut draw_list, &animation_state::NORMAL, rect, millis);
+            }
+        }
+
+        if draw_list.is_empty() {
+            None
+        } else {
+            Some(draw_list)
+        }
+    }
+
+    /// Labels each target considered in the latest step of the most recent
+    /// AI turn with the action and its score.  Chosen actions are highlighted
+    fn draw_ai_decisions(
+        &self,
+        renderer: &mut dyn GraphicsRenderer,
+        params: &Params,
+        offset: Offset,
+        scale: Scale,
+    ) {
+        let trace = GameState::ai_trace();
+        let trace = trace.borrow();
+        let step = match trace.decisions().map(|d| d.step).max() {
+            None => return,
+            Some(step) => step,
+        };
+
+        let mgr = GameState::turn_manager();
+        let font_rend = LineRenderer::new(&params.font);
+        let line_height = params.ap_scale;
+
+        let mut lines: HashMap<usize, f32> = HashMap::new();
+        for decision in trace.decisions().filter(|d| d.step == step) {
+            let index = match decision.target {
+                None => continue,
+                Some(index) => index,
+            };
+
+            let target = match mgr.borrow().entity_checked(index) {
+                None => continue,
+                Some(target) => target,
+            };
+
+            let text = match decision.score {
+                None => decision.action.to_string(),
+                Some(score) => format!("{} {:.2}", decision.action, score),
+            };
+
+            let line = lines.entry(index).or_insert(0.0);
+            let (x, y) = {
+                let target = target.borrow();
+                (target.location.x as f32, target.location.y as f32)
+            };
+
+            let offset = Offset {
+                x: x + offset.x,
+                y: y + offset.y - (*line + 1.0) * line_height,
+            };
+            *line += 1.0;
+
+            let (mut draw_list, _) = font_rend.get_draw_list(&text, offset, params.ap_scale);
+            draw_list.set_color(if decision.chosen { color::GREEN } else { color::LIGHT_GRAY });
+            draw_list.set_scale(scale);
+            renderer.draw(draw_list);
+        }
+    }
+
+    /// Draws a marker above each visible entity that is suspicious of or
+    /// alerted to hidden hostiles
+    fn draw_awareness(
+        &self,
+        renderer: &mut dyn GraphicsRenderer,
+        params: &Params,
+        offset: Offset,
+        scale: Scale,
+    ) {
+        let area_state = GameState::area_state();
+        let area_state = area_state.borrow();
+        let mgr = GameState::turn_manager();
+        let font_rend = LineRenderer::new(&params.font);
+
+        for entity in mgr.borrow().entity_iter() {
+            let entity = entity.borrow();
+            if entity.is_party_member() || !entity.location.is_in(&area_state) {
+                continue;
+            }
+
+            let (text, color) = match entity.awareness() {
+                Awareness::Unaware => continue,
+                Awareness::Suspicious => ("?", color::YELLOW),
+                Awareness::Alerted => ("!", color::RED),
+            };
+
+            let (x, y) = (entity.location.x, entity.location.y);
+            if !area_state.is_pc_visible(x, y) {
+                continue;
+            }
+
+            let font = &params.font;
+            let width = params.scale * font.get_width(text) as f32 / font.line_height as f32;
+            let offset = Offset {
+                x: x as f32 + (entity.size.width as f32 - width) / 2.0 + offset.x,
+                y: y as f32 - 1.5 + offset.y,
+            };
+            let (mut draw_list, _) = font_rend.get_draw_list(text, offset, params.scale);
+            draw_list.set_color(color);
+            draw_list.set_scale(scale);
+            renderer.draw(draw_list);
+        }
+    }
+
     pub fn draw_top(
         &self,
         renderer: &mut dyn GraphicsRenderer,
@@ -388,9 +531,16 @@ impl AreaOverlayHandler {
             }
         }
 
+        self.draw_awareness(renderer, params, offset, scale);
+
         if !GameState::is_combat_active() {
             return;
         }
+
+        if AIDecisionTrace::is_enabled() {
+            self.draw_ai_decisions(renderer, params, offset, scale);
+        }
+
         if let Some(ap) = self.path_ap {
             let font_rend = LineRenderer::new(&params.font);
             let text = format!("{} AP", Module::rules().format_ap(ap));
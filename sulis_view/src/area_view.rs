@@ -22,7 +22,7 @@ use std::rc::Rc;
 use std::time;
 
 use sulis_core::config::Config;
-use sulis_core::extern_image::ImageBuffer;
+use sulis_core::extern_image::{imageops, ImageBuffer, Rgba};
 use sulis_core::image::Image;
 use sulis_core::io::event::ClickKind;
 use sulis_core::io::*;
@@ -78,6 +78,8 @@ const ENTITY_TEX_ID: &str = "__entities__";
 const VISIBILITY_TEX_ID: &str = "__visibility__";
 const BASE_LAYER_ID: &str = "__base_layer__";
 const AERIAL_LAYER_ID: &str = "__aerial_layer__";
+const THUMBNAIL_TEX_ID: &str = "__save_thumbnail__";
+const THUMBNAIL_SIZE: (u32, u32) = (192, 108);
 
 impl AreaView {
     pub fn new(scroll: Scrollable) -> Rc<RefCell<AreaView>> {
@@ -348,6 +350,64 @@ impl AreaView {
         color: Color,
     ) {
         let p = widget.state.inner_position();
+        let mut draw_list = self.layer_draw_list(p, id, color);
+        draw_list.set_scale(scale);
+        renderer.draw(draw_list);
+    }
+
+    /// Renders the currently visible portion of the area into a small image,
+    /// suitable for display as a save game thumbnail.  Returns `None` if the
+    /// renderer is unable to read back textures.
+    pub fn draw_thumbnail(
+        &self,
+        renderer: &mut dyn GraphicsRenderer,
+    ) -> Option<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+        if self.cache_invalid {
+            return None;
+        }
+
+        // render with at least one pixel per texel so the layer textures are
+        // sampled at their base level, then downscale
+        let (ui_x, ui_y) = Config::ui_size();
+        let (scale_x, scale_y) = self.scale;
+        let width = (ui_x as f32 / scale_x * TILE_SIZE as f32) as u32;
+        let height = (ui_y as f32 / scale_y * TILE_SIZE as f32) as u32;
+        let width = width.clamp(1, TILE_CACHE_TEXTURE_SIZE);
+        let height = height.clamp(1, TILE_CACHE_TEXTURE_SIZE);
+
+        renderer.register_texture(
+            THUMBNAIL_TEX_ID,
+            ImageBuffer::new(width, height),
+            TextureMinFilter::Linear,
+            TextureMagFilter::Linear,
+        );
+
+        let area_state = GameState::area_state();
+        let state = area_state.borrow();
+        let rules = Module::rules();
+        let time = GameState::turn_manager().borrow().current_time();
+        let area_color = rules.get_area_color(state.area.area.location_kind, time);
+
+        let scale = Scale {
+            x: scale_x,
+            y: scale_y,
+        };
+        let mut layers = vec![(BASE_LAYER_ID, area_color), (AERIAL_LAYER_ID, area_color)];
+        if Config::debug().limit_line_of_sight {
+            layers.push((VISIBILITY_TEX_ID, color::WHITE));
+        }
+        for (id, color) in layers.iter() {
+            let mut draw_list = self.layer_draw_list(Point::new(0, 0), id, *color);
+            draw_list.set_scale(scale);
+            renderer.draw_to_texture(THUMBNAIL_TEX_ID, draw_list);
+        }
+
+        let image = renderer.texture_image(THUMBNAIL_TEX_ID)?;
+        let (thumb_x, thumb_y) = THUMBNAIL_SIZE;
+        Some(imageops::thumbnail(&image, thumb_x, thumb_y))
+    }
+
+    fn layer_draw_list(&self, p: Point, id: &str, color: Color) -> DrawList {
         let rect = Rect {
             x: p.x as f32 - self.scroll.x(),
             y: p.y as f32 - self.scroll.y(),
@@ -355,9 +415,8 @@ impl AreaView {
             h: (TILE_CACHE_TEXTURE_SIZE / TILE_SIZE) as f32,
         };
         let mut draw_list = DrawList::from_texture_id(id, &TEX_COORDS, rect);
-        draw_list.set_scale(scale);
         draw_list.set_color(color);
-        renderer.draw(draw_list);
+        draw_list
     }
 
     fn draw_entities_props(
@@ -617,14 +676,17 @@ impl WidgetKind for AreaView {
         self.feedback_text_params.heal_color =
             theme.get_custom_or_default("feedback_text_heal_color", color::BLUE);
 
+        // damage kind colors and icons are defined in the rules, but the
+        // colors may be overridden by the theme
+        self.feedback_text_params.damage_colors.clear();
+        self.feedback_text_params.damage_icons.clear();
         for kind in DamageKind::iter() {
-            let id = format!(
-                "feedback_text_damage_{}_color",
-                kind.to_str().to_lowercase()
-            );
-            let index = kind.index();
-            self.feedback_text_params.damage_colors[index] =
-                theme.get_custom_or_default(&id, color::LIGHT_GRAY);
+            let id = format!("feedback_text_damage_{}_color", kind.id().to_lowercase());
+            let color = theme.get_custom_or_default(&id, kind.color());
+            self.feedback_text_params.damage_colors.push(color);
+
+            let icon = kind.icon().and_then(|icon| ResourceSet::image(&icon));
+            self.feedback_text_params.damage_icons.push(icon);
         }
 
         if let Some(image_id) = theme.custom.get("feedback_icon_concealment") {
@@ -648,6 +710,13 @@ impl WidgetKind for AreaView {
             self.feedback_text_params.graze_icon = ResourceSet::image_else_empty(image_id);
         }
 
+        if let Some(image_id) = theme.custom.get("targeter_icon_partial_cover") {
+            self.feedback_text_params.partial_cover_icon = ResourceSet::image_else_empty(image_id);
+        }
+        if let Some(image_id) = theme.custom.get("targeter_icon_full_cover") {
+            self.feedback_text_params.full_cover_icon = ResourceSet::image_else_empty(image_id);
+        }
+
         if self.targeter_tile.is_none() {
             warn!("No targeter tile specified for Areaview");
         }
@@ -807,6 +876,11 @@ impl WidgetKind for AreaView {
             renderer.draw(draw_list);
         }
 
+        if let Some(mut draw_list) = self.overlay_handler.get_ai_path_draw_list(offset, millis) {
+            draw_list.set_scale(scale);
+            renderer.draw(draw_list);
+        }
+
         let mut draw_list = DrawList::empty_sprite();
         for transition in state.area.transitions.iter() {
             draw_list.set_scale(scale);
@@ -126,7 +126,7 @@ fn add_bonus(
 ) {
     use sulis_module::BonusKind::*;
     match &bonus.kind {
-        Attribute { attribute, amount } => add(state, attribute.short_name(), amount),
+        Attribute { attribute, amount } => add(state, &attribute.short_name(), amount),
         ActionPoints(amount) => add(state, "action_points", Module::rules().format_ap(*amount)),
         Armor(amount) => armor.add_base(*amount),
         ArmorKind { kind, amount } => armor.add_kind(*kind, *amount),
@@ -171,6 +171,8 @@ fn add_bonus(
         Will(amount) => add(state, "will", amount),
         Concealment(amount) => add(state, "concealment", amount),
         ConcealmentIgnore(amount) => add(state, "concealment_ignore", amount),
+        Stealth(amount) => add(state, "stealth", amount),
+        Perception(amount) => add(state, "perception", amount),
         CritChance(amount) => add(state, "crit_chance", amount),
         HitThreshold(amount) => add(state, "hit_threshold", amount),
         GrazeThreshold(amount) => add(state, "graze_threshold", amount),
@@ -235,6 +237,17 @@ fn add_bonus(
         FlankedImmunity => add(state, "flanked_immunity", true),
         SneakAttackImmunity => add(state, "sneak_attack_immunity", true),
         CritImmunity => add(state, "crit_immunity", true),
+        ConditionImmunity(id) => {
+            let name = match Module::condition(id) {
+                None => id.to_string(),
+                Some(condition) => condition.name.to_string(),
+            };
+            let names = match state.get_text_arg("condition_immunities") {
+                None => name,
+                Some(names) => format!("{}, {}", names, name),
+            };
+            add(state, "condition_immunities", names);
+        }
     }
 }
 
@@ -322,13 +335,13 @@ pub fn add_bonus_text_args(bonuses: &BonusList, widget_state: &mut WidgetState)
     }
 
     for kind in DamageKind::iter() {
-        if !armor.differs_from_base(*kind) {
+        if !armor.differs_from_base(kind) {
             continue;
         }
         add(
             widget_state,
             &format!("armor_{}", kind).to_lowercase(),
-            armor.amount(*kind),
+            armor.amount(kind),
         );
     }
 }
@@ -60,7 +60,7 @@ impl AttributeSelectorPane {
 
         let mut total = 0;
         for attr in Attribute::iter() {
-            total += self.attrs.get(*attr) as i32;
+            total += self.attrs.get(attr) as i32;
         }
         self.available = rules.builder_attribute_points - total;
     }
@@ -84,7 +84,7 @@ impl BuilderPane for AttributeSelectorPane {
 
         if let Some(ref class) = self.selected_class {
             self.selected_kit = Some(0);
-            self.attrs = class.kits[0].default_attributes;
+            self.attrs = class.kits[0].default_attributes.clone();
         }
 
         builder.attributes = None;
@@ -111,7 +111,7 @@ impl BuilderPane for AttributeSelectorPane {
         };
 
         builder.kit = self.selected_kit;
-        builder.attributes = Some(self.attrs);
+        builder.attributes = Some(self.attrs.clone());
         builder.inventory = Some(kit.starting_inventory.clone());
         builder.next(&widget);
     }
@@ -172,7 +172,7 @@ impl WidgetKind for AttributeSelectorPane {
                 .add_callback(Callback::new(Rc::new(move |widget, _| {
                     let (parent, pane) = Widget::parent_mut::<AttributeSelectorPane>(widget);
                     pane.selected_kit = Some(index);
-                    pane.attrs = class_ref.kits[index].default_attributes;
+                    pane.attrs = class_ref.kits[index].default_attributes.clone();
                     pane.set_next_enabled(&parent);
 
                     parent.borrow_mut().invalidate_children();
@@ -218,8 +218,11 @@ impl WidgetKind for AttributeSelectorPane {
             }
         }
 
+        let attributes = Widget::empty("attributes");
         for attr in Attribute::iter() {
-            let value = self.attrs.get(*attr) as i32;
+            let row = Widget::empty("attribute");
+
+            let value = self.attrs.get(attr) as i32;
             let max = if self.available > 0 {
                 rules.builder_max_attribute
             } else {
@@ -227,8 +230,8 @@ impl WidgetKind for AttributeSelectorPane {
             };
 
             let spinner = Spinner::new(value, rules.builder_min_attribute, max);
-            let widget = Widget::with_theme(spinner, &format!("{}_spinner", attr.short_name()));
-            widget
+            let spinner = Widget::with_theme(spinner, "spinner");
+            spinner
                 .borrow_mut()
                 .state
                 .add_callback(Callback::new(Rc::new(move |widget, kind| {
@@ -236,30 +239,35 @@ impl WidgetKind for AttributeSelectorPane {
 
                     let (parent, pane) = Widget::parent_mut::<AttributeSelectorPane>(widget);
                     parent.borrow_mut().invalidate_children();
-                    pane.attrs.set(*attr, value as u8);
+                    pane.attrs.set(attr, value as u8);
                     pane.set_next_enabled(&parent);
                 })));
-            children.push(widget);
 
-            let label = Widget::with_theme(Label::empty(), &format!("{}_label", attr.short_name()));
-            children.push(label);
+            let label = Widget::with_theme(Label::empty(), "label");
+            {
+                let state = &mut label.borrow_mut().state;
+                state.add_text_arg("name", &attr.name());
+                state.add_text_arg("description", &attr.description());
+            }
 
-            let bonus = Widget::with_theme(Label::empty(), &format!("{}_bonus", attr.short_name()));
-            let bonus_value = *attr_bonuses.get(attr).unwrap_or(&0);
+            let bonus = Widget::with_theme(Label::empty(), "bonus");
+            let bonus_value = *attr_bonuses.get(&attr).unwrap_or(&0);
             bonus
                 .borrow_mut()
                 .state
                 .add_text_arg("value", &bonus_value.to_string());
-            children.push(bonus);
 
             let total_value = bonus_value as i32 + value;
-            let total = Widget::with_theme(Label::empty(), &format!("{}_total", attr.short_name()));
+            let total = Widget::with_theme(Label::empty(), "total");
             total
                 .borrow_mut()
                 .state
                 .add_text_arg("value", &total_value.to_string());
-            children.push(total);
+
+            Widget::add_children_to(&row, vec![label, spinner, bonus, total]);
+            Widget::add_child_to(&attributes, row);
         }
+        children.push(attributes);
 
         let points_label = Widget::with_theme(Label::empty(), "points_label");
         points_label
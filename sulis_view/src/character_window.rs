@@ -247,7 +247,7 @@ fn export_character(pc: &ActorState) {
         race: Some(pc.actor.race.id.to_string()),
         inline_race: None,
         sex: Some(pc.actor.sex),
-        attributes: pc.actor.attributes,
+        attributes: pc.actor.attributes.clone(),
         faction: Some(pc.actor.faction()),
         conversation: None,
         images: pc.actor.builder_images.clone(),
@@ -453,8 +453,8 @@ pub fn create_details_text_box(pc: &ActorState, is_pc: bool) -> Rc<RefCell<Widge
 
         for attribute in Attribute::iter() {
             state.add_text_arg(
-                attribute.short_name(),
-                &stats.attributes.get(*attribute).to_string(),
+                &attribute.short_name(),
+                &stats.attributes.get(attribute).to_string(),
             )
         }
 
@@ -554,18 +554,18 @@ pub fn create_details_text_box(pc: &ActorState, is_pc: bool) -> Rc<RefCell<Widge
 
         state.add_text_arg("armor", &stats.armor.base().to_string());
         for kind in DamageKind::iter() {
-            if !stats.armor.differs_from_base(*kind) {
+            if !stats.armor.differs_from_base(kind) {
                 continue;
             }
 
             state.add_text_arg(
                 &format!("armor_{}", kind).to_lowercase(),
-                &stats.armor.amount(*kind).to_string(),
+                &stats.armor.amount(kind).to_string(),
             );
         }
 
         for kind in DamageKind::iter() {
-            let amount = stats.resistance.amount(*kind);
+            let amount = stats.resistance.amount(kind);
             if amount == 0 {
                 continue;
             }
@@ -0,0 +1,192 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sulis_core::ui::{Callback, Widget, WidgetKind};
+use sulis_core::widgets::{Button, Label, ScrollDirection, ScrollPane, TextArea};
+use sulis_module::{Cover, HitKind, ROUND_TIME_MILLIS};
+use sulis_state::{ChangeListener, CombatLogEntry, GameState};
+
+pub const NAME: &str = "combat_log_window";
+
+pub struct CombatLogWindow {
+    // index of the party member to show attacks for, or all attacks if None
+    filter: Option<usize>,
+}
+
+impl CombatLogWindow {
+    pub fn new() -> Rc<RefCell<CombatLogWindow>> {
+        Rc::new(RefCell::new(CombatLogWindow { filter: None }))
+    }
+}
+
+impl WidgetKind for CombatLogWindow {
+    widget_kind!(NAME);
+
+    fn on_add(&mut self, widget: &Rc<RefCell<Widget>>) -> Vec<Rc<RefCell<Widget>>> {
+        let log = GameState::combat_log();
+        log.borrow_mut()
+            .listeners
+            .add(ChangeListener::invalidate(NAME, widget));
+
+        let close = Widget::with_theme(Button::empty(), "close");
+        close
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(|widget, _| {
+                let (parent, _) = Widget::parent::<CombatLogWindow>(widget);
+                parent.borrow_mut().mark_for_removal();
+            })));
+
+        let title = Widget::with_theme(Label::empty(), "title");
+
+        let filters = Widget::empty("filters");
+        let all_button = Widget::with_theme(Button::empty(), "all_button");
+        all_button.borrow_mut().state.set_active(self.filter.is_none());
+        all_button
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(|widget, _| {
+                let (parent, window) = Widget::parent_mut::<CombatLogWindow>(widget);
+                window.filter = None;
+                parent.borrow_mut().invalidate_children();
+            })));
+        Widget::add_child_to(&filters, all_button);
+
+        for member in GameState::party() {
+            let member = member.borrow();
+            let index = member.index();
+
+            let button = Widget::with_theme(Button::empty(), "member_button");
+            {
+                let state = &mut button.borrow_mut().state;
+                state.add_text_arg("name", &member.actor.actor.name);
+                state.set_active(self.filter == Some(index));
+            }
+            button
+                .borrow_mut()
+                .state
+                .add_callback(Callback::new(Rc::new(move |widget, _| {
+                    let (parent, window) = Widget::parent_mut::<CombatLogWindow>(widget);
+                    window.filter = Some(index);
+                    parent.borrow_mut().invalidate_children();
+                })));
+            Widget::add_child_to(&filters, button);
+        }
+
+        let entries_pane = ScrollPane::new(ScrollDirection::Vertical);
+        let entries = Widget::with_theme(entries_pane.clone(), "entries");
+
+        let log = log.borrow();
+        for entry in log.iter().rev() {
+            if let Some(index) = self.filter {
+                if !entry.involves(index) {
+                    continue;
+                }
+            }
+
+            let widget = Widget::with_theme(TextArea::empty(), "entry");
+            add_entry_text_args(&mut widget.borrow_mut(), entry);
+            entries_pane.borrow().add_to_content(widget);
+        }
+
+        vec![close, title, filters, entries]
+    }
+}
+
+fn add_entry_text_args(widget: &mut Widget, entry: &CombatLogEntry) {
+    let state = &mut widget.state;
+
+    let round = entry.time.round + entry.time.millis / ROUND_TIME_MILLIS;
+    state.add_text_arg("hour", &entry.time.hour.to_string());
+    state.add_text_arg("round", &round.to_string());
+    state.add_text_arg("attacker", &entry.attacker_name);
+    state.add_text_arg("target", &entry.target_name);
+
+    let hit_kind = match entry.hit_kind {
+        HitKind::Miss => "miss",
+        HitKind::Graze => "graze",
+        HitKind::Hit => "hit",
+        HitKind::Crit => "crit",
+        HitKind::Auto => "auto",
+    };
+    state.add_text_arg(hit_kind, "true");
+
+    if entry.hit_flags.flanking {
+        state.add_text_arg("flanking", "true");
+    }
+    if entry.hit_flags.sneak_attack {
+        state.add_text_arg("sneak_attack", "true");
+    }
+    match entry.cover {
+        Cover::None => (),
+        Cover::Partial => state.add_text_arg("partial_cover", "true"),
+        Cover::Full => state.add_text_arg("full_cover", "true"),
+    }
+    if entry.elevation > 0 {
+        state.add_text_arg("higher_ground", "true");
+    } else if entry.elevation < 0 {
+        state.add_text_arg("lower_ground", "true");
+    }
+    if entry.hit_flags.concealment {
+        state.add_text_arg("concealment_miss", "true");
+    }
+    if entry.concealment > 0 {
+        state.add_text_arg("concealment", &entry.concealment.to_string());
+    }
+
+    if let Some(roll) = entry.roll {
+        state.add_text_arg("roll", &roll.roll.to_string());
+        state.add_text_arg("accuracy_kind", &format!("{:?}", roll.accuracy_kind));
+        state.add_text_arg("accuracy", &roll.accuracy.to_string());
+        state.add_text_arg("defense", &roll.defense.to_string());
+        state.add_text_arg("result", &roll.result().to_string());
+        state.add_text_arg("graze_threshold", &roll.graze_threshold.to_string());
+        state.add_text_arg("hit_threshold", &roll.hit_threshold.to_string());
+        if let Some(crit_roll) = roll.crit_roll {
+            state.add_text_arg("crit_roll", &crit_roll.to_string());
+        }
+    }
+
+    // components with no rolled damage are only placeholders
+    let damage: Vec<String> = entry
+        .damage
+        .iter()
+        .filter(|damage| damage.rolled > 0)
+        .map(|damage| {
+            format!(
+                "{}: {} x{:.2}, {}% resisted = {:.1}, {:.1} armor = {}",
+                damage.kind,
+                damage.rolled,
+                damage.multiplier,
+                damage.resistance,
+                damage.before_armor,
+                damage.armor,
+                damage.amount
+            )
+        })
+        .collect();
+
+    if damage.is_empty() {
+        return;
+    }
+
+    state.add_text_arg("damage", &damage.join("\n"));
+    state.add_text_arg("total_damage", &entry.total_damage().to_string());
+}
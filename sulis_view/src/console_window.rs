@@ -67,6 +67,10 @@ impl ConsoleWindow {
         self.output.borrow_mut().state.text = result;
     }
 
+    pub fn set_output(&mut self, text: &str) {
+        self.output.borrow_mut().state.text = text.to_string();
+    }
+
     pub fn current_history_text(&self) -> String {
         if self.history_index >= self.history.len() {
             return "".to_string();
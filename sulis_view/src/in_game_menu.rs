@@ -22,7 +22,7 @@ use sulis_core::ui::{Callback, Widget, WidgetKind};
 use sulis_core::widgets::{Button, ConfirmationWindow};
 use sulis_state::GameState;
 
-use crate::{LoadWindow, RootView};
+use crate::{LoadWindow, SaveWindow};
 
 const NAME: &str = "in_game_menu";
 
@@ -61,8 +61,10 @@ impl WidgetKind for InGameMenu {
                 let (parent, _) = Widget::parent::<InGameMenu>(widget);
                 parent.borrow_mut().mark_for_removal();
 
-                let (_, view) = Widget::parent_mut::<RootView>(&parent);
-                view.save();
+                let root = Widget::get_root(widget);
+                let window = Widget::with_defaults(SaveWindow::new());
+                window.borrow_mut().state.set_modal(true);
+                Widget::add_child_to(&root, window);
             })));
         save.borrow_mut()
             .state
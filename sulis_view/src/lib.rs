@@ -31,6 +31,9 @@ pub use self::ability_pane::AbilityPane;
 mod action_kind;
 pub use self::action_kind::ActionKind;
 
+mod ai_decision_panel;
+pub use self::ai_decision_panel::AIDecisionPanel;
+
 mod ap_bar;
 pub use self::ap_bar::ApBar;
 
@@ -54,6 +57,9 @@ pub use self::character_window::CharacterWindow;
 mod class_pane;
 pub use self::class_pane::ClassPane;
 
+mod combat_log_window;
+pub use self::combat_log_window::CombatLogWindow;
+
 mod console_window;
 pub use self::console_window::ConsoleWindow;
 
@@ -118,6 +124,9 @@ pub use self::race_pane::RacePane;
 mod root_view;
 pub use self::root_view::RootView;
 
+mod save_window;
+pub use self::save_window::SaveWindow;
+
 mod screen_shake;
 pub use self::screen_shake::ScreenShake;
 
@@ -16,9 +16,13 @@
 
 use std::any::Any;
 use std::cell::RefCell;
+use std::path::PathBuf;
 use std::rc::Rc;
 
+use sulis_core::extern_image;
+use sulis_core::io::{DrawList, GraphicsRenderer, TextureMagFilter, TextureMinFilter};
 use sulis_core::ui::{Callback, Widget, WidgetKind};
+use sulis_core::util::{Point, Rect};
 use sulis_core::widgets::{
     Button, ConfirmationWindow, Label, ScrollDirection, ScrollPane, TextArea,
 };
@@ -29,6 +33,8 @@ use crate::{main_menu::MainMenu, LoadingScreen, RootView};
 
 const NAME: &str = "load_window";
 
+const TEX_COORDS: [f32; 8] = [0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0, 0.0];
+
 pub struct LoadWindow {
     accept: Rc<RefCell<Widget>>,
     delete: Rc<RefCell<Widget>>,
@@ -43,7 +49,7 @@ impl LoadWindow {
         let accept = Widget::with_theme(Button::empty(), "accept");
         let cancel = Widget::with_theme(Button::empty(), "cancel");
         let delete = Widget::with_theme(Button::empty(), "delete");
-        let entries = match get_available_save_files() {
+        let mut entries = match get_available_save_files() {
             Ok(files) => files,
             Err(e) => {
                 warn!("Unable to read saved files");
@@ -52,6 +58,9 @@ impl LoadWindow {
             }
         };
 
+        // group autosaves after the manual saves, keeping the most recent first
+        entries.sort_by_key(|meta| meta.autosave);
+
         Rc::new(RefCell::new(LoadWindow {
             accept,
             delete,
@@ -185,10 +194,21 @@ impl WidgetKind for LoadWindow {
         let entries = Widget::with_theme(scrollpane.clone(), "entries");
 
         for (index, meta) in self.entries.iter().enumerate() {
+            if meta.autosave && (index == 0 || !self.entries[index - 1].autosave) {
+                let header = Widget::with_theme(Label::empty(), "autosaves_header");
+                scrollpane.borrow().add_to_content(header);
+            }
+
             let text_area = Widget::with_defaults(TextArea::empty());
             {
                 let area = &mut text_area.borrow_mut().state;
+                if let Some(name) = &meta.name {
+                    area.add_text_arg("name", name);
+                }
                 area.add_text_arg("player_name", &meta.player_name);
+                if meta.play_time_millis > 0 {
+                    area.add_text_arg("play_time", &format_play_time(meta.play_time_millis));
+                }
                 area.add_text_arg("datetime", &meta.datetime);
                 area.add_text_arg("current_area_name", &meta.current_area_name);
 
@@ -228,6 +248,19 @@ impl WidgetKind for LoadWindow {
                 }
             }
 
+            if let Some(path) = meta.thumbnail_path() {
+                let thumbnail = SaveThumbnail::new(path, index);
+                Widget::add_child_to(&widget, Widget::with_theme(thumbnail, "thumbnail"));
+            }
+
+            let portraits = Widget::empty("portraits");
+            for image in meta.party_portraits.iter() {
+                let portrait = Widget::with_theme(Label::empty(), "portrait");
+                portrait.borrow_mut().state.add_text_arg("image", image);
+                Widget::add_child_to(&portraits, portrait);
+            }
+
+            Widget::add_child_to(&widget, portraits);
             Widget::add_child_to(&widget, text_area);
             scrollpane.borrow().add_to_content(widget);
         }
@@ -243,3 +276,80 @@ impl WidgetKind for LoadWindow {
         ]
     }
 }
+
+fn format_play_time(millis: usize) -> String {
+    let minutes = millis / 60_000;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+// Displays the thumbnail image stored alongside a save file.  The image is
+// read and uploaded as a texture the first time this widget is drawn.
+struct SaveThumbnail {
+    path: PathBuf,
+    texture_id: String,
+    state: ThumbnailState,
+}
+
+enum ThumbnailState {
+    Unloaded,
+    Loaded,
+    Error,
+}
+
+impl SaveThumbnail {
+    fn new(path: PathBuf, index: usize) -> Rc<RefCell<SaveThumbnail>> {
+        Rc::new(RefCell::new(SaveThumbnail {
+            path,
+            texture_id: format!("__load_thumbnail_{}__", index),
+            state: ThumbnailState::Unloaded,
+        }))
+    }
+
+    fn load(&mut self, renderer: &mut dyn GraphicsRenderer) {
+        let image = match extern_image::open(&self.path) {
+            Ok(image) => image,
+            Err(e) => {
+                warn!("Unable to read save thumbnail {:?}", self.path);
+                warn!("{}", e);
+                self.state = ThumbnailState::Error;
+                return;
+            }
+        };
+
+        // the texture ID is reused for a different save each time the
+        // window is opened, so always replace any existing texture
+        renderer.register_texture(
+            &self.texture_id,
+            image.to_rgba8(),
+            TextureMinFilter::Linear,
+            TextureMagFilter::Linear,
+        );
+        self.state = ThumbnailState::Loaded;
+    }
+}
+
+impl WidgetKind for SaveThumbnail {
+    widget_kind!("save_thumbnail");
+
+    fn draw(
+        &mut self,
+        renderer: &mut dyn GraphicsRenderer,
+        _pixel_size: Point,
+        widget: &Widget,
+        _millis: u32,
+    ) {
+        if let ThumbnailState::Unloaded = self.state {
+            self.load(renderer);
+        }
+
+        if let ThumbnailState::Loaded = self.state {
+            let rect = Rect {
+                x: widget.state.inner_left() as f32,
+                y: widget.state.inner_top() as f32,
+                w: widget.state.inner_width() as f32,
+                h: widget.state.inner_height() as f32,
+            };
+            renderer.draw(DrawList::from_texture_id(&self.texture_id, &TEX_COORDS, rect));
+        }
+    }
+}
@@ -117,12 +117,21 @@ impl WidgetKind for PortraitView {
         let icons = Widget::empty("icons");
         let mgr = GameState::turn_manager();
         let mgr = mgr.borrow();
+        let mut conditions_shown = Vec::new();
         for index in entity.actor.effects_iter() {
             let effect = match mgr.effect_checked(*index) {
                 None => continue,
                 Some(effect) => effect,
             };
 
+            // show stacked conditions only once
+            if let Some(condition) = effect.condition() {
+                if conditions_shown.contains(&condition) {
+                    continue;
+                }
+                conditions_shown.push(condition);
+            }
+
             let icon = match effect.icon() {
                 None => continue,
                 Some(icon) => icon,
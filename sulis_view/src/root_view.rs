@@ -15,31 +15,33 @@
 //  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
 
 use std::collections::HashMap;
-use std::{any::Any, cell::RefCell, rc::Rc, time::Instant};
+use std::{any::Any, cell::RefCell, io::Error, rc::Rc, time::Instant};
 
 use crate::{
-    character_window, formation_window, inventory_window, merchant_window, prop_window,
-    quest_window, world_map_window, AbilitiesBar, ApBar, AreaView, CharacterWindow, ConsoleWindow,
-    FormationWindow, GameOverWindow, InGameMenu, InitiativeTicker, InventoryWindow, MerchantWindow,
-    PortraitPane, PropWindow, QuestWindow, QuickItemBar, WorldMapWindow,
+    character_window, combat_log_window, formation_window, inventory_window, merchant_window,
+    prop_window, quest_window, world_map_window, AIDecisionPanel, AbilitiesBar, ApBar, AreaView,
+    CharacterWindow, CombatLogWindow, ConsoleWindow, FormationWindow, GameOverWindow, InGameMenu,
+    InitiativeTicker, InventoryWindow, MerchantWindow, PortraitPane, PropWindow, QuestWindow,
+    QuickItemBar, WorldMapWindow,
 };
 use sulis_core::config::Config;
-use sulis_core::io::{keyboard_event::Key, InputActionKind};
+use sulis_core::io::{keyboard_event::Key, GraphicsRenderer, InputActionKind};
 use sulis_core::ui::{Callback, Cursor, Scrollable, Widget, WidgetKind};
-use sulis_core::util;
+use sulis_core::util::{self, Point};
 use sulis_core::widgets::{Button, ConfirmationWindow, Label};
 use sulis_module::{area::OnRest, Module};
 use sulis_state::{
-    area_feedback_text::ColorKind, save_file::create_save, script::script_callback,
-    script::ScriptEntity, AreaFeedbackText, ChangeListener, EntityState, GameState, NextGameStep,
-    Script,
+    area_feedback_text::ColorKind, hot_reload, save_file::create_save, script::script_cache,
+    script::script_callback, script::ScriptEntity, AreaFeedbackText, ChangeListener, EntityState,
+    GameState, NextGameStep, Reaction, Script,
 };
 
-const WINDOW_NAMES: [&str; 7] = [
+const WINDOW_NAMES: [&str; 8] = [
     self::formation_window::NAME,
     self::inventory_window::NAME,
     self::character_window::NAME,
     self::quest_window::NAME,
+    self::combat_log_window::NAME,
     self::world_map_window::NAME,
     self::merchant_window::NAME,
     self::prop_window::NAME,
@@ -47,6 +49,12 @@ const WINDOW_NAMES: [&str; 7] = [
 
 const NAME: &str = "game";
 
+// A save is deferred until the next draw, so a thumbnail of the area
+// can be rendered along with it
+struct PendingSave {
+    name: Option<String>,
+}
+
 pub struct RootView {
     pub(crate) next_step: Option<NextGameStep>,
     status: Rc<RefCell<Widget>>,
@@ -61,6 +69,7 @@ pub struct RootView {
     area: String,
 
     scroll_keys_down: Vec<InputActionKind>,
+    pending_save: Option<PendingSave>,
 }
 
 impl RootView {
@@ -103,6 +112,7 @@ impl RootView {
             quick_item_bar: None,
             abilities_bar: None,
             scroll_keys_down: Vec::new(),
+            pending_save: None,
         }))
     }
 
@@ -180,12 +190,45 @@ impl RootView {
         }
     }
 
+    fn report_script_reload(&mut self, report: script_cache::ReloadReport) {
+        if report.errors.is_empty() {
+            self.add_status_text(&format!("Reloaded {} script(s).", report.reloaded.len()));
+            return;
+        }
+
+        // show the errors without taking keyboard focus away from the game
+        self.console.borrow_mut().set_output(&report.errors.join("\n"));
+        self.console_widget.borrow_mut().state.set_visible(true);
+        self.add_status_text("Error reloading scripts, see console.");
+    }
+
+    fn report_resource_reload(&mut self, result: Result<usize, Error>, root: &Rc<RefCell<Widget>>) {
+        match result {
+            Ok(changed) => {
+                self.add_status_text(&format!("Reloaded {} changed resource(s).", changed));
+                // rebuild the UI so it picks up the reloaded resources
+                root.borrow_mut().invalidate_children();
+            }
+            Err(e) => {
+                self.console.borrow_mut().set_output(&e.to_string());
+                self.console_widget.borrow_mut().state.set_visible(true);
+                self.add_status_text("Error reloading resources, see console.");
+            }
+        }
+    }
+
     pub fn set_quest_window(&mut self, widget: &Rc<RefCell<Widget>>, desired_state: bool) {
         self.set_window(widget, self::quest_window::NAME, desired_state, &|| {
             Some(QuestWindow::new())
         });
     }
 
+    pub fn set_combat_log_window(&mut self, widget: &Rc<RefCell<Widget>>, desired_state: bool) {
+        self.set_window(widget, self::combat_log_window::NAME, desired_state, &|| {
+            Some(CombatLogWindow::new())
+        });
+    }
+
     pub fn set_formation_window(&mut self, widget: &Rc<RefCell<Widget>>, desired_state: bool) {
         self.set_window(widget, self::formation_window::NAME, desired_state, &|| {
             Some(FormationWindow::new())
@@ -270,6 +313,11 @@ impl RootView {
         self.set_quest_window(widget, desired_state);
     }
 
+    pub fn toggle_combat_log_window(&mut self, widget: &Rc<RefCell<Widget>>) {
+        let desired_state = !Widget::has_child_with_name(widget, self::combat_log_window::NAME);
+        self.set_combat_log_window(widget, desired_state);
+    }
+
     pub fn toggle_map_window(&mut self, widget: &Rc<RefCell<Widget>>) {
         let desired_state = !Widget::has_child_with_name(widget, self::world_map_window::NAME);
         self.set_map_window(widget, desired_state, false);
@@ -302,6 +350,34 @@ impl RootView {
         Widget::add_child_to(widget, window);
     }
 
+    /// Asks the player whether a party member should make the specified
+    /// reaction.  Declining still uses up the reaction.
+    fn show_reaction_prompt(&self, widget: &Rc<RefCell<Widget>>, reaction: Reaction) {
+        let reactor = reaction.reactor.borrow().actor.actor.name.to_string();
+        let target = reaction.target.borrow().actor.actor.name.to_string();
+        let name = reaction.name();
+
+        let accept_cb = Callback::new(Rc::new(move |widget, _| {
+            reaction.clone().accept();
+
+            let (parent, _) = Widget::parent::<ConfirmationWindow>(widget);
+            parent.borrow_mut().mark_for_removal();
+        }));
+
+        let window = ConfirmationWindow::new(accept_cb);
+        {
+            let title = Rc::clone(window.borrow().title());
+            let mut title = title.borrow_mut();
+            title.state.add_text_arg("reactor", &reactor);
+            title.state.add_text_arg("target", &target);
+            title.state.add_text_arg("reaction", &name);
+        }
+
+        let window = Widget::with_theme(window, "reaction_confirmation");
+        window.borrow_mut().state.set_modal(true);
+        Widget::add_child_to(widget, window);
+    }
+
     pub fn end_turn(&self) {
         self.cancel_targeter();
 
@@ -339,13 +415,26 @@ impl RootView {
         }
     }
 
-    pub fn save(&mut self) {
+    /// Saves the game with the specified name, or an unnamed quick save if
+    /// `None`.  The save is written at the next draw.
+    pub fn save(&mut self, name: Option<String>) {
         if GameState::is_combat_active() {
             self.add_status_text("Cannot save during combat.");
             return;
         }
 
-        if let Err(e) = create_save() {
+        self.pending_save = Some(PendingSave { name });
+    }
+
+    fn write_pending_save(&mut self, renderer: &mut dyn GraphicsRenderer) {
+        let save = match self.pending_save.take() {
+            None => return,
+            Some(save) => save,
+        };
+
+        let thumbnail = self.area_view.borrow().draw_thumbnail(renderer);
+
+        if let Err(e) = create_save(save.name, thumbnail) {
             error!("Error quick saving game");
             error!("{}", e);
             self.add_status_text("Error performing Save!");
@@ -366,6 +455,16 @@ impl RootView {
 impl WidgetKind for RootView {
     widget_kind!(NAME);
 
+    fn draw(
+        &mut self,
+        renderer: &mut dyn GraphicsRenderer,
+        _pixel_size: Point,
+        _widget: &Widget,
+        _millis: u32,
+    ) {
+        self.write_pending_save(renderer);
+    }
+
     fn update(&mut self, widget: &Rc<RefCell<Widget>>, millis: u32) {
         let area_state = GameState::area_state();
         let root = Widget::get_root(widget);
@@ -383,8 +482,24 @@ impl WidgetKind for RootView {
             }
         }
 
-        let root = Widget::get_root(widget);
-        let has_modal = root.borrow().has_modal();
+        if let Some(report) = script_cache::poll_for_changes() {
+            self.report_script_reload(report);
+        }
+
+        if let Some(result) = hot_reload::poll_for_changes() {
+            self.report_resource_reload(result, &root);
+        }
+
+        let mut has_modal = root.borrow().has_modal();
+        if !has_modal {
+            // any shown reaction prompt has now been accepted or declined
+            GameState::turn_manager().borrow_mut().close_reaction_prompt();
+            let reaction = GameState::turn_manager().borrow_mut().pop_reaction_prompt();
+            if let Some(reaction) = reaction {
+                self.show_reaction_prompt(&root, reaction);
+                has_modal = true;
+            }
+        }
         GameState::set_modal_locked(has_modal);
 
         let (cx, cy) = (Cursor::get_x(), Cursor::get_y());
@@ -472,12 +587,13 @@ impl WidgetKind for RootView {
             ToggleCharacter => self.toggle_character_window(widget),
             ToggleMap => self.toggle_map_window(widget),
             ToggleJournal => self.toggle_quest_window(widget),
+            ToggleCombatLog => self.toggle_combat_log_window(widget),
             ToggleFormation => self.toggle_formation_window(widget),
             EndTurn => self.end_turn(),
             Rest => self.rest(),
             Exit => self.show_exit(widget),
             SelectAll => GameState::select_party_members(GameState::party()),
-            QuickSave => self.save(),
+            QuickSave => self.save(None),
             ScrollUp | ScrollDown | ScrollRight | ScrollLeft => {
                 self.scroll_keys_down.push(key);
                 self.scroll_keys_down.sort_by(|k1, k2| {
@@ -738,14 +854,20 @@ impl WidgetKind for RootView {
         let ticker = Widget::with_defaults(InitiativeTicker::new());
 
         // area widget must be the first entry in the children list
-        vec![
+        let mut children = vec![
             Rc::clone(&self.area_view_widget),
             bot_pane,
             ap_bar,
             ticker,
             self.status.clone(),
             Rc::clone(&self.console_widget),
-        ]
+        ];
+
+        if Config::debug().show_ai_decisions {
+            children.push(Widget::with_defaults(AIDecisionPanel::new()));
+        }
+
+        children
     }
 }
 
@@ -0,0 +1,113 @@
+//  This file is part of Sulis, a turn based RPG written in Rust.
+//  Copyright 2018 Jared Stephen
+//
+//  Sulis is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  Sulis is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with Sulis.  If not, see <http://www.gnu.org/licenses/>
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sulis_core::ui::{Callback, Widget, WidgetKind};
+use sulis_core::widgets::{Button, InputField, Label};
+
+use crate::RootView;
+
+const NAME: &str = "save_window";
+
+pub struct SaveWindow {
+    name_field: Rc<RefCell<InputField>>,
+    name_field_widget: Rc<RefCell<Widget>>,
+    focus_grabbed: bool,
+}
+
+impl SaveWindow {
+    pub fn new() -> Rc<RefCell<SaveWindow>> {
+        let name_field = InputField::new("");
+        let name_field_widget = Widget::with_theme(name_field.clone(), "name_field");
+
+        Rc::new(RefCell::new(SaveWindow {
+            name_field,
+            name_field_widget,
+            focus_grabbed: false,
+        }))
+    }
+
+    fn save(widget: &Rc<RefCell<Widget>>, text: &str) {
+        let (parent, _) = Widget::parent::<SaveWindow>(widget);
+        let name = text.trim();
+        let name = if name.is_empty() {
+            None
+        } else {
+            Some(name.to_string())
+        };
+
+        Widget::clear_keyboard_focus(widget);
+        parent.borrow_mut().mark_for_removal();
+
+        let root = Widget::get_root(&parent);
+        let view = Widget::kind_mut::<RootView>(&root);
+        view.save(name);
+    }
+}
+
+impl WidgetKind for SaveWindow {
+    widget_kind!(NAME);
+
+    fn update(&mut self, _widget: &Rc<RefCell<Widget>>, _millis: u32) {
+        // the field can only take focus once it has been added to the tree
+        if !self.focus_grabbed {
+            self.focus_grabbed = Widget::grab_keyboard_focus(&self.name_field_widget);
+        }
+    }
+
+    fn on_add(&mut self, _widget: &Rc<RefCell<Widget>>) -> Vec<Rc<RefCell<Widget>>> {
+        let title = Widget::with_theme(Label::empty(), "title");
+        let name_label = Widget::with_theme(Label::empty(), "name_label");
+
+        self.name_field
+            .borrow_mut()
+            .set_enter_callback(Callback::new(Rc::new(|widget, kind| {
+                let field = Widget::downcast::<InputField>(kind);
+                SaveWindow::save(widget, &field.text);
+            })));
+
+        let accept = Widget::with_theme(Button::empty(), "accept");
+        accept
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(|widget, _| {
+                let (_, window) = Widget::parent::<SaveWindow>(widget);
+                let text = window.name_field.borrow().text();
+                SaveWindow::save(widget, &text);
+            })));
+
+        let cancel = Widget::with_theme(Button::empty(), "cancel");
+        cancel
+            .borrow_mut()
+            .state
+            .add_callback(Callback::new(Rc::new(|widget, _| {
+                Widget::clear_keyboard_focus(widget);
+                let (parent, _) = Widget::parent::<SaveWindow>(widget);
+                parent.borrow_mut().mark_for_removal();
+            })));
+
+        vec![
+            title,
+            name_label,
+            self.name_field_widget.clone(),
+            accept,
+            cancel,
+        ]
+    }
+}